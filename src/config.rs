@@ -1,9 +1,9 @@
-use crate::configs::Palette;
+use crate::configs::{Palette, THEME_ROLES};
 use crate::context::Context;
 
 use crate::serde_utils::{ValueDeserializer, ValueRef};
 use crate::utils;
-use nu_ansi_term::Color;
+use nu_ansi_term::{Color, Style};
 use serde::{
     de::value::Error as ValueError, de::Error as SerdeError, Deserialize, Deserializer, Serialize,
 };
@@ -274,6 +274,8 @@ where
  string include the following:
  - 'fg:<color>'    (specifies that the color read should be a foreground color)
  - 'bg:<color>'    (specifies that the color read should be a background color)
+ - 'fg:gradient(<color>, <color>, ...)' (spreads the colors across a text group, see
+   `parse_style_gradient`)
  - 'underline'
  - 'bold'
  - 'italic'
@@ -285,9 +287,9 @@ pub fn parse_style_string(
     style_string: &str,
     context: Option<&Context>,
 ) -> Option<nu_ansi_term::Style> {
-    style_string
-        .split_whitespace()
-        .fold(Some(nu_ansi_term::Style::new()), |maybe_style, token| {
+    style_tokens(style_string).into_iter().fold(
+        Some(nu_ansi_term::Style::new()),
+        |maybe_style, token| {
             maybe_style.and_then(|style| {
                 let token = token.to_lowercase();
 
@@ -317,15 +319,7 @@ pub fn parse_style_string(
                             None // fg:none yields no style.
                         } else {
                             // Either bg or valid color or both.
-                            let parsed = parse_color_string(
-                                color_string,
-                                context.and_then(|x| {
-                                    get_palette(
-                                        &x.root_config.palettes,
-                                        x.root_config.palette.as_deref(),
-                                    )
-                                }),
-                            );
+                            let parsed = parse_themed_color_string(color_string, context);
                             // bg + invalid color = reset the background to default.
                             if !col_fg && parsed.is_none() {
                                 let mut new_style = style;
@@ -345,21 +339,246 @@ pub fn parse_style_string(
                     }
                 }
             })
+        },
+    )
+}
+
+/// Splits a style string into its words, keeping the arguments of color functions together
+fn style_tokens(style_string: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut depth = 0_usize;
+    let mut start = None;
+    for (i, c) in style_string.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            c if c.is_whitespace() && depth == 0 => {
+                if let Some(start) = start.take() {
+                    tokens.push(&style_string[start..i]);
+                }
+                continue;
+            }
+            _ => {}
+        }
+        start.get_or_insert(i);
+    }
+    if let Some(start) = start {
+        tokens.push(&style_string[start..]);
+    }
+    tokens
+}
+
+/// Splits a call of a color function like `mix(red, blue, 0.3)` into its name and arguments
+fn split_color_function(color_string: &str) -> Option<(&str, Vec<&str>)> {
+    let (name, args) = color_string.split_once('(')?;
+    let args = args.strip_suffix(')')?;
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+
+    let mut split = Vec::new();
+    let mut depth = 0_usize;
+    let mut start = 0;
+    for (i, c) in args.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                split.push(args[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    split.push(args[start..].trim());
+    Some((name, split))
+}
+
+/// Parses an amount like `20%` or `0.2` as a fraction between 0 and 1
+fn parse_amount(amount: &str) -> Option<f64> {
+    let amount = match amount.strip_suffix('%') {
+        Some(percent) => percent.trim().parse::<f64>().ok()? / 100.0,
+        None => amount.parse::<f64>().ok()?,
+    };
+    (0.0..=1.0).contains(&amount).then_some(amount)
+}
+
+/// Parses a call of a color function, where `parse_color` parses the colors among its
+/// arguments. Returns None if the string is not a call of a color function.
+///
+/// - `lighten(<color>, <amount>)` and `darken(<color>, <amount>)` mix the color with white or black
+/// - `mix(<color>, <color>, <amount>)` mixes the colors, from the first at 0 to the second at 1
+/// - `gradient(<color>, <color>, ...)` is the first color outside of text groups
+fn parse_color_function(
+    color_string: &str,
+    parse_color: &dyn Fn(&str) -> Option<Color>,
+) -> Option<Option<Color>> {
+    let (name, args) = split_color_function(color_string)?;
+    let rgb = |i: usize| parse_color(args.get(i)?).and_then(color_to_rgb);
+    let amount = |i: usize| parse_amount(args.get(i)?);
+
+    let color = match (name, args.len()) {
+        ("lighten", 2) => rgb(0)
+            .zip(amount(1))
+            .map(|(color, amount)| mix_rgb(color, (255, 255, 255), amount)),
+        ("darken", 2) => rgb(0)
+            .zip(amount(1))
+            .map(|(color, amount)| mix_rgb(color, (0, 0, 0), amount)),
+        ("mix", 3) => rgb(0)
+            .zip(rgb(1))
+            .zip(amount(2))
+            .map(|((first, second), amount)| mix_rgb(first, second, amount)),
+        ("gradient", 2..) => parse_color(args[0]),
+        _ => None,
+    };
+    if color.is_none() {
+        log::debug!("Could not parse color function: {}", color_string);
+    }
+    Some(color)
+}
+
+/// The RGB value of a color, where the 16 ANSI colors take the colors of xterm
+fn color_to_rgb(color: Color) -> Option<(u8, u8, u8)> {
+    match color {
+        Color::Rgb(r, g, b) => Some((r, g, b)),
+        Color::Fixed(n) => Some(fixed_to_rgb(n)),
+        color => ANSI_COLORS
+            .iter()
+            .find(|(ansi_color, _)| *ansi_color == color)
+            .map(|(_, rgb)| *rgb),
+    }
+}
+
+fn mix_rgb(first: (u8, u8, u8), second: (u8, u8, u8), amount: f64) -> Color {
+    let mix = |a: u8, b: u8| (f64::from(a) + (f64::from(b) - f64::from(a)) * amount).round() as u8;
+    Color::Rgb(
+        mix(first.0, second.0),
+        mix(first.1, second.1),
+        mix(first.2, second.2),
+    )
+}
+
+/// Colors which a `gradient` in a style string spreads across the graphemes of a text group
+#[derive(Clone, Debug, PartialEq)]
+pub struct StyleGradient {
+    foreground: bool,
+    stops: Vec<(u8, u8, u8)>,
+}
+
+impl StyleGradient {
+    /// The color at `position`, from 0 at the first color to 1 at the last
+    fn color_at(&self, position: f64) -> Color {
+        let position = position.clamp(0.0, 1.0) * (self.stops.len() - 1) as f64;
+        let index = (position.floor() as usize).min(self.stops.len() - 2);
+        mix_rgb(
+            self.stops[index],
+            self.stops[index + 1],
+            position - index as f64,
+        )
+    }
+
+    /// Colors each of the `count` graphemes of a text group with its color of the gradient
+    pub fn apply(&self, style: Option<Style>, index: usize, count: usize) -> Style {
+        let color = self.color_at(match count {
+            0 | 1 => 0.0,
+            count => index as f64 / (count - 1) as f64,
+        });
+        let style = style.unwrap_or_default();
+        match self.foreground {
+            true => style.fg(color),
+            false => style.on(color),
+        }
+    }
+}
+
+/// Parses the last `gradient(<color>, <color>, ...)` of a style string, if it has one
+pub fn parse_style_gradient(
+    style_string: &str,
+    context: Option<&Context>,
+) -> Option<StyleGradient> {
+    style_tokens(style_string)
+        .into_iter()
+        .rev()
+        .find_map(|token| {
+            let token = token.to_lowercase();
+            let (token, foreground) = match token.strip_prefix("bg:") {
+                Some(token) => (token, false),
+                None => (token.strip_prefix("fg:").unwrap_or(&token), true),
+            };
+            match split_color_function(token)? {
+                ("gradient", args) if args.len() >= 2 => Some(StyleGradient {
+                    foreground,
+                    stops: args
+                        .iter()
+                        .map(|arg| parse_themed_color_string(arg, context).and_then(color_to_rgb))
+                        .collect::<Option<_>>()?,
+                }),
+                _ => None,
+            }
         })
 }
 
+/// How deep the colors of roles can refer to other roles, so that cycles end
+const MAX_ROLE_DEPTH: usize = 8;
+
+/// Parse a color string like `parse_color_string`, where the colors of the palette take
+/// precedence over the roles of the theme, which take precedence over predefined colors
+fn parse_themed_color_string(
+    color_string: &str,
+    context: Option<&Context>,
+) -> Option<nu_ansi_term::Color> {
+    parse_role_color_string(color_string, context, 0)
+}
+
+fn parse_role_color_string(
+    color_string: &str,
+    context: Option<&Context>,
+    depth: usize,
+) -> Option<nu_ansi_term::Color> {
+    let parse_color = |color_string: &str| parse_role_color_string(color_string, context, depth);
+    if let Some(color) = parse_color_function(color_string, &parse_color) {
+        return color;
+    }
+
+    let palette = context
+        .and_then(|x| get_palette(&x.root_config.palettes, x.root_config.palette.as_deref()));
+    let in_palette = palette.map_or(false, |palette| palette.contains_key(color_string));
+    match context.and_then(|x| x.theme_roles().get(color_string)) {
+        Some(role_color) if !in_palette => {
+            log::trace!(
+                "Read theme role: {} defined as {}",
+                color_string,
+                role_color
+            );
+            if depth >= MAX_ROLE_DEPTH {
+                log::warn!("Theme role {} refers to itself", color_string);
+                return None;
+            }
+            parse_role_color_string(role_color, context, depth + 1)
+        }
+        _ => parse_color_string(color_string, palette),
+    }
+}
+
 /** Parse a string that represents a color setting, returning None if this fails
  There are three valid color formats:
   - #RRGGBB      (a hash followed by an RGB hex)
   - u8           (a number from 0-255, representing an ANSI color)
   - colstring    (one of the 16 predefined color strings or a custom user-defined color)
+
+ Colors can also be derived from other colors with the functions of `parse_color_function`.
 */
 fn parse_color_string(
     color_string: &str,
     palette: Option<&Palette>,
 ) -> Option<nu_ansi_term::Color> {
-    // Parse RGB hex values
     log::trace!("Parsing color_string: {}", color_string);
+    let parse_color = |color_string: &str| parse_color_string(color_string, palette);
+    if let Some(color) = parse_color_function(color_string, &parse_color) {
+        return color;
+    }
+
+    // Parse RGB hex values
     if color_string.starts_with('#') {
         log::trace!(
             "Attempting to read hexadecimal color string: {}",
@@ -422,6 +641,239 @@ fn parse_color_string(
     predefined_color
 }
 
+/// The colors a terminal can show, from the least to the most capable
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColorMode {
+    None,
+    Ansi16,
+    Ansi256,
+    TrueColor,
+}
+
+/// The colors of the 16 ANSI colors in the default palette of xterm
+const ANSI_COLORS: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Purple, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::White, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightPurple, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::LightGray, (255, 255, 255)),
+];
+
+/// The levels of each channel in the 6x6x6 color cube of the 256 color palette
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+impl ColorMode {
+    /// The mode set by `color_mode` in the root config, or the one detected from the environment
+    /// for `auto`
+    pub fn from_context(context: &Context) -> Self {
+        match context.root_config.color_mode.as_str() {
+            "truecolor" => Self::TrueColor,
+            "256" => Self::Ansi256,
+            "16" => Self::Ansi16,
+            "none" => Self::None,
+            mode => {
+                if mode != "auto" {
+                    log::warn!("Unknown color_mode {mode:?}, detecting the colors of the terminal");
+                }
+                Self::detect(context)
+            }
+        }
+    }
+
+    /// Detects the colors of the terminal from `NO_COLOR`, `COLORTERM` and `TERM`. Terminals
+    /// which are not known to lack colors keep the colors of the config.
+    fn detect(context: &Context) -> Self {
+        let term = context.get_env("TERM").unwrap_or_default();
+        if term == "dumb"
+            || context
+                .get_env("NO_COLOR")
+                .map_or(false, |value| !value.is_empty())
+        {
+            return Self::None;
+        }
+        if matches!(
+            context.get_env("COLORTERM").as_deref(),
+            Some("truecolor" | "24bit")
+        ) {
+            return Self::TrueColor;
+        }
+
+        if term.ends_with("-direct") {
+            Self::TrueColor
+        } else if term.contains("256color") {
+            Self::Ansi256
+        } else if matches!(
+            term.as_str(),
+            "linux" | "ansi" | "vt100" | "vt220" | "cons25"
+        ) || term.ends_with("-color")
+            || term.ends_with("-16color")
+        {
+            Self::Ansi16
+        } else {
+            Self::TrueColor
+        }
+    }
+
+    /// Maps the colors of `style` to the nearest ones the terminal can show
+    pub fn apply(self, mut style: Style) -> Style {
+        style.foreground = style.foreground.and_then(|color| self.convert(color));
+        style.background = style.background.and_then(|color| self.convert(color));
+        style
+    }
+
+    fn convert(self, color: Color) -> Option<Color> {
+        match (self, color) {
+            (Self::None, _) => None,
+            (Self::Ansi256, Color::Rgb(r, g, b)) => Some(Color::Fixed(rgb_to_fixed(r, g, b))),
+            (Self::Ansi16, Color::Rgb(r, g, b)) => Some(nearest_ansi_color((r, g, b))),
+            (Self::Ansi16, Color::Fixed(n)) => Some(nearest_ansi_color(fixed_to_rgb(n))),
+            _ => Some(color),
+        }
+    }
+}
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    [(r1, r2), (g1, g2), (b1, b2)]
+        .iter()
+        .map(|&(a, b)| u32::from(a.abs_diff(b)).pow(2))
+        .sum()
+}
+
+/// The nearest color of the 6x6x6 cube or the gray ramp of the 256 color palette
+fn rgb_to_fixed(r: u8, g: u8, b: u8) -> u8 {
+    let level = |channel: u8| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|&i| CUBE_LEVELS[i].abs_diff(channel))
+            .unwrap_or_default() as u8
+    };
+    let (ri, gi, bi) = (level(r), level(g), level(b));
+    let cube = 16 + 36 * ri + 6 * gi + bi;
+
+    let average = (u16::from(r) + u16::from(g) + u16::from(b)) / 3;
+    let gray_index = (average.saturating_sub(3) / 10).min(23) as u8;
+    let gray = 232 + gray_index;
+
+    if distance((r, g, b), fixed_to_rgb(gray)) < distance((r, g, b), fixed_to_rgb(cube)) {
+        gray
+    } else {
+        cube
+    }
+}
+
+fn fixed_to_rgb(n: u8) -> (u8, u8, u8) {
+    match n {
+        0..=15 => ANSI_COLORS[usize::from(n)].1,
+        16..=231 => {
+            let n = usize::from(n - 16);
+            (
+                CUBE_LEVELS[n / 36],
+                CUBE_LEVELS[(n / 6) % 6],
+                CUBE_LEVELS[n % 6],
+            )
+        }
+        _ => {
+            let level = 8 + 10 * (n - 232);
+            (level, level, level)
+        }
+    }
+}
+
+fn nearest_ansi_color(rgb: (u8, u8, u8)) -> Color {
+    ANSI_COLORS
+        .iter()
+        .min_by_key(|(_, ansi_rgb)| distance(rgb, *ansi_rgb))
+        .map_or(Color::Default, |(color, _)| *color)
+}
+
+/// Whether the terminal has a dark or a light background
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ThemeVariant {
+    Dark,
+    Light,
+}
+
+impl ThemeVariant {
+    /// The variant set by `theme_variant` in the config, or the detected one for `auto`
+    pub fn from_context(context: &Context) -> Self {
+        match context.root_config.theme_variant.as_str() {
+            "dark" => ThemeVariant::Dark,
+            "light" => ThemeVariant::Light,
+            "auto" => Self::detect(context),
+            variant => {
+                log::warn!(
+                    "Unknown theme variant {variant:?}, expected \"auto\", \"dark\" or \"light\""
+                );
+                Self::detect(context)
+            }
+        }
+    }
+
+    /// Detects the background from `COLORFGBG`, which some terminals set to `fg;bg` or
+    /// `fg;default;bg`. Backgrounds other than white and the bright colors are dark.
+    fn detect(context: &Context) -> Self {
+        let background = context
+            .get_env("COLORFGBG")
+            .and_then(|colorfgbg| colorfgbg.rsplit(';').next()?.parse::<u8>().ok());
+        match background {
+            Some(7 | 9..=15) => ThemeVariant::Light,
+            _ => ThemeVariant::Dark,
+        }
+    }
+}
+
+/// The colors of the roles of the selected theme, including the roles it inherits and those
+/// of the variant for the background of the terminal
+pub fn get_theme_roles(context: &Context) -> Palette {
+    let root_config = &context.root_config;
+    let variant = ThemeVariant::from_context(context);
+
+    // The selected theme, followed by the themes it inherits from
+    let mut themes = Vec::new();
+    let mut theme_name = root_config.theme.as_deref();
+    while let Some(name) = theme_name {
+        if themes.iter().any(|(inherited, _)| *inherited == name) {
+            log::warn!("Theme {} inherits from itself", name);
+            break;
+        }
+        let Some(theme) = root_config.themes.get(name) else {
+            log::warn!("Could not find theme: {}", name);
+            break;
+        };
+        themes.push((name, theme));
+        theme_name = theme.inherits.as_deref();
+    }
+
+    let mut roles: Palette = THEME_ROLES
+        .iter()
+        .map(|(role, color)| (role.to_string(), color.to_string()))
+        .collect();
+    for (_, theme) in themes.into_iter().rev() {
+        let variant_roles = match variant {
+            ThemeVariant::Dark => &theme.dark,
+            ThemeVariant::Light => &theme.light,
+        };
+        roles.extend(
+            theme
+                .roles
+                .iter()
+                .chain(variant_roles)
+                .map(|(role, color)| (role.to_lowercase(), color.clone())),
+        );
+    }
+    roles
+}
+
 fn get_palette<'a>(
     palettes: &'a HashMap<String, Palette>,
     palette_name: Option<&str>,
@@ -443,7 +895,7 @@ fn get_palette<'a>(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use nu_ansi_term::Style;
+    use crate::test::default_context;
 
     // Small wrapper to allow deserializing Style without a struct with #[serde(deserialize_with=)]
     #[derive(Default, Clone, Debug, PartialEq)]
@@ -925,4 +1377,256 @@ mod tests {
             "if the platform doesn't have utils::home_dir(), it should return None"
         );
     }
+
+    #[test]
+    fn color_mode_maps_rgb_to_256_colors() {
+        let mode = ColorMode::Ansi256;
+        assert_eq!(mode.convert(Color::Rgb(255, 0, 0)), Some(Color::Fixed(196)));
+        assert_eq!(
+            mode.convert(Color::Rgb(128, 128, 128)),
+            Some(Color::Fixed(244))
+        );
+        assert_eq!(
+            mode.convert(Color::Rgb(0, 135, 175)),
+            Some(Color::Fixed(31))
+        );
+        assert_eq!(mode.convert(Color::Fixed(100)), Some(Color::Fixed(100)));
+        assert_eq!(mode.convert(Color::Green), Some(Color::Green));
+    }
+
+    #[test]
+    fn color_mode_maps_to_16_colors() {
+        let mode = ColorMode::Ansi16;
+        assert_eq!(mode.convert(Color::Rgb(250, 10, 10)), Some(Color::LightRed));
+        assert_eq!(mode.convert(Color::Rgb(29, 34, 48)), Some(Color::Black));
+        assert_eq!(mode.convert(Color::Fixed(34)), Some(Color::Green));
+        assert_eq!(mode.convert(Color::Fixed(12)), Some(Color::LightBlue));
+    }
+
+    #[test]
+    fn color_mode_none_keeps_attributes() {
+        let style = Color::Rgb(1, 2, 3).bold().on(Color::Blue);
+        assert_eq!(ColorMode::None.apply(style), Style::new().bold());
+        assert_eq!(ColorMode::TrueColor.apply(style), style);
+    }
+
+    #[test]
+    fn color_mode_detection() {
+        let detect = |env: &[(&'static str, &str)]| {
+            let mut context = default_context();
+            for (key, value) in env {
+                context.env.insert(key, value.to_string());
+            }
+            ColorMode::from_context(&context)
+        };
+
+        assert_eq!(detect(&[]), ColorMode::TrueColor);
+        assert_eq!(detect(&[("TERM", "linux")]), ColorMode::Ansi16);
+        assert_eq!(detect(&[("TERM", "xterm-256color")]), ColorMode::Ansi256);
+        assert_eq!(
+            detect(&[("TERM", "xterm-256color"), ("COLORTERM", "truecolor")]),
+            ColorMode::TrueColor
+        );
+        assert_eq!(
+            detect(&[("COLORTERM", "truecolor"), ("NO_COLOR", "1")]),
+            ColorMode::None
+        );
+        assert_eq!(detect(&[("NO_COLOR", "")]), ColorMode::TrueColor);
+        assert_eq!(
+            detect(&[("TERM", "dumb"), ("COLORTERM", "truecolor")]),
+            ColorMode::None
+        );
+    }
+
+    #[test]
+    fn color_mode_from_config() {
+        let mut context = default_context().set_config(toml::toml! {
+            color_mode = "16"
+        });
+        context.env.insert("COLORTERM", String::from("truecolor"));
+        assert_eq!(ColorMode::from_context(&context), ColorMode::Ansi16);
+    }
+
+    #[test]
+    fn style_tokens_keep_function_arguments() {
+        assert_eq!(
+            style_tokens(" bold  fg:mix(red, darken(blue, 10%), 0.3)\tbg:red "),
+            vec!["bold", "fg:mix(red, darken(blue, 10%), 0.3)", "bg:red"]
+        );
+    }
+
+    #[test]
+    fn table_get_styles_color_functions() {
+        assert_eq!(
+            parse_style_string("lighten(#000000, 20%)", None),
+            Some(Color::Rgb(51, 51, 51).normal())
+        );
+        assert_eq!(
+            parse_style_string("bold darken(Red, 0.5)", None),
+            Some(Color::Rgb(103, 0, 0).bold())
+        );
+        assert_eq!(
+            parse_style_string("bg:mix(16, 231, 0.25) underline", None),
+            Some(Style::new().on(Color::Rgb(64, 64, 64)).underline())
+        );
+        assert_eq!(
+            parse_style_string("fg:gradient(blue, red)", None),
+            Some(Color::Blue.normal())
+        );
+        assert_eq!(parse_style_string("darken(red, 120%)", None), None);
+        assert_eq!(parse_style_string("mix(red, blue)", None), None);
+        assert_eq!(parse_style_string("shade(red, 0.1)", None), None);
+    }
+
+    #[test]
+    fn table_get_colors_functions_of_palette() {
+        let mut palette = Palette::new();
+        palette.insert("base".to_string(), "#204080".to_string());
+        palette.insert("dim".to_string(), "darken(#204080, 50%)".to_string());
+
+        assert_eq!(
+            parse_color_string("lighten(base, 50%)", Some(&palette)),
+            Some(Color::Rgb(144, 160, 192))
+        );
+        assert_eq!(
+            parse_color_string("dim", Some(&palette)),
+            Some(Color::Rgb(16, 32, 64))
+        );
+    }
+
+    #[test]
+    fn color_functions_of_theme_roles() {
+        let context = default_context().set_config(toml::toml! {
+            theme = "derived"
+            [themes.derived.roles]
+            accent = "#4080c0"
+            muted = "mix(accent, #000000, 0.5)"
+        });
+        assert_eq!(
+            parse_style_string("fg:darken(accent, 50%)", Some(&context)),
+            Some(Color::Rgb(32, 64, 96).normal())
+        );
+        assert_eq!(
+            parse_style_string("muted", Some(&context)),
+            Some(Color::Rgb(32, 64, 96).normal())
+        );
+    }
+
+    #[test]
+    fn style_gradient_of_style_string() {
+        let gradient = parse_style_gradient("bold bg:gradient(#000000, #ffffff)", None).unwrap();
+        assert_eq!(
+            gradient.apply(None, 0, 3),
+            Style::new().on(Color::Rgb(0, 0, 0))
+        );
+        assert_eq!(
+            gradient.apply(Some(Color::Red.bold()), 1, 3),
+            Color::Red.bold().on(Color::Rgb(128, 128, 128))
+        );
+        assert_eq!(
+            gradient.apply(None, 2, 3),
+            Style::new().on(Color::Rgb(255, 255, 255))
+        );
+        assert_eq!(
+            gradient.apply(None, 0, 1),
+            Style::new().on(Color::Rgb(0, 0, 0))
+        );
+        assert_eq!(parse_style_gradient("bold red", None), None);
+        assert_eq!(parse_style_gradient("fg:gradient(red)", None), None);
+    }
+
+    #[test]
+    fn theme_roles_default_colors() {
+        let context = default_context();
+        assert_eq!(
+            parse_style_string("bold accent", Some(&context)),
+            Some(Color::Cyan.bold())
+        );
+        assert_eq!(
+            parse_style_string("danger", Some(&context)),
+            Some(Color::Red.normal())
+        );
+        assert_eq!(parse_style_string("accent", None), None);
+    }
+
+    #[test]
+    fn theme_roles_inherit_and_override() {
+        let context = default_context().set_config(toml::toml! {
+            theme = "team"
+            palette = "default"
+            [themes.base.roles]
+            accent = "blue"
+            warning = "#ffaf00"
+            [themes.team]
+            inherits = "base"
+            [themes.team.roles]
+            accent = "mustard"
+            [palettes.default]
+            mustard = "#af8700"
+        });
+
+        assert_eq!(
+            parse_style_string("accent", Some(&context)),
+            Some(Color::Rgb(0xaf, 0x87, 0x00).normal())
+        );
+        assert_eq!(
+            parse_style_string("bg:warning", Some(&context)),
+            Some(Style::new().on(Color::Rgb(0xff, 0xaf, 0x00)))
+        );
+        assert_eq!(
+            parse_style_string("vcs", Some(&context)),
+            Some(Color::Purple.normal())
+        );
+    }
+
+    #[test]
+    fn theme_roles_of_variant() {
+        let theme = |variant: &str, colorfgbg: &str| {
+            let mut config = toml::toml! {
+                theme = "both"
+                [themes.both.roles]
+                muted = "white"
+                [themes.both.light]
+                muted = "black"
+            };
+            config.insert(String::from("theme_variant"), variant.into());
+            let mut context = default_context().set_config(config);
+            context.env.insert("COLORFGBG", colorfgbg.to_string());
+            parse_style_string("muted", Some(&context))
+        };
+
+        assert_eq!(theme("auto", "0;15"), Some(Color::Black.normal()));
+        assert_eq!(theme("auto", "15;default;0"), Some(Color::White.normal()));
+        assert_eq!(theme("auto", ""), Some(Color::White.normal()));
+        assert_eq!(theme("dark", "0;15"), Some(Color::White.normal()));
+        assert_eq!(theme("light", "15;0"), Some(Color::Black.normal()));
+    }
+
+    #[test]
+    fn theme_roles_referring_to_themselves() {
+        let context = default_context().set_config(toml::toml! {
+            theme = "cycle"
+            [themes.cycle.roles]
+            accent = "danger"
+            danger = "lighten(accent, 10%)"
+        });
+        assert_eq!(parse_style_string("accent", Some(&context)), None);
+    }
+
+    #[test]
+    fn theme_roles_ignore_inheritance_cycles() {
+        let context = default_context().set_config(toml::toml! {
+            theme = "a"
+            [themes.a]
+            inherits = "b"
+            [themes.b]
+            inherits = "a"
+            [themes.b.roles]
+            danger = "purple"
+        });
+        assert_eq!(
+            parse_style_string("danger", Some(&context)),
+            Some(Color::Purple.normal())
+        );
+    }
 }
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use super::cloud::CloudEnvironmentConfig;
+
 #[derive(Clone, Deserialize, Serialize)]
 #[cfg_attr(
     feature = "config-schema",
@@ -40,10 +42,23 @@ pub struct AwsConfig<'a> {
     pub region_aliases: HashMap<String, &'a str>,
     /// Table of profile aliases to display in addition to the AWS name.
     pub profile_aliases: HashMap<String, &'a str>,
+    /// Table of account aliases to display in addition to the AWS account id.
+    pub account_aliases: HashMap<String, &'a str>,
     /// The symbol displayed when the temporary credentials have expired.
     pub expiration_symbol: &'a str,
     /// If true displays info even if `credentials`, `credential_process` or `sso_start_url` have not been setup.
     pub force_display: bool,
+    /// Which environment variables should trigger this module.
+    pub detect_env_vars: Vec<&'a str>,
+    /// Which extensions should trigger this module.
+    pub detect_extensions: Vec<&'a str>,
+    /// Which filenames should trigger this module.
+    pub detect_files: Vec<&'a str>,
+    /// Which folders should trigger this module.
+    pub detect_folders: Vec<&'a str>,
+    /// Customized styles, symbols and aliases for specific profiles.
+    #[serde(borrow)]
+    pub environments: Vec<CloudEnvironmentConfig<'a>>,
 }
 
 impl<'a> Default for AwsConfig<'a> {
@@ -55,8 +70,14 @@ impl<'a> Default for AwsConfig<'a> {
             disabled: false,
             region_aliases: HashMap::new(),
             profile_aliases: HashMap::new(),
+            account_aliases: HashMap::new(),
             expiration_symbol: "X",
             force_display: false,
+            detect_env_vars: vec![],
+            detect_extensions: vec![],
+            detect_files: vec![],
+            detect_folders: vec![],
+            environments: vec![],
         }
     }
 }
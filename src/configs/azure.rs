@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use super::cloud::CloudEnvironmentConfig;
+
 #[derive(Clone, Deserialize, Serialize)]
 #[cfg_attr(
     feature = "config-schema",
@@ -14,6 +16,12 @@ pub struct AzureConfig<'a> {
     pub style: &'a str,
     pub disabled: bool,
     pub subscription_aliases: HashMap<String, &'a str>,
+    pub detect_env_vars: Vec<&'a str>,
+    pub detect_extensions: Vec<&'a str>,
+    pub detect_files: Vec<&'a str>,
+    pub detect_folders: Vec<&'a str>,
+    #[serde(borrow)]
+    pub environments: Vec<CloudEnvironmentConfig<'a>>,
 }
 
 impl<'a> Default for AzureConfig<'a> {
@@ -24,6 +32,11 @@ impl<'a> Default for AzureConfig<'a> {
             style: "blue bold",
             disabled: true,
             subscription_aliases: HashMap::new(),
+            detect_env_vars: vec![],
+            detect_extensions: vec![],
+            detect_files: vec![],
+            detect_folders: vec![],
+            environments: vec![],
         }
     }
 }
@@ -52,7 +52,7 @@ impl<'a> Default for BatteryDisplayConfig<'a> {
     fn default() -> Self {
         BatteryDisplayConfig {
             threshold: 10,
-            style: "red bold",
+            style: "danger bold",
             charging_symbol: None,
             discharging_symbol: None,
         }
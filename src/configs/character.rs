@@ -24,7 +24,7 @@ impl<'a> Default for CharacterConfig<'a> {
         CharacterConfig {
             format: "$symbol ",
             success_symbol: "[❯](bold green)",
-            error_symbol: "[❯](bold red)",
+            error_symbol: "[❯](bold danger)",
             vimcmd_symbol: "[❮](bold green)",
             vimcmd_visual_symbol: "[❮](bold yellow)",
             vimcmd_replace_symbol: "[❮](bold purple)",
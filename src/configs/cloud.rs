@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+
+/// Style overrides of a cloud module for a specific environment, e.g. a profile or project.
+#[derive(Clone, Deserialize, Serialize, Default)]
+#[cfg_attr(
+    feature = "config-schema",
+    derive(schemars::JsonSchema),
+    schemars(deny_unknown_fields)
+)]
+#[serde(default)]
+pub struct CloudEnvironmentConfig<'a> {
+    /// Regular expression matching the entire name of the environment.
+    pub pattern: &'a str,
+    /// Name to display instead, may reference capture groups of `pattern`.
+    pub alias: Option<&'a str>,
+    /// The style for the module in this environment.
+    pub style: Option<&'a str>,
+    /// The symbol for the module in this environment.
+    pub symbol: Option<&'a str>,
+    /// Marks the environment as production, which defaults its style to `bold red`.
+    pub production: bool,
+}
@@ -1,3 +1,4 @@
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Deserialize, Serialize)]
@@ -9,28 +10,38 @@ use serde::{Deserialize, Serialize};
 #[serde(default)]
 pub struct CmdDurationConfig<'a> {
     pub min_time: i64,
+    pub min_time_by_command: IndexMap<String, i64>,
+    pub ignored_commands: Vec<&'a str>,
     pub format: &'a str,
     pub style: &'a str,
     pub show_milliseconds: bool,
+    pub time_format: &'a str,
     pub disabled: bool,
     pub show_notifications: bool,
     pub min_time_to_notify: i64,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub notification_timeout: Option<u32>,
+    pub notification_failure_urgency: &'a str,
+    pub notification_failure_sound: &'a str,
 }
 
 impl<'a> Default for CmdDurationConfig<'a> {
     fn default() -> Self {
         CmdDurationConfig {
             min_time: 2_000,
+            min_time_by_command: IndexMap::new(),
+            ignored_commands: vec![],
             format: "took [$duration]($style) ",
             show_milliseconds: false,
-            style: "yellow bold",
+            time_format: "%T",
+            style: "warning bold",
             disabled: false,
             show_notifications: false,
             min_time_to_notify: 45_000,
             notification_timeout: None,
+            notification_failure_urgency: "normal",
+            notification_failure_sound: "",
         }
     }
 }
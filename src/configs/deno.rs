@@ -24,7 +24,7 @@ impl<'a> Default for DenoConfig<'a> {
             format: "via [$symbol($version )]($style)",
             version_format: "v${raw}",
             symbol: "🦕 ",
-            style: "green bold",
+            style: "language bold",
             disabled: false,
             detect_extensions: vec![],
             detect_files: vec![
@@ -38,12 +38,12 @@ impl<'a> Default for DirectoryConfig<'a> {
             substitutions: IndexMap::new(),
             format: "[$path]($style)[$read_only]($read_only_style) ",
             repo_root_format: "[$before_root_path]($before_repo_root_style)[$repo_root]($repo_root_style)[$path]($style)[$read_only]($read_only_style) ",
-            style: "cyan bold",
+            style: "accent bold",
             repo_root_style: None,
             before_repo_root_style: None,
             disabled: false,
             read_only: "🔒",
-            read_only_style: "red",
+            read_only_style: "danger",
             truncation_symbol: "",
             home_symbol: "~",
             use_os_path_sep: true,
@@ -27,8 +27,16 @@ impl<'a> Default for DockerContextConfig<'a> {
             only_with_files: true,
             disabled: false,
             detect_extensions: vec![],
-            detect_files: vec!["docker-compose.yml", "docker-compose.yaml", "Dockerfile"],
-            detect_folders: vec![],
+            detect_files: vec![
+                "compose.yaml",
+                "compose.yml",
+                "docker-compose.yml",
+                "docker-compose.yaml",
+                "Dockerfile",
+                "Containerfile",
+                ".devcontainer.json",
+            ],
+            detect_folders: vec![".devcontainer"],
         }
     }
 }
@@ -25,7 +25,7 @@ impl<'a> Default for FennelConfig<'a> {
             format: "via [$symbol($version )]($style)",
             version_format: "v${raw}",
             symbol: "🧅 ",
-            style: "bold green",
+            style: "bold language",
             disabled: true,
             detect_extensions: vec!["fnl"],
             detect_files: vec![],
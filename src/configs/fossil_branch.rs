@@ -21,7 +21,7 @@ impl<'a> Default for FossilBranchConfig<'a> {
         FossilBranchConfig {
             format: "on [$symbol$branch]($style) ",
             symbol: " ",
-            style: "bold purple",
+            style: "bold vcs",
             truncation_length: std::i64::MAX,
             truncation_symbol: "…",
             disabled: true,
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use super::cloud::CloudEnvironmentConfig;
+
 #[derive(Clone, Deserialize, Serialize)]
 #[cfg_attr(
     feature = "config-schema",
@@ -16,6 +18,11 @@ pub struct GcloudConfig<'a> {
     pub region_aliases: HashMap<String, &'a str>,
     pub project_aliases: HashMap<String, &'a str>,
     pub detect_env_vars: Vec<&'a str>,
+    pub detect_extensions: Vec<&'a str>,
+    pub detect_files: Vec<&'a str>,
+    pub detect_folders: Vec<&'a str>,
+    #[serde(borrow)]
+    pub environments: Vec<CloudEnvironmentConfig<'a>>,
 }
 
 impl<'a> Default for GcloudConfig<'a> {
@@ -28,6 +35,10 @@ impl<'a> Default for GcloudConfig<'a> {
             region_aliases: HashMap::new(),
             project_aliases: HashMap::new(),
             detect_env_vars: vec![],
+            detect_extensions: vec![],
+            detect_files: vec![],
+            detect_folders: vec![],
+            environments: vec![],
         }
     }
 }
@@ -1,3 +1,4 @@
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Deserialize, Serialize)]
@@ -16,6 +17,7 @@ pub struct GitBranchConfig<'a> {
     pub only_attached: bool,
     pub always_show_remote: bool,
     pub ignore_branches: Vec<&'a str>,
+    pub forge_symbols: IndexMap<String, &'a str>,
     pub disabled: bool,
 }
 
@@ -24,13 +26,24 @@ impl<'a> Default for GitBranchConfig<'a> {
         GitBranchConfig {
             format: "on [$symbol$branch(:$remote_branch)]($style) ",
             symbol: " ",
-            style: "bold purple",
+            style: "bold vcs",
             truncation_length: std::i64::MAX,
             truncation_symbol: "…",
             only_attached: false,
             always_show_remote: false,
             ignore_branches: vec![],
+            forge_symbols: default_forge_symbols(),
             disabled: false,
         }
     }
 }
+
+/// The symbols of the forges hosting the remote, shared with `git_commit`
+pub fn default_forge_symbols<'a>() -> IndexMap<String, &'a str> {
+    IndexMap::from([
+        (String::from("github"), "\u{f09b} "),
+        (String::from("gitlab"), "\u{f296} "),
+        (String::from("bitbucket"), "\u{f171} "),
+        (String::from("gitea"), "\u{f1d3} "),
+    ])
+}
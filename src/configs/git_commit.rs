@@ -1,5 +1,8 @@
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
+use super::git_branch::default_forge_symbols;
+
 #[derive(Clone, Deserialize, Serialize)]
 #[cfg_attr(
     feature = "config-schema",
@@ -16,6 +19,11 @@ pub struct GitCommitConfig<'a> {
     pub tag_symbol: &'a str,
     pub tag_disabled: bool,
     pub tag_max_candidates: usize,
+    pub describe_max_candidates: usize,
+    pub subject_max_length: usize,
+    pub truncation_symbol: &'a str,
+    pub signed_symbol: &'a str,
+    pub forge_symbols: IndexMap<String, &'a str>,
 }
 
 impl<'a> Default for GitCommitConfig<'a> {
@@ -30,6 +38,12 @@ impl<'a> Default for GitCommitConfig<'a> {
             tag_symbol: " 🏷  ",
             tag_disabled: true,
             tag_max_candidates: 0,
+            // the default of `git describe`
+            describe_max_candidates: 10,
+            subject_max_length: 32,
+            truncation_symbol: "…",
+            signed_symbol: "🔏",
+            forge_symbols: default_forge_symbols(),
         }
     }
 }
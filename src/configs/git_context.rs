@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Deserialize, Serialize)]
+#[cfg_attr(
+    feature = "config-schema",
+    derive(schemars::JsonSchema),
+    schemars(deny_unknown_fields)
+)]
+#[serde(default)]
+pub struct GitContextConfig<'a> {
+    pub format: &'a str,
+    pub style: &'a str,
+    pub worktree_symbol: &'a str,
+    pub submodule_symbol: &'a str,
+    pub sparse: &'a str,
+    pub partial_clone: &'a str,
+    pub bare: &'a str,
+    pub disabled: bool,
+}
+
+impl<'a> Default for GitContextConfig<'a> {
+    fn default() -> Self {
+        GitContextConfig {
+            format: "([$worktree_symbol$worktree]($style) )([$submodule_symbol$superproject]($style) )([\\[$flags\\]]($style) )",
+            style: "bold cyan",
+            worktree_symbol: "🌲 ",
+            submodule_symbol: "↳ ",
+            sparse: "sparse",
+            partial_clone: "partial",
+            bare: "bare",
+            disabled: false,
+        }
+    }
+}
@@ -30,7 +30,7 @@ impl<'a> Default for GitStateConfig<'a> {
             bisect: "BISECTING",
             am: "AM",
             am_or_rebase: "AM/REBASE",
-            style: "bold yellow",
+            style: "bold warning",
             format: "\\([$state( $progress_current/$progress_total)]($style)\\) ",
             disabled: false,
         }
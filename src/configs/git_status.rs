@@ -15,6 +15,12 @@ pub struct GitStatusConfig<'a> {
     pub behind: &'a str,
     pub up_to_date: &'a str,
     pub diverged: &'a str,
+    pub upstream_gone: &'a str,
+    pub no_upstream: &'a str,
+    pub push_ahead: &'a str,
+    pub push_behind: &'a str,
+    pub push_up_to_date: &'a str,
+    pub push_diverged: &'a str,
     pub conflicted: &'a str,
     pub deleted: &'a str,
     pub renamed: &'a str,
@@ -23,6 +29,8 @@ pub struct GitStatusConfig<'a> {
     pub untracked: &'a str,
     pub typechanged: &'a str,
     pub ignore_submodules: bool,
+    pub untracked_scan_budget_ms: u64,
+    pub partial_count_prefix: &'a str,
     pub disabled: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub windows_starship: Option<&'a str>,
@@ -31,13 +39,19 @@ pub struct GitStatusConfig<'a> {
 impl<'a> Default for GitStatusConfig<'a> {
     fn default() -> Self {
         GitStatusConfig {
-            format: "([\\[$all_status$ahead_behind\\]]($style) )",
-            style: "red bold",
+            format: "([\\[$all_status$ahead_behind$push_ahead_behind\\]]($style) )",
+            style: "danger bold",
             stashed: "\\$",
             ahead: "⇡",
             behind: "⇣",
             up_to_date: "",
             diverged: "⇕",
+            upstream_gone: "⊘",
+            no_upstream: "",
+            push_ahead: "⇢",
+            push_behind: "⇠",
+            push_up_to_date: "",
+            push_diverged: "⇄",
             conflicted: "=",
             deleted: "✘",
             renamed: "»",
@@ -46,6 +60,8 @@ impl<'a> Default for GitStatusConfig<'a> {
             untracked: "?",
             typechanged: "",
             ignore_submodules: false,
+            untracked_scan_budget_ms: 0,
+            partial_count_prefix: "≥",
             disabled: false,
             windows_starship: None,
         }
@@ -20,7 +20,7 @@ impl<'a> Default for HgBranchConfig<'a> {
     fn default() -> Self {
         HgBranchConfig {
             symbol: " ",
-            style: "bold purple",
+            style: "bold vcs",
             format: "on [$symbol$branch(:$topic)]($style) ",
             truncation_length: std::i64::MAX,
             truncation_symbol: "…",
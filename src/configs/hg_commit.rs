@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Deserialize, Serialize)]
+#[cfg_attr(
+    feature = "config-schema",
+    derive(schemars::JsonSchema),
+    schemars(deny_unknown_fields)
+)]
+#[serde(default)]
+pub struct HgCommitConfig<'a> {
+    pub hash_length: usize,
+    pub format: &'a str,
+    pub style: &'a str,
+    pub tag_symbol: &'a str,
+    pub tag_disabled: bool,
+    pub disabled: bool,
+}
+
+impl<'a> Default for HgCommitConfig<'a> {
+    fn default() -> Self {
+        HgCommitConfig {
+            // be consistent with hg, which shows 12 digits of a changeset id by default
+            hash_length: 12,
+            format: "[\\($hash$tag\\)]($style) ",
+            style: "green bold",
+            tag_symbol: " 🏷  ",
+            tag_disabled: true,
+            disabled: true,
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Deserialize, Serialize)]
+#[cfg_attr(
+    feature = "config-schema",
+    derive(schemars::JsonSchema),
+    schemars(deny_unknown_fields)
+)]
+#[serde(default)]
+pub struct HgStateConfig<'a> {
+    pub merge: &'a str,
+    pub rebase: &'a str,
+    pub graft: &'a str,
+    pub histedit: &'a str,
+    pub unshelve: &'a str,
+    pub bisect: &'a str,
+    pub style: &'a str,
+    pub format: &'a str,
+    pub disabled: bool,
+}
+
+impl<'a> Default for HgStateConfig<'a> {
+    fn default() -> Self {
+        HgStateConfig {
+            merge: "MERGING",
+            rebase: "REBASING",
+            graft: "GRAFTING",
+            histedit: "HISTEDITING",
+            unshelve: "UNSHELVING",
+            bisect: "BISECTING",
+            style: "bold warning",
+            format: "\\([$state]($style)\\) ",
+            disabled: true,
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Deserialize, Serialize)]
+#[cfg_attr(
+    feature = "config-schema",
+    derive(schemars::JsonSchema),
+    schemars(deny_unknown_fields)
+)]
+#[serde(default)]
+pub struct HgStatusConfig<'a> {
+    pub format: &'a str,
+    pub style: &'a str,
+    pub modified: &'a str,
+    pub added: &'a str,
+    pub removed: &'a str,
+    pub unknown: &'a str,
+    pub ahead: &'a str,
+    pub behind: &'a str,
+    pub up_to_date: &'a str,
+    pub diverged: &'a str,
+    pub disabled: bool,
+}
+
+impl<'a> Default for HgStatusConfig<'a> {
+    fn default() -> Self {
+        HgStatusConfig {
+            format: "([\\[$all_status$ahead_behind\\]]($style) )",
+            style: "danger bold",
+            modified: "!",
+            added: "+",
+            removed: "✘",
+            unknown: "?",
+            ahead: "⇡",
+            behind: "⇣",
+            up_to_date: "",
+            diverged: "⇕",
+            disabled: true,
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Deserialize, Serialize)]
+#[cfg_attr(
+    feature = "config-schema",
+    derive(schemars::JsonSchema),
+    schemars(deny_unknown_fields)
+)]
+#[serde(default)]
+pub struct HistoryConfig<'a> {
+    pub size: usize,
+    pub success_symbol: &'a str,
+    pub failure_symbol: &'a str,
+    pub format: &'a str,
+    pub style: &'a str,
+    pub disabled: bool,
+}
+
+impl<'a> Default for HistoryConfig<'a> {
+    fn default() -> Self {
+        HistoryConfig {
+            size: 10,
+            success_symbol: "[✓](green)",
+            failure_symbol: "[✗](danger)",
+            format: "$sparkline( [$failures failed]($style)) ",
+            style: "danger",
+            disabled: true,
+        }
+    }
+}
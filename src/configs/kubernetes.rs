@@ -15,9 +15,12 @@ pub struct KubernetesConfig<'a> {
     pub disabled: bool,
     pub context_aliases: HashMap<String, &'a str>,
     pub user_aliases: HashMap<String, &'a str>,
+    pub detect_env_vars: Vec<&'a str>,
     pub detect_extensions: Vec<&'a str>,
     pub detect_files: Vec<&'a str>,
     pub detect_folders: Vec<&'a str>,
+    #[serde(borrow)]
+    pub contexts: Vec<KubernetesContextConfig<'a>>,
 }
 
 impl<'a> Default for KubernetesConfig<'a> {
@@ -29,9 +32,30 @@ impl<'a> Default for KubernetesConfig<'a> {
             disabled: true,
             context_aliases: HashMap::new(),
             user_aliases: HashMap::new(),
+            detect_env_vars: vec![],
             detect_extensions: vec![],
             detect_files: vec![],
             detect_folders: vec![],
+            contexts: vec![],
         }
     }
 }
+
+#[derive(Clone, Deserialize, Serialize, Default)]
+#[cfg_attr(
+    feature = "config-schema",
+    derive(schemars::JsonSchema),
+    schemars(deny_unknown_fields)
+)]
+#[serde(default)]
+pub struct KubernetesContextConfig<'a> {
+    pub context_pattern: &'a str,
+    pub user_pattern: &'a str,
+    pub cluster_pattern: &'a str,
+    pub namespace_pattern: &'a str,
+    pub symbol: Option<&'a str>,
+    pub style: Option<&'a str>,
+    pub context_alias: Option<&'a str>,
+    pub user_alias: Option<&'a str>,
+    pub production: bool,
+}
@@ -8,6 +8,7 @@ pub mod buf;
 pub mod bun;
 pub mod c;
 pub mod character;
+pub mod cloud;
 pub mod cmake;
 pub mod cmd_duration;
 pub mod cobol;
@@ -31,6 +32,7 @@ pub mod fossil_branch;
 pub mod gcloud;
 pub mod git_branch;
 pub mod git_commit;
+pub mod git_context;
 pub mod git_metrics;
 pub mod git_state;
 pub mod git_status;
@@ -41,6 +43,10 @@ pub mod haskell;
 pub mod haxe;
 pub mod helm;
 pub mod hg_branch;
+pub mod hg_commit;
+pub mod hg_state;
+pub mod hg_status;
+pub mod history;
 pub mod hostname;
 pub mod java;
 pub mod jobs;
@@ -86,6 +92,7 @@ pub mod time;
 pub mod username;
 pub mod v;
 pub mod vagrant;
+pub mod vcs;
 pub mod vcsh;
 pub mod zig;
 
@@ -165,6 +172,8 @@ pub struct FullConfig<'a> {
     #[serde(borrow)]
     git_commit: git_commit::GitCommitConfig<'a>,
     #[serde(borrow)]
+    git_context: git_context::GitContextConfig<'a>,
+    #[serde(borrow)]
     git_metrics: git_metrics::GitMetricsConfig<'a>,
     #[serde(borrow)]
     git_state: git_state::GitStateConfig<'a>,
@@ -185,6 +194,14 @@ pub struct FullConfig<'a> {
     #[serde(borrow)]
     hg_branch: hg_branch::HgBranchConfig<'a>,
     #[serde(borrow)]
+    hg_commit: hg_commit::HgCommitConfig<'a>,
+    #[serde(borrow)]
+    hg_state: hg_state::HgStateConfig<'a>,
+    #[serde(borrow)]
+    hg_status: hg_status::HgStatusConfig<'a>,
+    #[serde(borrow)]
+    history: history::HistoryConfig<'a>,
+    #[serde(borrow)]
     hostname: hostname::HostnameConfig<'a>,
     #[serde(borrow)]
     java: java::JavaConfig<'a>,
@@ -270,6 +287,8 @@ pub struct FullConfig<'a> {
     #[serde(borrow)]
     vagrant: vagrant::VagrantConfig<'a>,
     #[serde(borrow)]
+    vcs: vcs::VcsConfig<'a>,
+    #[serde(borrow)]
     vcsh: vcsh::VcshConfig<'a>,
     #[serde(borrow)]
     vlang: v::VConfig<'a>,
@@ -25,7 +25,7 @@ impl<'a> Default for NodejsConfig<'a> {
             format: "via [$symbol($version )]($style)",
             version_format: "v${raw}",
             symbol: " ",
-            style: "bold green",
+            style: "bold language",
             disabled: false,
             not_capable_style: "bold red",
             detect_extensions: vec!["js", "mjs", "cjs", "ts", "mts", "cts"],
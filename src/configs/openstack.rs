@@ -1,4 +1,7 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::cloud::CloudEnvironmentConfig;
 
 #[derive(Clone, Deserialize, Serialize)]
 #[cfg_attr(
@@ -12,6 +15,14 @@ pub struct OspConfig<'a> {
     pub symbol: &'a str,
     pub style: &'a str,
     pub disabled: bool,
+    pub cloud_aliases: HashMap<String, &'a str>,
+    pub project_aliases: HashMap<String, &'a str>,
+    pub detect_env_vars: Vec<&'a str>,
+    pub detect_extensions: Vec<&'a str>,
+    pub detect_files: Vec<&'a str>,
+    pub detect_folders: Vec<&'a str>,
+    #[serde(borrow)]
+    pub environments: Vec<CloudEnvironmentConfig<'a>>,
 }
 
 impl<'a> Default for OspConfig<'a> {
@@ -21,6 +32,13 @@ impl<'a> Default for OspConfig<'a> {
             symbol: "☁️  ",
             style: "bold yellow",
             disabled: false,
+            cloud_aliases: HashMap::new(),
+            project_aliases: HashMap::new(),
+            detect_env_vars: vec![],
+            detect_extensions: vec![],
+            detect_files: vec![],
+            detect_folders: vec![],
+            environments: vec![],
         }
     }
 }
@@ -20,7 +20,7 @@ impl<'a> Default for PijulConfig<'a> {
     fn default() -> Self {
         PijulConfig {
             symbol: " ",
-            style: "bold purple",
+            style: "bold vcs",
             format: "on [$symbol$channel]($style) ",
             truncation_length: std::i64::MAX,
             truncation_symbol: "…",
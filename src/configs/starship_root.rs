@@ -14,18 +14,177 @@ pub struct StarshipRootConfig {
     schema: String,
     pub format: String,
     pub right_format: String,
+    pub cache_right_prompt: bool,
     pub continuation_prompt: String,
+    pub transient_format: String,
     pub scan_timeout: u64,
     pub command_timeout: u64,
     pub add_newline: bool,
+    pub ambiguous_width: usize,
+    pub color_mode: String,
+    pub accessibility: bool,
+    pub symbol_set: String,
+    pub locale: String,
+    pub locales: HashMap<String, Messages>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub palette: Option<String>,
     pub palettes: HashMap<String, Palette>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub theme: Option<String>,
+    pub theme_variant: String,
+    pub themes: HashMap<String, Theme>,
     pub profiles: IndexMap<String, String>,
+    pub layout: IndexMap<String, ModuleLayout>,
+    pub separator: SeparatorConfig,
 }
 
 pub type Palette = HashMap<String, String>;
 
+/// Translations of the `[messages]` of a locale, keyed by their name in `locales/en.toml`
+pub type Messages = HashMap<String, String>;
+
+/// Colors for semantic roles like `accent` or `danger`, which can be used like color names
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+#[cfg_attr(
+    feature = "config-schema",
+    derive(schemars::JsonSchema),
+    schemars(deny_unknown_fields)
+)]
+#[serde(default)]
+pub struct Theme {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inherits: Option<String>,
+    pub roles: Palette,
+    pub dark: Palette,
+    pub light: Palette,
+}
+
+// The colors of the roles which no theme sets. The default styles of modules refer to
+// these roles, so they keep the colors of the default prompt.
+pub const THEME_ROLES: &[(&str, &str)] = &[
+    ("accent", "cyan"),
+    ("warning", "yellow"),
+    ("danger", "red"),
+    ("muted", "bright-black"),
+    ("vcs", "purple"),
+    ("language", "green"),
+];
+
+/// How a module gives way when the prompt is wider than the terminal
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+#[cfg_attr(
+    feature = "config-schema",
+    derive(schemars::JsonSchema),
+    schemars(deny_unknown_fields)
+)]
+#[serde(default)]
+pub struct ModuleLayout {
+    pub priority: i64,
+    pub min_width: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compact_format: Option<String>,
+}
+
+/// Glyphs which starship puts between the modules of the prompt
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[cfg_attr(
+    feature = "config-schema",
+    derive(schemars::JsonSchema),
+    schemars(deny_unknown_fields)
+)]
+#[serde(default)]
+pub struct SeparatorConfig {
+    pub mode: String,
+    pub symbol: String,
+    pub thin_symbol: String,
+    pub start_symbol: String,
+    pub end_symbol: String,
+}
+
+impl Default for SeparatorConfig {
+    fn default() -> Self {
+        SeparatorConfig {
+            mode: "none".to_string(),
+            symbol: "\u{e0b0}".to_string(),
+            thin_symbol: String::new(),
+            start_symbol: String::new(),
+            end_symbol: "\u{e0b0}".to_string(),
+        }
+    }
+}
+
+// The symbols of modules for each `symbol_set`, where `emoji` keeps the defaults of the modules
+pub const SYMBOL_SETS: &[(&str, &str)] = &[
+    ("emoji", ""),
+    ("nerd-font", include_str!("symbol_sets/nerd-font.toml")),
+    ("ascii", include_str!("symbol_sets/ascii.toml")),
+];
+
+// The bundled message catalogs, where `en` holds every message and the others translate them
+pub const LOCALES: &[(&str, &str)] = &[
+    ("en", include_str!("locales/en.toml")),
+    ("de", include_str!("locales/de.toml")),
+    ("es", include_str!("locales/es.toml")),
+    ("fr", include_str!("locales/fr.toml")),
+];
+
+// Textual symbols and formats of modules in accessibility mode, which replace glyphs and
+// meaning that is only conveyed by color. Modules not listed here read out their name in
+// place of their `symbol`.
+pub const A11Y_CONFIG: &str = r#"
+[battery]
+full_symbol = "battery full "
+charging_symbol = "battery charging "
+discharging_symbol = "battery discharging "
+unknown_symbol = "battery "
+empty_symbol = "battery empty "
+
+[character]
+success_symbol = "[>](bold green)"
+error_symbol = "[error >](bold danger)"
+vimcmd_symbol = "[normal mode <](bold green)"
+vimcmd_visual_symbol = "[visual mode <](bold yellow)"
+vimcmd_replace_symbol = "[replace mode <](bold purple)"
+vimcmd_replace_one_symbol = "[replace one mode <](bold purple)"
+
+[directory]
+read_only = ", read only"
+
+[fill]
+symbol = " "
+
+[git_status]
+format = "([git status$all_status$ahead_behind$push_ahead_behind]($style) )"
+stashed = ", ${count} stashed"
+ahead = ", ahead ${count}"
+behind = ", behind ${count}"
+diverged = ", ahead ${ahead_count} behind ${behind_count}"
+upstream_gone = ", upstream gone"
+push_ahead = ", push ahead ${count}"
+push_behind = ", push behind ${count}"
+push_diverged = ", push ahead ${ahead_count} behind ${behind_count}"
+conflicted = ", ${count} conflicted"
+deleted = ", ${count} deleted"
+renamed = ", ${count} renamed"
+modified = ", ${count} modified"
+staged = ", ${count} staged"
+untracked = ", ${count} untracked"
+typechanged = ", ${count} type changed"
+partial_count_prefix = "at least "
+
+[history]
+success_symbol = " ok"
+failure_symbol = " failed"
+format = "history$sparkline(, [$failures failed]($style)) "
+
+[status]
+symbol = "exit status "
+not_executable_symbol = "not executable, exit status "
+not_found_symbol = "not found, exit status "
+sigint_symbol = "interrupted, exit status "
+signal_symbol = "signal, exit status "
+"#;
+
 // List of default prompt order
 // NOTE: If this const value is changed then Default prompt order subheading inside
 // prompt heading of config docs needs to be updated according to changes made here.
@@ -41,11 +200,16 @@ pub const PROMPT_ORDER: &[&str] = &[
     "fossil_branch",
     "git_branch",
     "git_commit",
+    "git_context",
     "git_state",
     "git_metrics",
     "git_status",
     "hg_branch",
+    "hg_commit",
+    "hg_state",
+    "hg_status",
     "pijul_channel",
+    "vcs",
     "docker_context",
     "package",
     // ↓ Toolchain version modules ↓
@@ -115,6 +279,7 @@ pub const PROMPT_ORDER: &[&str] = &[
     "battery",
     "time",
     "status",
+    "history",
     "container",
     "os",
     "shell",
@@ -128,13 +293,26 @@ impl Default for StarshipRootConfig {
             schema: "https://starship.rs/config-schema.json".to_string(),
             format: "$all".to_string(),
             right_format: String::new(),
-            continuation_prompt: "[∙](bright-black) ".to_string(),
+            cache_right_prompt: false,
+            continuation_prompt: "[∙](muted) ".to_string(),
+            transient_format: "$character".to_string(),
             profiles: Default::default(),
             scan_timeout: 30,
             command_timeout: 500,
             add_newline: true,
+            ambiguous_width: 1,
+            color_mode: "auto".to_string(),
+            accessibility: false,
+            symbol_set: "emoji".to_string(),
+            locale: String::new(),
+            locales: HashMap::default(),
             palette: None,
             palettes: HashMap::default(),
+            theme: None,
+            theme_variant: "auto".to_string(),
+            themes: HashMap::default(),
+            layout: IndexMap::new(),
+            separator: SeparatorConfig::default(),
         }
     }
 }
@@ -36,7 +36,7 @@ impl<'a> Default for StatusConfig<'a> {
             not_found_symbol: "🔍",
             sigint_symbol: "🧱",
             signal_symbol: "⚡",
-            style: "bold red",
+            style: "bold danger",
             map_symbol: false,
             recognize_signal_code: true,
             pipestatus: false,
@@ -12,7 +12,9 @@ pub struct TerraformConfig<'a> {
     pub version_format: &'a str,
     pub symbol: &'a str,
     pub style: &'a str,
+    pub init_needed_symbol: &'a str,
     pub disabled: bool,
+    pub commands: Vec<Vec<&'a str>>,
     pub detect_extensions: Vec<&'a str>,
     pub detect_files: Vec<&'a str>,
     pub detect_folders: Vec<&'a str>,
@@ -21,13 +23,15 @@ pub struct TerraformConfig<'a> {
 impl<'a> Default for TerraformConfig<'a> {
     fn default() -> Self {
         TerraformConfig {
-            format: "via [$symbol$workspace]($style) ",
+            format: "via [$symbol$workspace( $init_needed)]($style) ",
             version_format: "v${raw}",
             symbol: "💠 ",
             style: "bold 105",
+            init_needed_symbol: "⚠",
             disabled: false,
+            commands: vec![vec!["terraform", "version"], vec!["tofu", "version"]],
             detect_extensions: vec!["tf", "tfplan", "tfstate"],
-            detect_files: vec![],
+            detect_files: vec![".terraform-version", ".opentofu-version"],
             detect_folders: vec![".terraform"],
         }
     }
@@ -19,7 +19,7 @@ impl<'a> Default for UsernameConfig<'a> {
     fn default() -> Self {
         UsernameConfig {
             format: "[$user]($style) in ",
-            style_root: "red bold",
+            style_root: "danger bold",
             style_user: "yellow bold",
             show_always: false,
             disabled: false,
@@ -0,0 +1,69 @@
+use indexmap::{indexmap, IndexMap};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Deserialize, Serialize)]
+#[cfg_attr(
+    feature = "config-schema",
+    derive(schemars::JsonSchema),
+    schemars(deny_unknown_fields)
+)]
+#[serde(default)]
+pub struct VcsConfig<'a> {
+    pub format: &'a str,
+    pub style: &'a str,
+    pub operation_style: &'a str,
+    pub symbols: IndexMap<String, &'a str>,
+    pub backends: Vec<&'a str>,
+    pub dirty_symbol: &'a str,
+    pub truncation_length: i64,
+    pub truncation_symbol: &'a str,
+    pub rebase: &'a str,
+    pub merge: &'a str,
+    pub revert: &'a str,
+    pub cherry_pick: &'a str,
+    pub bisect: &'a str,
+    pub am: &'a str,
+    pub graft: &'a str,
+    pub histedit: &'a str,
+    pub unshelve: &'a str,
+    pub conflict: &'a str,
+    pub disabled: bool,
+}
+
+impl<'a> VcsConfig<'a> {
+    pub fn get_symbol(&self, key: &str) -> Option<&'a str> {
+        self.symbols.get(key).copied()
+    }
+}
+
+impl<'a> Default for VcsConfig<'a> {
+    fn default() -> Self {
+        VcsConfig {
+            format: "on [$symbol$branch]($style)( [$dirty]($style))( [⇡$ahead]($style))( [⇣$behind]($style))( \\([$operation]($operation_style)\\)) ",
+            style: "bold vcs",
+            operation_style: "bold warning",
+            symbols: indexmap! {
+                String::from("jj") => "jj ",
+                String::from("git") => " ",
+                String::from("hg") => " ",
+                String::from("fossil") => " ",
+                String::from("pijul") => " ",
+            },
+            backends: vec!["jj", "git", "hg", "fossil", "pijul"],
+            dirty_symbol: "*",
+            truncation_length: i64::MAX,
+            truncation_symbol: "…",
+            rebase: "REBASING",
+            merge: "MERGING",
+            revert: "REVERTING",
+            cherry_pick: "CHERRY-PICKING",
+            bisect: "BISECTING",
+            am: "AM",
+            graft: "GRAFTING",
+            histedit: "HISTEDITING",
+            unshelve: "UNSHELVING",
+            conflict: "CONFLICT",
+            disabled: true,
+        }
+    }
+}
@@ -1,8 +1,10 @@
 use crate::config::{ModuleConfig, StarshipConfig};
-use crate::configs::StarshipRootConfig;
+use crate::configs::{FullConfig, Palette, StarshipRootConfig, A11Y_CONFIG, LOCALES, SYMBOL_SETS};
 use crate::context_env::Env;
-use crate::module::Module;
-use crate::utils::{create_command, exec_timeout, read_file, CommandOutput, PathExt};
+use crate::module::{Module, ALL_MODULES};
+use crate::utils::{
+    count_lines_timeout, create_command, exec_timeout, read_file, CommandOutput, PathExt,
+};
 
 use crate::modules;
 use crate::utils;
@@ -12,7 +14,6 @@ use gix::{
     state as git_state, Repository, ThreadSafeRepository,
 };
 use once_cell::sync::OnceCell;
-#[cfg(test)]
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::env;
@@ -24,6 +25,7 @@ use std::num::ParseIntError;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::string::String;
+use std::sync::RwLock;
 use std::time::{Duration, Instant};
 use terminal_size::terminal_size;
 
@@ -77,6 +79,22 @@ pub struct Context<'a> {
     /// Starship root config
     pub root_config: StarshipRootConfig,
 
+    /// Modules which the layout pass in `print` switched to their `compact_format`
+    compact_modules: RwLock<HashSet<String>>,
+
+    /// The configs of modules with a `compact_format`, whose `format` is replaced by it
+    compact_configs: OnceCell<HashMap<String, toml::Value>>,
+
+    /// The configs of modules which the symbol set, the locale, accessibility mode or an
+    /// `a11y_format` change, resolved the first time one of them is loaded
+    resolved_configs: OnceCell<HashMap<String, toml::Value>>,
+
+    /// The messages and module labels of the locale, over the English ones
+    catalog: OnceCell<toml::Table>,
+
+    /// The colors of the roles of the theme, resolved the first time a style refers to one
+    theme_roles: OnceCell<Palette>,
+
     /// Avoid issues with unused lifetimes when features are disabled
     _marker: PhantomData<&'a ()>,
 }
@@ -160,7 +178,7 @@ impl<'a> Context<'a> {
 
         let width = properties.terminal_width;
 
-        Context {
+        let mut context = Context {
             config,
             properties,
             current_dir,
@@ -178,8 +196,15 @@ impl<'a> Context<'a> {
             #[cfg(feature = "battery")]
             battery_info_provider: &crate::modules::BatteryInfoProviderImpl,
             root_config,
+            compact_modules: RwLock::default(),
+            compact_configs: OnceCell::new(),
+            resolved_configs: OnceCell::new(),
+            catalog: OnceCell::new(),
+            theme_roles: OnceCell::new(),
             _marker: PhantomData,
-        }
+        };
+        context.load_root_symbols();
+        context
     }
 
     /// Sets the context config, overwriting the existing config
@@ -188,14 +213,45 @@ impl<'a> Context<'a> {
         self.config = StarshipConfig {
             config: Some(config),
         };
+        self.load_root_symbols();
         self
     }
 
+    /// Reloads the root config over the options of the `symbol_set` which are not modules, like
+    /// the `continuation_prompt`
+    fn load_root_symbols(&mut self) {
+        let symbols: toml::Table = toml::from_str(self.symbol_set()).unwrap_or_default();
+        let mut config: toml::Table = symbols
+            .into_iter()
+            .filter(|(key, _)| !ALL_MODULES.contains(&key.as_str()))
+            .collect();
+        if config.is_empty() {
+            return;
+        }
+        if let Some(user_config) = &self.config.config {
+            merge_tables(&mut config, user_config);
+        }
+        self.root_config = StarshipRootConfig::load(&config);
+    }
+
     // Tries to retrieve home directory from a table in testing mode or else retrieves it from the os
     pub fn get_home(&self) -> Option<PathBuf> {
         home_dir(&self.env)
     }
 
+    /// Returns the directory starship uses to persist cached data between prompts
+    #[cfg(not(test))]
+    pub fn get_cache_dir(&self) -> PathBuf {
+        crate::logger::get_log_dir()
+    }
+
+    // Uses the mocked `STARSHIP_CACHE` or the mock root directory in testing mode
+    #[cfg(test)]
+    pub fn get_cache_dir(&self) -> PathBuf {
+        self.get_env_os("STARSHIP_CACHE")
+            .map_or_else(|| self.root_dir.path().join("cache"), PathBuf::from)
+    }
+
     // Retrieves a environment variable from the os or from a table if in testing mode
     #[inline]
     pub fn get_env<K: AsRef<str>>(&self, key: K) -> Option<String> {
@@ -219,12 +275,255 @@ impl<'a> Context<'a> {
 
     /// Create a new module
     pub fn new_module(&self, name: &str) -> Module {
-        let config = self.config.get_module_config(name);
+        let config = self.get_module_config(name);
         let desc = modules::description(name);
 
         Module::new(name, desc, config)
     }
 
+    /// Renders the module with the `compact_format` of its layout from now on
+    pub fn set_compact(&self, name: &str) {
+        if let Ok(mut compact_modules) = self.compact_modules.write() {
+            compact_modules.insert(name.to_string());
+        }
+    }
+
+    /// The config of a module, with the formats which accessibility mode and the layout pass
+    /// in `print` switch it to
+    pub fn get_module_config(&self, name: &str) -> Option<&toml::Value> {
+        let is_compact = self
+            .compact_modules
+            .read()
+            .map_or(false, |compact_modules| compact_modules.contains(name));
+        if !is_compact {
+            return self.get_resolved_module_config(name);
+        }
+
+        let compact_configs = self.compact_configs.get_or_init(|| {
+            self.root_config
+                .layout
+                .iter()
+                .filter_map(|(module, layout)| {
+                    let mut config = self
+                        .get_resolved_module_config(module)
+                        .and_then(toml::Value::as_table)
+                        .cloned()
+                        .unwrap_or_default();
+                    let format = layout.compact_format.clone()?;
+                    config.insert(String::from("format"), toml::Value::String(format));
+                    Some((module.clone(), toml::Value::Table(config)))
+                })
+                .collect()
+        });
+        compact_configs
+            .get(name)
+            .or_else(|| self.get_resolved_module_config(name))
+    }
+
+    /// The config of a module as the user set it over the symbols of the `symbol_set` and the
+    /// labels of the locale, without its `a11y_format`. In accessibility mode, the symbols are
+    /// replaced by the textual ones of `A11Y_CONFIG`, or by the name of the module for modules
+    /// which are not listed there, and the format by the `a11y_format`. Settings of the user
+    /// take precedence over all of these. Named modules like `env_var.NAME` and `custom.NAME`
+    /// are resolved the same way.
+    fn get_resolved_module_config(&self, name: &str) -> Option<&toml::Value> {
+        let resolved_configs = self.resolved_configs.get_or_init(|| {
+            let accessibility = self.root_config.accessibility;
+            let symbols: toml::Table = toml::from_str(self.symbol_set()).unwrap_or_default();
+            let (defaults, a11y_defaults) = match accessibility {
+                true => (
+                    toml::Value::try_from(FullConfig::default()).ok(),
+                    toml::from_str(A11Y_CONFIG).unwrap_or_default(),
+                ),
+                false => (None, toml::Table::new()),
+            };
+
+            // `env_var` is not among the modules, but may hold a top-level module definition
+            let modules = ALL_MODULES
+                .iter()
+                .chain(&["env_var"])
+                .map(|&module| (module.to_string(), self.config.get_module_config(module)));
+            let named_modules = ["env_var", "custom"].into_iter().flat_map(|kind| {
+                self.config
+                    .get_module_config(kind)
+                    .and_then(toml::Value::as_table)
+                    .into_iter()
+                    .flatten()
+                    .filter(|(_, config)| config.is_table())
+                    .map(move |(name, config)| (format!("{kind}.{name}"), Some(config)))
+            });
+
+            modules
+                .chain(named_modules)
+                .filter_map(|(module, user_config)| {
+                    let module = module.as_str();
+                    let user_config = user_config.and_then(toml::Value::as_table);
+                    let module_symbols = symbols.get(module).and_then(toml::Value::as_table);
+                    let module_labels = self.catalog().get(module).and_then(toml::Value::as_table);
+                    let has_a11y_format =
+                        user_config.map_or(false, |config| config.contains_key("a11y_format"));
+                    if !accessibility
+                        && module_symbols.is_none()
+                        && module_labels.is_none()
+                        && !has_a11y_format
+                    {
+                        return None;
+                    }
+
+                    let mut config = module_symbols.cloned().unwrap_or_default();
+                    if let Some(module_labels) = module_labels {
+                        merge_tables(&mut config, module_labels);
+                    }
+                    if accessibility {
+                        let has_symbol = defaults
+                            .as_ref()
+                            .and_then(|defaults| defaults.get(module))
+                            .and_then(|default| default.get("symbol"))
+                            .is_some();
+                        if has_symbol {
+                            let label = format!("{} ", module.replace('_', " "));
+                            config.insert(String::from("symbol"), toml::Value::String(label));
+                        }
+                        if let Some(a11y_config) =
+                            a11y_defaults.get(module).and_then(toml::Value::as_table)
+                        {
+                            merge_tables(&mut config, a11y_config);
+                        }
+                    }
+                    if let Some(user_config) = user_config {
+                        merge_tables(&mut config, user_config);
+                    }
+                    let a11y_format = config.remove("a11y_format");
+                    if let (true, Some(a11y_format)) = (accessibility, a11y_format) {
+                        config.insert(String::from("format"), a11y_format);
+                    }
+                    Some((module.to_string(), toml::Value::Table(config)))
+                })
+                .collect()
+        });
+        resolved_configs
+            .get(name)
+            .or_else(|| match name.split_once('.') {
+                Some(("env_var", name)) => self.config.get_config(&["env_var", name]),
+                Some(("custom", name)) => self.config.get_custom_module_config(name),
+                _ => self.config.get_module_config(name),
+            })
+    }
+
+    /// The symbols of the `symbol_set` of the config, or of `STARSHIP_SYMBOL_SET` if it is set
+    fn symbol_set(&self) -> &'static str {
+        let name = self
+            .get_env("STARSHIP_SYMBOL_SET")
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| self.root_config.symbol_set.clone());
+        match SYMBOL_SETS.iter().find(|(set, _)| *set == name) {
+            Some((_, symbols)) => symbols,
+            None => {
+                log::warn!(
+                    "Unknown symbol set {name:?}, expected one of {:?}",
+                    SYMBOL_SETS.iter().map(|(set, _)| set).collect::<Vec<_>>()
+                );
+                ""
+            }
+        }
+    }
+
+    /// The name of the locale of messages: the `locale` of the config, or else the one of
+    /// `LC_ALL`, `LC_MESSAGES` or `LANG`. A locale like `de_AT.UTF-8` falls back to `de` when
+    /// neither the bundled catalogs nor the `locales` of the config have `de_AT`, and to `en`
+    /// when they have neither.
+    fn locale(&self) -> String {
+        let requested = Some(self.root_config.locale.clone())
+            .filter(|locale| !locale.is_empty())
+            .or_else(|| {
+                ["LC_ALL", "LC_MESSAGES", "LANG"]
+                    .iter()
+                    .find_map(|var| self.get_env(var).filter(|locale| !locale.is_empty()))
+            });
+        let Some(requested) = requested else {
+            return String::from("en");
+        };
+
+        let name = requested
+            .split(['.', '@'])
+            .next()
+            .unwrap_or_default()
+            .replace('-', "_");
+        let language = name.split('_').next().unwrap_or_default();
+        let is_known = |locale: &str| {
+            LOCALES.iter().any(|(bundled, _)| *bundled == locale)
+                || self.root_config.locales.contains_key(locale)
+        };
+        let locale = [name.as_str(), language]
+            .into_iter()
+            .find(|&locale| is_known(locale));
+        match locale {
+            Some(locale) => locale.to_string(),
+            None => {
+                log::debug!("No messages for locale {requested:?}, using English");
+                String::from("en")
+            }
+        }
+    }
+
+    /// The catalog of the locale over the English one, with the `locales` of the config on top
+    fn catalog(&self) -> &toml::Table {
+        self.catalog.get_or_init(|| {
+            let bundled = |locale: &str| {
+                LOCALES
+                    .iter()
+                    .find(|(bundled, _)| *bundled == locale)
+                    .and_then(|(_, catalog)| toml::from_str::<toml::Table>(catalog).ok())
+            };
+            let locale = self.locale();
+            let mut catalog = bundled("en").unwrap_or_default();
+            if let Some(translations) = bundled(&locale) {
+                merge_tables(&mut catalog, &translations);
+            }
+            if let (Some(messages), Some(toml::Value::Table(catalog_messages))) = (
+                self.root_config.locales.get(&locale),
+                catalog.get_mut("messages"),
+            ) {
+                for (key, message) in messages {
+                    catalog_messages.insert(key.clone(), toml::Value::String(message.clone()));
+                }
+            }
+            catalog
+        })
+    }
+
+    /// The message of `key` in the locale, like `status_error` or `unit_byte`
+    pub fn message<'s>(&'s self, key: &'s str) -> &'s str {
+        self.catalog()
+            .get("messages")
+            .and_then(|messages| messages.get(key))
+            .and_then(toml::Value::as_str)
+            .unwrap_or(key)
+    }
+
+    /// The units of days, hours, minutes, seconds and milliseconds in the locale
+    pub fn time_units(&self) -> [&str; 5] {
+        [
+            "unit_day",
+            "unit_hour",
+            "unit_minute",
+            "unit_second",
+            "unit_millisecond",
+        ]
+        .map(|key| self.message(key))
+    }
+
+    /// The decimal and group separators of numbers in the locale
+    pub fn number_separators(&self) -> [&str; 2] {
+        ["number_decimal", "number_group"].map(|key| self.message(key))
+    }
+
+    /// The colors of the roles of the selected theme
+    pub fn theme_roles(&self) -> &Palette {
+        self.theme_roles
+            .get_or_init(|| crate::config::get_theme_roles(self))
+    }
+
     /// Check if `disabled` option of the module is true in configuration file.
     pub fn is_module_disabled_in_config(&self, name: &str) -> bool {
         let config = self.config.get_module_config(name);
@@ -235,6 +534,16 @@ impl<'a> Context<'a> {
         disabled == Some(true)
     }
 
+    /// Whether the `vcs` module is enabled and shows the state of a git repository colocated with
+    /// a Jujutsu repository in place of the git modules.
+    pub fn is_jj_colocated_shown_by_vcs(&self, repo: &Repo) -> bool {
+        if !repo.is_jj_colocated() {
+            return false;
+        }
+        let config = crate::configs::vcs::VcsConfig::try_load(self.get_module_config("vcs"));
+        !config.disabled && config.backends.contains(&"jj")
+    }
+
     pub fn detect_env_vars(&'a self, env_vars: &'a [&'a str]) -> bool {
         env_vars.is_empty() || (env_vars.iter().any(|e| self.get_env(e).is_some()))
     }
@@ -356,6 +665,14 @@ impl<'a> Context<'a> {
             .and_then(|cd| cd.parse::<u128>().ok())
     }
 
+    /// The command line of the last command, if the shell passed it on. It comes from the
+    /// environment rather than the arguments, which other users can read from the process list.
+    pub fn get_last_command(&self) -> Option<String> {
+        self.get_env("STARSHIP_LAST_COMMAND")
+            .map(|command| command.trim().to_string())
+            .filter(|command| !command.is_empty())
+    }
+
     /// Execute a command and return the output on stdout and stderr if successful
     #[inline]
     pub fn exec_cmd<T: AsRef<OsStr> + Debug, U: AsRef<OsStr> + Debug>(
@@ -388,6 +705,36 @@ impl<'a> Context<'a> {
         )
     }
 
+    /// Counts the lines a command writes to stdout within `time_limit`, see
+    /// [`count_lines_timeout`]. Returns whether all lines were counted as well.
+    pub fn count_cmd_lines<T: AsRef<OsStr> + Debug, U: AsRef<OsStr> + Debug>(
+        &self,
+        cmd: T,
+        args: &[U],
+        time_limit: Duration,
+    ) -> Option<(usize, bool)> {
+        log::trace!(
+            "Counting lines of command {:?} with args {:?} from context",
+            cmd,
+            args
+        );
+        #[cfg(test)]
+        {
+            let command = crate::utils::display_command(&cmd, args);
+            if let Some(output) = self
+                .cmd
+                .get(command.as_str())
+                .cloned()
+                .or_else(|| crate::utils::mock_cmd(&cmd, args))
+            {
+                return output.map(|output| (output.stdout.lines().count(), true));
+            }
+        }
+        let mut cmd = create_command(cmd).ok()?;
+        cmd.args(args).current_dir(&self.current_dir);
+        count_lines_timeout(&mut cmd, time_limit)
+    }
+
     /// Attempt to execute several commands with `exec_cmd`, return the results of the first that works
     pub fn exec_cmds_return_first(&self, commands: Vec<Vec<&str>>) -> Option<CommandOutput> {
         commands
@@ -427,6 +774,20 @@ fn home_dir(env: &Env) -> Option<PathBuf> {
     utils::home_dir()
 }
 
+/// Merges the values of `overrides` into `base`, where nested tables are merged in turn
+fn merge_tables(base: &mut toml::Table, overrides: &toml::Table) {
+    for (key, value) in overrides {
+        match (base.get_mut(key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(overrides)) => {
+                merge_tables(base, overrides);
+            }
+            _ => {
+                base.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
 fn get_config_path_os(env: &Env) -> Option<OsString> {
     if let Some(config_path) = env.get_env_os("STARSHIP_CONFIG") {
         return Some(config_path);
@@ -444,6 +805,10 @@ pub struct DirContents {
     folders: HashSet<PathBuf>,
     // HashSet of all extensions found, without dots, e.g. "js" instead of ".js".
     extensions: HashSet<String>,
+    // The directory which was read.
+    base: PathBuf,
+    // Whether all entries were read before the timeout ran out.
+    is_complete: bool,
 }
 
 impl DirContents {
@@ -459,13 +824,15 @@ impl DirContents {
         let mut files: HashSet<PathBuf> = HashSet::new();
         let mut file_names: HashSet<String> = HashSet::new();
         let mut extensions: HashSet<String> = HashSet::new();
+        let mut is_complete = true;
 
         fs::read_dir(base)?
             .enumerate()
             .take_while(|(n, _)| {
-                cfg!(test) // ignore timeout during tests
+                is_complete = cfg!(test) // ignore timeout during tests
                 || n & 0xFF != 0 // only check timeout once every 2^8 entries
-                || start.elapsed() < timeout
+                || start.elapsed() < timeout;
+                is_complete
             })
             .filter_map(|(_, entry)| entry.ok())
             .for_each(|entry| {
@@ -505,12 +872,22 @@ impl DirContents {
             "Building HashSets of directory files, folders and extensions took {:?}",
             start.elapsed()
         );
+        if !is_complete {
+            // Files and folders which weren't read yet are looked up on disk instead
+            log::debug!(
+                "Scanning {:?} timed out after {} entries, results are partial",
+                base,
+                files.len() + folders.len()
+            );
+        }
 
         Ok(Self {
             files,
             file_names,
             folders,
             extensions,
+            base: base.to_path_buf(),
+            is_complete,
         })
     }
 
@@ -519,15 +896,24 @@ impl DirContents {
     }
 
     pub fn has_file(&self, path: &str) -> bool {
-        self.files.contains(Path::new(path))
+        self.files.contains(Path::new(path)) || self.is_unread_file(path)
     }
 
     pub fn has_file_name(&self, name: &str) -> bool {
-        self.file_names.contains(name)
+        self.file_names.contains(name) || self.is_unread_file(name)
     }
 
     pub fn has_folder(&self, path: &str) -> bool {
-        self.folders.contains(Path::new(path))
+        self.folders.contains(Path::new(path)) || !self.is_complete && self.base.join(path).is_dir()
+    }
+
+    // When the scan timed out, a file might exist without having been read. Extensions can't be
+    // looked up like this, so only files and folders are found in partial results.
+    fn is_unread_file(&self, path: &str) -> bool {
+        !self.is_complete && {
+            let path = self.base.join(path);
+            path.symlink_metadata().is_ok() && !path.is_dir()
+        }
     }
 
     pub fn has_extension(&self, ext: &str) -> bool {
@@ -596,12 +982,22 @@ impl Repo {
     pub fn open(&self) -> Repository {
         self.repo.to_thread_local()
     }
+
+    /// Whether the repository is colocated with a Jujutsu repository, whose working copy commit
+    /// git only sees as a detached `HEAD`.
+    pub fn is_jj_colocated(&self) -> bool {
+        self.workdir
+            .as_ref()
+            .map_or(false, |workdir| workdir.join(".jj").is_dir())
+    }
 }
 
 /// Remote repository
 pub struct Remote {
     pub branch: Option<String>,
     pub name: Option<String>,
+    /// The fetch URL of the remote, or of the default remote if there is no upstream
+    pub url: Option<String>,
 }
 
 // A struct of Criteria which will be used to verify current PathBuf is
@@ -701,16 +1097,38 @@ fn get_remote_repository_info(
     repository: &Repository,
     branch_name: Option<&str>,
 ) -> Option<Remote> {
-    let branch_name = branch_name?;
-    let branch = repository
-        .branch_remote_ref(branch_name)
-        .and_then(std::result::Result::ok)
-        .map(|r| r.shorten().to_string());
-    let name = repository
-        .branch_remote_name(branch_name)
-        .map(|n| n.as_bstr().to_string());
-
-    Some(Remote { branch, name })
+    let (branch, name) = match branch_name {
+        Some(branch_name) => (
+            repository
+                .branch_remote_ref(branch_name)
+                .and_then(std::result::Result::ok)
+                .map(|r| r.shorten().to_string()),
+            repository
+                .branch_remote_name(branch_name)
+                .map(|n| n.as_bstr().to_string()),
+        ),
+        None => (None, None),
+    };
+
+    // Without an upstream, e.g. on a detached HEAD, the URL of the default remote is used
+    let url = name
+        .clone()
+        .or_else(|| {
+            repository
+                .remote_default_name(gix::remote::Direction::Fetch)
+                .map(|n| n.to_string())
+        })
+        .and_then(|name| {
+            let remote = repository.find_remote(name.as_str()).ok()?;
+            let url = remote.url(gix::remote::Direction::Fetch)?;
+            Some(url.to_bstring().to_string())
+        });
+
+    if branch_name.is_none() && url.is_none() {
+        return None;
+    }
+
+    Some(Remote { branch, name, url })
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -734,6 +1152,7 @@ pub enum Target {
     Main,
     Right,
     Continuation,
+    Transient,
     Profile(String),
 }
 
@@ -915,6 +1334,38 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_scan_dir_partial() -> Result<(), Box<dyn std::error::Error>> {
+        let node = testdir(&["node_modules/lodash/main.js", "package.json", "index.js"])?;
+        // The scan timed out before reading any entry
+        let partial_dc = DirContents {
+            files: HashSet::new(),
+            file_names: HashSet::new(),
+            folders: HashSet::new(),
+            extensions: HashSet::new(),
+            base: node.path().to_path_buf(),
+            is_complete: false,
+        };
+
+        let scan = |files, extensions, folders| {
+            ScanDir {
+                dir_contents: &partial_dc,
+                files,
+                extensions,
+                folders,
+            }
+            .is_match()
+        };
+        assert!(scan(&["package.json"], &[], &[]));
+        assert!(scan(&[], &[], &["node_modules"]));
+        assert!(!scan(&["node_modules"], &[], &["package.json"]));
+        assert!(!scan(&["package.json", "!index.js"], &[], &[]));
+        assert!(!scan(&[], &["js"], &[]));
+        node.close()?;
+
+        Ok(())
+    }
+
     #[test]
     fn context_constructor_should_canonicalize_current_dir() -> io::Result<()> {
         #[cfg(not(windows))]
@@ -998,6 +1449,119 @@ mod tests {
         assert_eq!(expected_logical_dir, context.logical_dir);
     }
 
+    #[test]
+    fn symbol_set_under_user_config() {
+        let context = default_context().set_config(toml::toml! {
+            symbol_set = "ascii"
+            [character]
+            success_symbol = "[ok](green)"
+            [os.symbols]
+            Ubuntu = "ubuntu "
+        });
+
+        let character = context.get_module_config("character").unwrap();
+        assert_eq!(character["success_symbol"].as_str(), Some("[ok](green)"));
+        assert_eq!(character["error_symbol"].as_str(), Some("[x](bold red)"));
+        let symbols = &context.get_module_config("os").unwrap()["symbols"];
+        assert_eq!(symbols["Ubuntu"].as_str(), Some("ubuntu "));
+        assert_eq!(symbols["Windows"].as_str(), Some("win "));
+        assert!(context.get_module_config("line_break").is_none());
+        assert_eq!(context.root_config.continuation_prompt, "[.](muted) ");
+
+        let context = default_context().set_config(toml::toml! {
+            symbol_set = "ascii"
+            continuation_prompt = "-> "
+        });
+        assert_eq!(context.root_config.continuation_prompt, "-> ");
+        assert_eq!(context.root_config.separator.symbol, ">");
+    }
+
+    #[test]
+    fn ascii_symbol_set_is_ascii() {
+        fn find_non_ascii(path: &str, value: &toml::Value, found: &mut Vec<String>) {
+            match value {
+                toml::Value::String(string) if !string.is_ascii() => {
+                    found.push(format!("{path} = {string:?}"));
+                }
+                toml::Value::Array(values) => values
+                    .iter()
+                    .for_each(|value| find_non_ascii(path, value, found)),
+                toml::Value::Table(table) => table.iter().for_each(|(key, value)| {
+                    find_non_ascii(&format!("{path}.{key}"), value, found);
+                }),
+                _ => {}
+            }
+        }
+
+        let mut config = toml::Table::try_from(FullConfig::default()).unwrap();
+        let (_, symbols) = SYMBOL_SETS.iter().find(|(set, _)| *set == "ascii").unwrap();
+        merge_tables(&mut config, &toml::from_str(symbols).unwrap());
+
+        let mut found = Vec::new();
+        find_non_ascii("", &toml::Value::Table(config), &mut found);
+        assert!(found.is_empty(), "{found:#?}");
+    }
+
+    #[test]
+    fn symbol_set_from_env() {
+        let mut context = default_context().set_config(toml::toml! {
+            symbol_set = "nerd-font"
+        });
+        context
+            .env
+            .insert("STARSHIP_SYMBOL_SET", String::from("ascii"));
+        let rust = context.get_module_config("rust").unwrap();
+        assert_eq!(rust["symbol"].as_str(), Some("rs "));
+
+        let context = default_context().set_config(toml::toml! {
+            symbol_set = "wingdings"
+        });
+        assert!(context.get_module_config("rust").is_none());
+    }
+
+    #[test]
+    fn locale_from_env() {
+        let mut context = default_context();
+        context.env.insert("LANG", String::from("fr_FR.UTF-8"));
+        context
+            .env
+            .insert("LC_MESSAGES", String::from("de_AT.UTF-8@euro"));
+        assert_eq!(context.locale(), "de");
+        assert_eq!(context.message("status_error"), "FEHLER");
+        assert_eq!(context.time_units(), ["T", "h", "m", "s", "ms"]);
+        assert_eq!(context.number_separators(), [",", "."]);
+
+        let mut context = default_context();
+        context.env.insert("LANG", String::from("C"));
+        assert_eq!(context.locale(), "en");
+        assert_eq!(context.message("status_error"), "ERROR");
+        assert_eq!(context.number_separators(), [".", ","]);
+    }
+
+    #[test]
+    fn locale_from_config() {
+        let mut context = default_context().set_config(toml::toml! {
+            locale = "nl"
+            [locales.nl]
+            status_error = "FOUT"
+            [git_state]
+            merge = "SAMENVOEGEN"
+        });
+        context.env.insert("LANG", String::from("fr_FR.UTF-8"));
+        assert_eq!(context.locale(), "nl");
+        assert_eq!(context.message("status_error"), "FOUT");
+        assert_eq!(context.message("status_usage"), "USAGE");
+
+        let mut context = default_context().set_config(toml::toml! {
+            [git_state]
+            merge = "SAMENVOEGEN"
+        });
+        context.env.insert("LANG", String::from("fr_FR.UTF-8"));
+        let git_state = context.get_module_config("git_state").unwrap();
+        assert_eq!(git_state["merge"].as_str(), Some("SAMENVOEGEN"));
+        assert_eq!(git_state["rebase"].as_str(), Some("REBASAGE"));
+    }
+
     #[test]
     fn set_config_method_overwrites_constructor() {
         let context = default_context();
@@ -51,7 +51,9 @@ fn parse_style(style: Pair<Rule>) -> Vec<StyleElement> {
     style
         .into_inner()
         .map(|pair| match pair.as_rule() {
-            Rule::string => StyleElement::Text(pair.as_str().into()),
+            Rule::string | Rule::style_open | Rule::style_close => {
+                StyleElement::Text(pair.as_str().into())
+            }
             Rule::variable => StyleElement::Variable(parse_variable(pair).into()),
             _ => unreachable!(),
         })
@@ -5,8 +5,9 @@ use std::borrow::Cow;
 use std::collections::{BTreeMap, BTreeSet};
 use std::error::Error;
 use std::fmt;
+use unicode_segmentation::UnicodeSegmentation;
 
-use crate::config::parse_style_string;
+use crate::config::{parse_style_gradient, parse_style_string, StyleGradient};
 use crate::context::{Context, Shell};
 use crate::segment::Segment;
 
@@ -245,21 +246,20 @@ impl<'a> StringFormatter<'a> {
             style_variables: &'a StyleVariableMapType<'a>,
             context: Option<&Context>,
         ) -> Result<Vec<Segment>, StringFormatterError> {
-            let style = parse_style(textgroup.style, style_variables, context);
-            parse_format(
-                textgroup.format,
-                style.transpose()?,
-                variables,
-                style_variables,
-                context,
-            )
+            let style_string = parse_style(textgroup.style, style_variables)?;
+            let style = parse_style_string(&style_string, context);
+            let segments =
+                parse_format(textgroup.format, style, variables, style_variables, context)?;
+            Ok(match parse_style_gradient(&style_string, context) {
+                Some(gradient) => apply_gradient(segments, &gradient),
+                None => segments,
+            })
         }
 
         fn parse_style<'a>(
             style: Vec<StyleElement>,
             variables: &'a StyleVariableMapType<'a>,
-            context: Option<&Context>,
-        ) -> Option<Result<Style, StringFormatterError>> {
+        ) -> Result<String, StringFormatterError> {
             let style_strings = style
                 .into_iter()
                 .map(|style| match style {
@@ -273,13 +273,32 @@ impl<'a> StringFormatter<'a> {
                     }
                 })
                 .collect::<Result<Vec<Cow<str>>, StringFormatterError>>();
-            style_strings
-                .map(|style_strings| {
-                    let style_string: String =
-                        style_strings.iter().flat_map(|s| s.chars()).collect();
-                    parse_style_string(&style_string, context)
+            style_strings.map(|style_strings| style_strings.concat())
+        }
+
+        /// Splits the text of the segments into graphemes with the colors of the gradient
+        fn apply_gradient(segments: Vec<Segment>, gradient: &StyleGradient) -> Vec<Segment> {
+            let count = segments
+                .iter()
+                .filter(|segment| matches!(segment, Segment::Text(_)))
+                .map(|segment| segment.value().graphemes(true).count())
+                .sum();
+            let mut index = 0;
+            segments
+                .into_iter()
+                .flat_map(|segment| match segment {
+                    Segment::Text(_) => segment
+                        .value()
+                        .graphemes(true)
+                        .flat_map(|grapheme| {
+                            let style = gradient.apply(segment.style(), index, count);
+                            index += 1;
+                            Segment::from_text(Some(style), grapheme)
+                        })
+                        .collect(),
+                    segment => vec![segment],
                 })
-                .transpose()
+                .collect()
         }
 
         fn parse_format<'a>(
@@ -402,13 +421,19 @@ impl<'a> StringFormatter<'a> {
             Ok(results?.into_iter().flatten().collect())
         }
 
-        parse_format(
+        let mut segments = parse_format(
             self.format,
             default_style,
             &self.variables,
             &self.style_variables,
             context,
-        )
+        )?;
+        if context.map_or(false, |context| context.root_config.accessibility) {
+            segments
+                .iter_mut()
+                .for_each(Segment::strip_decorative_symbols);
+        }
+        Ok(segments)
     }
 }
 
@@ -569,6 +594,43 @@ mod tests {
         match_next!(result_iter, "inner", inner_style);
     }
 
+    #[test]
+    fn test_color_function_in_textgroup() {
+        const FORMAT_STR: &str = "[text](bold fg:mix(red, #0000ff, 50%) bg:darken(#ffffff, 0.5))";
+        let style = Some(Color::Rgb(103, 0, 128).bold().on(Color::Rgb(128, 128, 128)));
+
+        let formatter = StringFormatter::new(FORMAT_STR).unwrap().map(empty_mapper);
+        let result = formatter.parse(None, None).unwrap();
+        let mut result_iter = result.iter();
+        match_next!(result_iter, "text", style);
+        assert!(result_iter.next().is_none());
+    }
+
+    #[test]
+    fn test_gradient_textgroup() {
+        const FORMAT_STR: &str = "[a$var](bold fg:gradient(#000000, #ff0000, #ffffff))";
+
+        let formatter = StringFormatter::new(FORMAT_STR)
+            .unwrap()
+            .map(|variable| match variable {
+                "var" => Some(Ok("bc🇩🇪d")),
+                _ => None,
+            });
+        let result = formatter.parse(None, None).unwrap();
+        let mut result_iter = result.iter();
+        match_next!(result_iter, "a", Some(Color::Rgb(0, 0, 0).bold()));
+        match_next!(result_iter, "b", Some(Color::Rgb(128, 0, 0).bold()));
+        match_next!(result_iter, "c", Some(Color::Rgb(255, 0, 0).bold()));
+        match_next!(result_iter, "🇩🇪", Some(Color::Rgb(255, 128, 128).bold()));
+        match_next!(result_iter, "d", Some(Color::Rgb(255, 255, 255).bold()));
+        assert!(result_iter.next().is_none());
+    }
+
+    #[test]
+    fn test_unbalanced_style_parentheses() {
+        assert!(StringFormatter::new("[text](fg:mix(red, blue, 0.3)").is_err());
+    }
+
     #[test]
     fn test_style_variable_nested() {
         const STYLE_VAR_NAME: &str = "style";
@@ -24,6 +24,9 @@ starship_preexec() {
     if [ "$STARSHIP_PREEXEC_READY" = "true" ]; then
         STARSHIP_PREEXEC_READY=false
         STARSHIP_START_TIME=$(::STARSHIP:: time)
+        # bash-preexec passes on the whole command line. Without it, the DEBUG trap only sees
+        # the first simple command which is about to run, like `cd x` for `cd x && vim y`.
+        STARSHIP_LAST_COMMAND=${2-$BASH_COMMAND}
     fi
 
     : "$PREV_LAST_ARG"
@@ -56,11 +59,17 @@ starship_precmd() {
     if [[ $STARSHIP_START_TIME ]]; then
         STARSHIP_END_TIME=$(::STARSHIP:: time)
         STARSHIP_DURATION=$((STARSHIP_END_TIME - STARSHIP_START_TIME))
-        PS1="$(::STARSHIP:: prompt --terminal-width="$COLUMNS" --status=$STARSHIP_CMD_STATUS --pipestatus="${STARSHIP_PIPE_STATUS[*]}" --jobs="$NUM_JOBS" --cmd-duration=$STARSHIP_DURATION)"
+        # The command line is passed on in the environment, as arguments show up in `ps`. The
+        # start time tells the history module that the prompt is drawn for a new command.
+        PS1="$(STARSHIP_LAST_COMMAND="$STARSHIP_LAST_COMMAND" STARSHIP_COMMAND_ID="$STARSHIP_START_TIME" ::STARSHIP:: prompt --terminal-width="$COLUMNS" --status=$STARSHIP_CMD_STATUS --pipestatus="${STARSHIP_PIPE_STATUS[*]}" --jobs="$NUM_JOBS" --cmd-duration=$STARSHIP_DURATION)"
         unset STARSHIP_START_TIME
     else
         PS1="$(::STARSHIP:: prompt --terminal-width="$COLUMNS" --status=$STARSHIP_CMD_STATUS --pipestatus="${STARSHIP_PIPE_STATUS[*]}" --jobs="$NUM_JOBS")"
+        unset STARSHIP_DURATION STARSHIP_LAST_COMMAND
     fi
+    # The transient prompt is rendered from PS0 once a command line is accepted, with the state
+    # of this prompt and the history number it started at
+    STARSHIP_JOBS_COUNT=$NUM_JOBS STARSHIP_HISTCMD=$HISTCMD
     STARSHIP_PREEXEC_READY=true  # Signal that we can safely restart the timer
 }
 
@@ -68,7 +77,7 @@ starship_precmd() {
 # then hook our functions into their framework.
 if [[ "${__bp_imported:-}" == "defined" || $preexec_functions || $precmd_functions ]]; then
     # bash-preexec needs a single function--wrap the args into a closure and pass
-    starship_preexec_all(){ starship_preexec "$_"; }
+    starship_preexec_all(){ starship_preexec "$_" "$1"; }
     preexec_functions+=(starship_preexec_all)
     precmd_functions+=(starship_precmd)
 else
@@ -111,6 +120,65 @@ STARSHIP_SESSION_KEY="$RANDOM$RANDOM$RANDOM$RANDOM$RANDOM"; # Random generates a
 STARSHIP_SESSION_KEY="${STARSHIP_SESSION_KEY}0000000000000000" # Pad it to 16+ chars.
 export STARSHIP_SESSION_KEY=${STARSHIP_SESSION_KEY:0:16}; # Trim to 16-digits if excess.
 
+# Bash has no hook to redraw the prompt once a command line is accepted, so the transient
+# prompt is drawn from PS0 (bash 4.4+): move the cursor back up to the start of the prompt,
+# clear the screen below it and print the transient prompt followed by the command line.
+# The number of rows is estimated from the length of the prompt and the command, so this is
+# a best effort for prompts with wide characters or commands edited across several lines.
+__starship_transient() {
+    local visible=$PS1 head rest cmd line rows width prompt
+    # The command line wasn't added to the history, because of HISTCONTROL or `set +o history`,
+    # so it can't be recovered and the prompt is left as it is
+    [[ $HISTCMD != "$STARSHIP_HISTCMD" ]] || return
+
+    # Remove the non-printing sequences between \[ and \]
+    while [[ $visible == *'\['*'\]'* ]]; do
+        head=${visible%%'\['*} rest=${visible#*'\['}
+        visible=$head${rest#*'\]'}
+    done
+
+    cmd=$(HISTTIMEFORMAT='' builtin history 1)
+    [[ $cmd =~ ^\ *[0-9]+\*?\ +(.*)$ ]] || return
+    cmd=${BASH_REMATCH[1]}
+
+    rest=${visible//[!$'\n']/}
+    rows=${#rest}
+    width=${visible##*$'\n'}
+    width=${#width}
+    while IFS= read -r line; do
+        (( width += ${#line} ))
+        (( rows += width > 0 ? (width - 1) / ${COLUMNS:-80} + 1 : 1 ))
+        width=0
+    done <<< "$cmd"
+
+    prompt="$(STARSHIP_LAST_COMMAND="${STARSHIP_LAST_COMMAND:-}" ::STARSHIP:: prompt --transient --terminal-width="$COLUMNS" --status=$STARSHIP_CMD_STATUS --pipestatus="${STARSHIP_PIPE_STATUS[*]}" --jobs="$STARSHIP_JOBS_COUNT" ${STARSHIP_DURATION:+--cmd-duration=$STARSHIP_DURATION})"
+    # The prompt is printed directly, so drop the markers for non-printing sequences
+    prompt=${prompt//\\[/}
+    prompt=${prompt//\\]/}
+
+    printf '\e[%dA\r\e[J%s%s\n\r' "$rows" "$prompt" "$cmd"
+}
+
+# Replace the prompt of each accepted command line with `transient_format`
+enable_transience() {
+    if (( BASH_VERSINFO[0] < 4 || (BASH_VERSINFO[0] == 4 && BASH_VERSINFO[1] < 4) )); then
+        echo "starship: the transient prompt requires bash 4.4 or newer" >&2
+        return 1
+    fi
+    if [[ -z $STARSHIP_TRANSIENT_ENABLED ]]; then
+        STARSHIP_TRANSIENT_ENABLED=1
+        _PRESERVED_PS0=$PS0
+        PS0='$(__starship_transient)'"$PS0"
+    fi
+}
+
+disable_transience() {
+    if [[ $STARSHIP_TRANSIENT_ENABLED ]]; then
+        PS0=$_PRESERVED_PS0
+        unset STARSHIP_TRANSIENT_ENABLED _PRESERVED_PS0
+    fi
+}
+
 # Set the continuation prompt
 PS2="$(::STARSHIP:: prompt --continuation)"
 
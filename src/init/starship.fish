@@ -10,14 +10,20 @@ function fish_prompt
     # Account for changes in variable name between v2.7 and v3.0
     set STARSHIP_DURATION "$CMD_DURATION$cmd_duration"
     set STARSHIP_JOBS (count (jobs -p))
-    if test "$TRANSIENT" = "1"
-        # Clear from cursor to end of screen as `commandline -f repaint` does not do this
-        # See https://github.com/fish-shell/fish-shell/issues/8418
-        printf \e\[0J
+    # Pass the command line on in the environment, as arguments show up in `ps`
+    set -lx STARSHIP_LAST_COMMAND $STARSHIP_LAST_COMMAND
+    set -lx STARSHIP_COMMAND_ID $STARSHIP_COMMAND_ID
+    # fish 4.1+ passes --final-rendering when `fish_transient_prompt` is set
+    if contains -- --final-rendering $argv; or test "$TRANSIENT" = "1"
+        if test "$TRANSIENT" = "1"
+            # Clear from cursor to end of screen as `commandline -f repaint` does not do this
+            # See https://github.com/fish-shell/fish-shell/issues/8418
+            printf \e\[0J
+        end
         if type -q starship_transient_prompt_func
             starship_transient_prompt_func
         else
-            printf "\e[1;32m❯\e[0m "
+            ::STARSHIP:: prompt --transient --terminal-width="$COLUMNS" --status=$STARSHIP_CMD_STATUS --pipestatus="$STARSHIP_CMD_PIPESTATUS" --keymap=$STARSHIP_KEYMAP --cmd-duration=$STARSHIP_DURATION --jobs=$STARSHIP_JOBS
         end
     else
         ::STARSHIP:: prompt --terminal-width="$COLUMNS" --status=$STARSHIP_CMD_STATUS --pipestatus="$STARSHIP_CMD_PIPESTATUS" --keymap=$STARSHIP_KEYMAP --cmd-duration=$STARSHIP_DURATION --jobs=$STARSHIP_JOBS
@@ -36,7 +42,10 @@ function fish_right_prompt
     # Account for changes in variable name between v2.7 and v3.0
     set STARSHIP_DURATION "$CMD_DURATION$cmd_duration"
     set STARSHIP_JOBS (count (jobs -p))
-    if test "$TRANSIENT" = "1"
+    # Pass the command line on in the environment, as arguments show up in `ps`
+    set -lx STARSHIP_LAST_COMMAND $STARSHIP_LAST_COMMAND
+    set -lx STARSHIP_COMMAND_ID $STARSHIP_COMMAND_ID
+    if contains -- --final-rendering $argv; or test "$TRANSIENT" = "1"
         if type -q starship_transient_rprompt_func
             starship_transient_rprompt_func
         else
@@ -55,6 +64,14 @@ builtin functions -e fish_mode_prompt
 
 set -gx STARSHIP_SHELL "fish"
 
+# Remember the command line for the per-command options of cmd_duration, and count the
+# commands, so the history module tells a new command apart from a redraw of the prompt
+set -g STARSHIP_COMMAND_ID 0
+function __starship_preexec --on-event fish_preexec
+    set -g STARSHIP_LAST_COMMAND $argv
+    set -g STARSHIP_COMMAND_ID (math $STARSHIP_COMMAND_ID + 1)
+end
+
 # Transience related functions
 function reset-transient --on-event fish_postexec
     set -g TRANSIENT 0
@@ -70,18 +87,36 @@ function transient_execute
     commandline -f execute
 end
 
+# fish 4.1 and newer render the final prompt natively, older versions use key bindings
+function __starship_native_transience
+    set -l fish_version (string match -r '^(\d+)\.(\d+)' -- $version)
+    test -n "$fish_version[2]"; and begin
+        test "$fish_version[2]" -gt 4; or begin
+            test "$fish_version[2]" -eq 4; and test "$fish_version[3]" -ge 1
+        end
+    end
+end
+
 # --user is the default, but listed anyway to make it explicit.
-function enable_transience --description 'enable transient prompt keybindings'
-    bind --user \r transient_execute
-    bind --user -M insert \r transient_execute
+function enable_transience --description 'enable transient prompt'
+    if __starship_native_transience
+        set -g fish_transient_prompt 1
+    else
+        bind --user \r transient_execute
+        bind --user -M insert \r transient_execute
+    end
 end
 
 # Erase the transient prompt related key bindings.
 # --user is the default, but listed anyway to make it explicit.
 # Erasing a user binding will revert to the preset.
-function disable_transience --description 'remove transient prompt keybindings'
-    bind --user -e \r
-    bind --user -M insert -e \r
+function disable_transience --description 'disable transient prompt'
+    if __starship_native_transience
+        set -e fish_transient_prompt
+    else
+        bind --user -e \r
+        bind --user -M insert -e \r
+    end
 end
 
 # Set up the session key that will be used to store logs
@@ -32,7 +32,8 @@ pub fn get_log_dir() -> PathBuf {
         })
 }
 
-/// Deletes all log files in the log directory that were modified more than 24 hours ago.
+/// Deletes all log, history and right prompt files of sessions in the log directory that were
+/// modified more than 24 hours ago.
 pub fn cleanup_log_files<P: AsRef<Path>>(path: P) {
     let log_dir = path.as_ref();
     let Ok(log_files) = fs::read_dir(log_dir) else {
@@ -46,15 +47,16 @@ pub fn cleanup_log_files<P: AsRef<Path>>(path: P) {
             continue;
         };
 
-        // Avoid deleting files that don't look like log files.
-        if !file
-            .path()
+        // Avoid deleting files that don't look like session files.
+        let path = file.path();
+        let extension = path.extension().unwrap_or_default();
+        if !path
             .file_name()
             .unwrap_or_default()
             .to_str()
             .unwrap_or_default()
             .starts_with("session_")
-            || file.path().extension() != Some("log".as_ref())
+            || (extension != "log" && extension != "history" && extension != "right")
         {
             continue;
         }
@@ -368,12 +370,14 @@ mod test {
 
         // Should be deleted
         let old_file = log_dir.path().join("session_old.log");
+        let old_history_file = log_dir.path().join("session_old.history");
 
         for file in &[
             &non_matching_file,
             &non_matching_file2,
             &new_file,
             &old_file,
+            &old_history_file,
         ] {
             File::create(file)?;
         }
@@ -384,6 +388,7 @@ mod test {
             &non_matching_file,
             &non_matching_file2,
             &old_file,
+            &old_history_file,
             &directory,
         ] {
             utimes(file.as_path(), &TimeVal::new(0, 0), &TimeVal::new(0, 0))?;
@@ -404,6 +409,10 @@ mod test {
         }
 
         assert!(!old_file.exists(), "File {old_file:?} should not exist");
+        assert!(
+            !old_history_file.exists(),
+            "File {old_history_file:?} should not exist"
+        );
 
         log_dir.close()
     }
@@ -95,6 +95,14 @@ enum Commands {
         /// Print the continuation prompt (instead of the standard left prompt)
         #[clap(long, conflicts_with = "right", conflicts_with = "profile")]
         continuation: bool,
+        /// Print the transient prompt, which replaces the prompt of a submitted command line
+        #[clap(
+            long,
+            conflicts_with = "right",
+            conflicts_with = "profile",
+            conflicts_with = "continuation"
+        )]
+        transient: bool,
         #[clap(flatten)]
         properties: Properties,
     },
@@ -181,12 +189,14 @@ fn main() {
             right,
             profile,
             continuation,
+            transient,
         } => {
-            let target = match (right, profile, continuation) {
-                (true, _, _) => Target::Right,
-                (_, Some(profile_name), _) => Target::Profile(profile_name),
-                (_, _, true) => Target::Continuation,
-                (_, _, _) => Target::Main,
+            let target = match (right, profile, continuation, transient) {
+                (true, _, _, _) => Target::Right,
+                (_, Some(profile_name), _, _) => Target::Profile(profile_name),
+                (_, _, true, _) => Target::Continuation,
+                (_, _, _, true) => Target::Transient,
+                (_, _, _, _) => Target::Main,
             };
             print::prompt(properties, target)
         }
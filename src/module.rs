@@ -1,4 +1,5 @@
 use crate::context::Shell;
+use crate::print::PromptWidth;
 use crate::segment;
 use crate::segment::{FillSegment, Segment};
 use crate::utils::wrap_colorseq_for_shell;
@@ -38,6 +39,7 @@ pub const ALL_MODULES: &[&str] = &[
     "gcloud",
     "git_branch",
     "git_commit",
+    "git_context",
     "git_metrics",
     "git_state",
     "git_status",
@@ -48,6 +50,10 @@ pub const ALL_MODULES: &[&str] = &[
     "haxe",
     "helm",
     "hg_branch",
+    "hg_commit",
+    "hg_state",
+    "hg_status",
+    "history",
     "hostname",
     "java",
     "jobs",
@@ -91,6 +97,7 @@ pub const ALL_MODULES: &[&str] = &[
     "time",
     "username",
     "vagrant",
+    "vcs",
     "vcsh",
     "vlang",
     "zig",
@@ -161,7 +168,11 @@ impl<'a> Module<'a> {
         self.ansi_strings_for_shell(Shell::Unknown, None)
     }
 
-    pub fn ansi_strings_for_shell(&self, shell: Shell, width: Option<usize>) -> Vec<AnsiString> {
+    pub fn ansi_strings_for_shell(
+        &self,
+        shell: Shell,
+        width: Option<PromptWidth>,
+    ) -> Vec<AnsiString> {
         let mut iter = self.segments.iter().peekable();
         let mut ansi_strings: Vec<AnsiString> = Vec::new();
         while iter.peek().is_some() {
@@ -194,11 +205,13 @@ fn ansi_strings_modified(ansi_strings: Vec<AnsiString>, shell: Shell) -> Vec<Ans
         .collect::<Vec<AnsiString>>()
 }
 
-fn ansi_line<'a, I>(segments: &mut I, term_width: Option<usize>) -> Vec<AnsiString<'a>>
+fn ansi_line<'a, I>(segments: &mut I, width: Option<PromptWidth>) -> Vec<AnsiString<'a>>
 where
     I: Iterator<Item = &'a Segment>,
 {
+    let ambiguous_wide = width.map_or(false, |w| w.ambiguous_wide);
     let mut used = 0usize;
+    let mut last_line = true;
     let mut current: Vec<AnsiString> = Vec::new();
     let mut chunks: Vec<(Vec<AnsiString>, &FillSegment)> = Vec::new();
 
@@ -209,12 +222,13 @@ where
                 current = Vec::new();
             }
             _ => {
-                used += segment.width_graphemes();
+                used += segment.width_graphemes_with(ambiguous_wide);
                 current.push(segment.ansi_string());
             }
         }
 
         if matches!(segment, Segment::LineTerm) {
+            last_line = false;
             break;
         }
     }
@@ -222,14 +236,15 @@ where
     if chunks.is_empty() {
         current
     } else {
-        let fill_size = term_width
+        let fill_size = width
+            .map(|w| w.available(used, last_line))
             .and_then(|tw| if tw > used { Some(tw - used) } else { None })
             .map(|remaining| remaining / chunks.len());
         chunks
             .into_iter()
             .flat_map(|(strs, fill)| {
                 strs.into_iter()
-                    .chain(std::iter::once(fill.ansi_string(fill_size)))
+                    .chain(std::iter::once(fill.ansi_string(fill_size, ambiguous_wide)))
             })
             .chain(current.into_iter())
             .collect::<Vec<AnsiString>>()
@@ -1,16 +1,18 @@
-use std::collections::HashMap;
+use std::borrow::Cow;
 use std::path::PathBuf;
 use std::str::FromStr;
 
-use chrono::DateTime;
+use chrono::{DateTime, FixedOffset};
 use ini::Ini;
 use once_cell::unsync::OnceCell;
+use sha1::{Digest, Sha1};
 
+use super::utils::cloud::{self, CloudEnvironment};
 use super::{Context, Module, ModuleConfig};
 
 use crate::configs::aws::AwsConfig;
 use crate::formatter::StringFormatter;
-use crate::utils::render_time;
+use crate::utils::{self, render_time};
 
 type Profile = String;
 type Region = String;
@@ -116,10 +118,113 @@ fn get_aws_profile_and_region(
     }
 }
 
+fn get_aws_dir(context: &Context) -> Option<PathBuf> {
+    let mut home = context.get_home()?;
+    home.push(".aws");
+    Some(home)
+}
+
+// Older versions of the AWS CLI write timestamps like `2023-06-20T15:58:52UTC`
+fn parse_aws_timestamp(timestamp: &str) -> Option<DateTime<FixedOffset>> {
+    let timestamp = match timestamp.strip_suffix("UTC") {
+        Some(timestamp) => Cow::Owned(format!("{timestamp}Z")),
+        None => Cow::Borrowed(timestamp),
+    };
+    DateTime::parse_from_rfc3339(&timestamp).ok()
+}
+
+// The token cache of `aws sso login` is keyed by the SHA-1 of the `sso_session` name,
+// or of the `sso_start_url` for legacy SSO profiles.
+fn get_sso_expiration(
+    context: &Context,
+    aws_profile: Option<&Profile>,
+    aws_config: &AwsConfigFile,
+) -> Option<DateTime<FixedOffset>> {
+    let config = get_config(context, aws_config)?;
+    let section = get_profile_config(config, aws_profile)?;
+    let cache_key = section
+        .get("sso_session")
+        .or_else(|| section.get("sso_start_url"))?;
+
+    let mut hasher = Sha1::new();
+    hasher.update(cache_key.as_bytes());
+    let mut path = get_aws_dir(context)?;
+    path.push("sso/cache");
+    path.push(format!("{}.json", utils::encode_to_hex(&hasher.finalize())));
+
+    let contents = utils::read_file(path).ok()?;
+    let token: serde_json::Value = serde_json::from_str(&contents).ok()?;
+    parse_aws_timestamp(token["expiresAt"].as_str()?)
+}
+
+struct AssumedRole {
+    arn: String,
+    expiration: Option<DateTime<FixedOffset>>,
+}
+
+// The AWS CLI caches the credentials of an assumed role under the SHA-1 of the JSON encoded
+// `AssumeRole` arguments of the profile, with sorted keys and Python's default separators.
+// The `RoleSessionName` is left out of the key, as it is generated when it is not set.
+fn get_cached_assumed_role(
+    context: &Context,
+    profile_config: &ini::Properties,
+    role_arn: &str,
+) -> Option<AssumedRole> {
+    let args = [
+        ("DurationSeconds", "duration_seconds"),
+        ("ExternalId", "external_id"),
+        ("RoleArn", "role_arn"),
+        ("SerialNumber", "mfa_serial"),
+    ]
+    .iter()
+    .filter_map(|(arg, key)| {
+        let value = profile_config.get(key)?;
+        let value = match value.parse::<u64>() {
+            Ok(seconds) if *key == "duration_seconds" => seconds.to_string(),
+            _ => serde_json::to_string(value).ok()?,
+        };
+        Some(format!("\"{arg}\": {value}"))
+    })
+    .collect::<Vec<_>>()
+    .join(", ");
+
+    let mut hasher = Sha1::new();
+    hasher.update(format!("{{{args}}}").as_bytes());
+    let mut path = get_aws_dir(context)?;
+    path.push("cli/cache");
+    path.push(format!("{}.json", utils::encode_to_hex(&hasher.finalize())));
+
+    let contents = utils::read_file(path).ok()?;
+    let cached: serde_json::Value = serde_json::from_str(&contents).ok()?;
+    let arn = cached["AssumedRoleUser"]["Arn"].as_str()?;
+
+    // A role `arn:aws:iam::<account>:role/<path>/<name>` is cached with the session
+    // `arn:aws:sts::<account>:assumed-role/<name>/<session name>`.
+    let parts: Vec<&str> = role_arn.splitn(6, ':').collect();
+    let [_, partition, _, _, account_id, resource] = parts[..] else {
+        return None;
+    };
+    let role_name = resource.rsplit('/').next()?;
+    if !arn.starts_with(&format!(
+        "arn:{partition}:sts::{account_id}:assumed-role/{role_name}/"
+    )) {
+        return None;
+    }
+
+    Some(AssumedRole {
+        arn: arn.to_string(),
+        expiration: cached["Credentials"]["Expiration"]
+            .as_str()
+            .and_then(parse_aws_timestamp),
+    })
+}
+
 fn get_credentials_duration(
     context: &Context,
     aws_profile: Option<&Profile>,
+    aws_config: &AwsConfigFile,
     aws_creds: &AwsCredsFile,
+    assumed_role: Option<&AssumedRole>,
 ) -> Option<i64> {
     let expiration_env_vars = [
         "AWS_CREDENTIAL_EXPIRATION",
@@ -132,26 +237,22 @@ fn get_credentials_duration(
     {
         chrono::DateTime::parse_from_rfc3339(&expiration_date).ok()
     } else {
-        let creds = get_creds(context, aws_creds)?;
-        let section = get_profile_creds(creds, aws_profile)?;
-
         let expiration_keys = ["expiration", "x_security_token_expires"];
-        expiration_keys
-            .iter()
-            .find_map(|expiration_key| section.get(expiration_key))
+        get_creds(context, aws_creds)
+            .and_then(|creds| get_profile_creds(creds, aws_profile))
+            .and_then(|section| {
+                expiration_keys
+                    .iter()
+                    .find_map(|expiration_key| section.get(expiration_key))
+            })
             .and_then(|expiration| DateTime::parse_from_rfc3339(expiration).ok())
+            .or_else(|| assumed_role?.expiration)
+            .or_else(|| get_sso_expiration(context, aws_profile, aws_config))
     }?;
 
     Some(expiration_date.timestamp() - chrono::Local::now().timestamp())
 }
 
-fn alias_name(name: Option<String>, aliases: &HashMap<String, &str>) -> Option<String> {
-    name.as_ref()
-        .and_then(|n| aliases.get(n))
-        .map(|&a| a.to_string())
-        .or(name)
-}
-
 fn has_credential_process_or_sso(
     context: &Context,
     aws_profile: Option<&Profile>,
@@ -232,6 +333,16 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("aws");
     let config: AwsConfig = AwsConfig::try_load(module.config);
 
+    if !cloud::is_detected(
+        context,
+        &config.detect_env_vars,
+        &config.detect_files,
+        &config.detect_folders,
+        &config.detect_extensions,
+    ) {
+        return None;
+    }
+
     let aws_config = OnceCell::new();
     let aws_creds = OnceCell::new();
 
@@ -251,34 +362,87 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         return None;
     }
 
+    let profile_config = get_config(context, &aws_config)
+        .and_then(|config| get_profile_config(config, aws_profile.as_ref()));
+    let role_arn = profile_config.and_then(|section| section.get("role_arn"));
+    let assumed_role = profile_config
+        .zip(role_arn)
+        .and_then(|(section, role_arn)| get_cached_assumed_role(context, section, role_arn));
+
     let duration = {
-        get_credentials_duration(context, aws_profile.as_ref(), &aws_creds).map(|duration| {
+        get_credentials_duration(
+            context,
+            aws_profile.as_ref(),
+            &aws_config,
+            &aws_creds,
+            assumed_role.as_ref(),
+        )
+        .map(|duration| {
             if duration > 0 {
-                render_time((duration * 1000) as u128, false)
+                render_time((duration * 1000) as u128, false, context.time_units())
             } else {
                 config.expiration_symbol.to_string()
             }
         })
     };
 
-    let mapped_region = alias_name(aws_region, &config.region_aliases);
+    let role_arn = assumed_role
+        .map(|role| role.arn)
+        .or_else(|| role_arn.map(ToOwned::to_owned));
+
+    // The account id of an ARN is its fifth field, e.g. `arn:aws:iam::<account>:role/<name>`
+    let account_id = profile_config
+        .and_then(|section| section.get("sso_account_id"))
+        .map(ToOwned::to_owned)
+        .or_else(|| Some(role_arn.as_ref()?.split(':').nth(4)?.to_string()))
+        .filter(|account_id| !account_id.is_empty());
+
+    let role = profile_config
+        .and_then(|section| section.get("sso_role_name"))
+        .map(ToOwned::to_owned)
+        .or_else(|| {
+            // `arn:aws:sts::<account>:assumed-role/<name>/<session name>` or `...:role/<path>/<name>`
+            let resource = role_arn.as_ref()?.split(':').nth(5)?;
+            match resource.split('/').collect::<Vec<_>>()[..] {
+                ["assumed-role", name, _] => Some(name.to_string()),
+                ["role", .., name] => Some(name.to_string()),
+                _ => None,
+            }
+        });
+
+    let mapped_account_id = account_id
+        .as_deref()
+        .map(|account_id| cloud::alias_or_value(&config.account_aliases, account_id));
+
+    let mapped_region = aws_region
+        .as_deref()
+        .map(|region| cloud::alias_or_value(&config.region_aliases, region));
 
-    let mapped_profile = alias_name(aws_profile, &config.profile_aliases);
+    let environment = CloudEnvironment::resolve(
+        &config.environments,
+        &config.profile_aliases,
+        aws_profile.as_deref(),
+        config.symbol,
+        config.style,
+    );
 
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
             .map_meta(|variable, _| match variable {
-                "symbol" => Some(config.symbol),
+                "symbol" => Some(environment.symbol),
                 _ => None,
             })
             .map_style(|variable| match variable {
-                "style" => Some(Ok(config.style)),
+                "style" => Some(Ok(environment.style)),
                 _ => None,
             })
             .map(|variable| match variable {
-                "profile" => mapped_profile.as_ref().map(Ok),
-                "region" => mapped_region.as_ref().map(Ok),
-                "duration" => duration.as_ref().map(Ok),
+                "profile" => environment.name.as_deref().map(Ok),
+                "region" => mapped_region.as_deref().map(Ok),
+                "duration" => duration.as_deref().map(Ok),
+                "account_id" => mapped_account_id.as_deref().map(Ok),
+                "role" => role.as_deref().map(Ok),
+                "role_arn" => role_arn.as_deref().map(Ok),
                 _ => None,
             })
             .parse(None, Some(context))
@@ -1190,4 +1354,267 @@ source_profile = starship
         assert_eq!(expected, actual);
         dir.close()
     }
+
+    #[test]
+    fn sso_session_expiration_from_cache() -> io::Result<()> {
+        use sha1::{Digest, Sha1};
+
+        let dir = tempfile::tempdir()?;
+        let aws_dir = dir.path().join(".aws");
+        let sso_cache_dir = aws_dir.join("sso/cache");
+        std::fs::create_dir_all(&sso_cache_dir)?;
+
+        std::fs::write(
+            aws_dir.join("config"),
+            "[profile astronauts]
+sso_session = my-sso
+sso_account_id = 123456789011
+sso_role_name = readOnly
+region = us-west-2
+
+[sso-session my-sso]
+sso_region = us-east-1
+sso_start_url = https://starship.rs/sso
+",
+        )?;
+
+        let mut hasher = Sha1::new();
+        hasher.update(b"my-sso");
+        let token_file = format!("{}.json", crate::utils::encode_to_hex(&hasher.finalize()));
+        std::fs::write(
+            sso_cache_dir.join(token_file),
+            r#"{"startUrl": "https://starship.rs/sso", "region": "us-east-1", "accessToken": "dummy", "expiresAt": "2019-11-14T04:05:45UTC"}"#,
+        )?;
+
+        let actual = ModuleRenderer::new("aws")
+            .env("HOME", dir.path().to_string_lossy())
+            .env("AWS_PROFILE", "astronauts")
+            .config(toml::toml! {
+                [aws]
+                format = "$profile $account_id $role \\[$duration\\]"
+                expiration_symbol = "expired"
+                [aws.account_aliases]
+                "123456789011" = "production"
+            })
+            .collect();
+        let expected = Some("astronauts production readOnly [expired]".to_string());
+
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn sso_legacy_expiration_from_cache() -> io::Result<()> {
+        use sha1::{Digest, Sha1};
+
+        let dir = tempfile::tempdir()?;
+        let aws_dir = dir.path().join(".aws");
+        let sso_cache_dir = aws_dir.join("sso/cache");
+        std::fs::create_dir_all(&sso_cache_dir)?;
+
+        std::fs::write(
+            aws_dir.join("config"),
+            "[default]
+region = ap-northeast-2
+sso_start_url = https://starship.rs/sso
+sso_account_id = 123456789011
+sso_role_name = admin
+",
+        )?;
+
+        let expires_at = chrono::Utc::now() + chrono::Duration::days(1);
+        let mut hasher = Sha1::new();
+        hasher.update(b"https://starship.rs/sso");
+        let token_file = format!("{}.json", crate::utils::encode_to_hex(&hasher.finalize()));
+        std::fs::write(
+            sso_cache_dir.join(token_file),
+            format!(
+                r#"{{"startUrl": "https://starship.rs/sso", "expiresAt": "{}"}}"#,
+                expires_at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+            ),
+        )?;
+
+        let actual = ModuleRenderer::new("aws")
+            .env("HOME", dir.path().to_string_lossy())
+            .config(toml::toml! {
+                [aws]
+                format = "$account_id \\[$duration\\]"
+            })
+            .collect();
+
+        // Scheduling may delay the render, so the remaining time could be slightly less
+        let possible_values = ["1d", "23h59m59s", "23h59m58s", "23h59m57s"]
+            .map(|duration| Some(format!("123456789011 [{duration}]")));
+        assert!(
+            possible_values.contains(&actual),
+            "time is not in range: {actual:?}"
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn assumed_role_from_cli_cache() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let aws_dir = dir.path().join(".aws");
+        let cli_cache_dir = aws_dir.join("cli/cache");
+        std::fs::create_dir_all(&cli_cache_dir)?;
+
+        std::fs::write(
+            aws_dir.join("config"),
+            "[profile astronauts]
+role_arn = arn:aws:iam::123456789012:role/team/deploy
+role_session_name = new-session
+duration_seconds = 3600
+source_profile = base
+
+[profile other]
+role_arn = arn:aws:iam::123456789012:role/team/deploy
+source_profile = base
+
+[profile base]
+region = us-east-1
+",
+        )?;
+        std::fs::write(
+            aws_dir.join("credentials"),
+            "[base]
+aws_access_key_id=dummy
+aws_secret_access_key=dummy
+",
+        )?;
+
+        let cached_role = |arn: &str, expiration: &str| {
+            format!(
+                r#"{{"Credentials": {{"AccessKeyId": "dummy", "Expiration": "{expiration}"}}, "AssumedRoleUser": {{"Arn": "{arn}"}}}}"#
+            )
+        };
+        // The cache key of `{"DurationSeconds": 3600, "RoleArn": "..."}`, without the session name
+        std::fs::write(
+            cli_cache_dir.join("31502abe4c2205389f7731b5709204c8486eab1c.json"),
+            cached_role(
+                "arn:aws:sts::123456789012:assumed-role/deploy/new-session",
+                "2020-11-14T04:05:45Z",
+            ),
+        )?;
+        // The same role assumed by the `other` profile must not be picked up
+        std::fs::write(
+            cli_cache_dir.join("7aa174ab820ec66e84d73db5565587cdca42def4.json"),
+            cached_role(
+                "arn:aws:sts::123456789012:assumed-role/deploy/other-session",
+                "2021-11-14T04:05:45Z",
+            ),
+        )?;
+
+        let actual = ModuleRenderer::new("aws")
+            .env("HOME", dir.path().to_string_lossy())
+            .env("AWS_PROFILE", "astronauts")
+            .config(toml::toml! {
+                [aws]
+                format = "$account_id $role $role_arn \\[$duration\\]"
+            })
+            .collect();
+        let expected = Some(
+            "123456789012 deploy arn:aws:sts::123456789012:assumed-role/deploy/new-session [X]"
+                .to_string(),
+        );
+
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn role_arn_without_cli_cache() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let aws_dir = dir.path().join(".aws");
+        std::fs::create_dir_all(&aws_dir)?;
+
+        std::fs::write(
+            aws_dir.join("config"),
+            "[profile astronauts]
+role_arn = arn:aws:iam::123456789012:role/deploy
+credential_process = /opt/bin/awscreds-retriever
+",
+        )?;
+
+        let actual = ModuleRenderer::new("aws")
+            .env("HOME", dir.path().to_string_lossy())
+            .env("AWS_PROFILE", "astronauts")
+            .config(toml::toml! {
+                [aws]
+                format = "$account_id $role $role_arn( \\[$duration\\])"
+            })
+            .collect();
+        let expected =
+            Some("123456789012 deploy arn:aws:iam::123456789012:role/deploy".to_string());
+
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn region_set_with_regex_alias() {
+        let actual = ModuleRenderer::new("aws")
+            .env("AWS_REGION", "ap-southeast-2")
+            .env("AWS_ACCESS_KEY_ID", "dummy")
+            .config(toml::toml! {
+                [aws.region_aliases]
+                "ap-(?P<area>[a-z]+)-\\d" = "ap $area"
+            })
+            .collect();
+        let expected = Some(format!(
+            "on {}",
+            Color::Yellow.bold().paint("☁️  (ap southeast) ")
+        ));
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn profile_set_with_production_environment() {
+        let actual = ModuleRenderer::new("aws")
+            .env("AWS_PROFILE", "prod-payments")
+            .env("AWS_ACCESS_KEY_ID", "dummy")
+            .config(toml::toml! {
+                [[aws.environments]]
+                pattern = "dev-.*"
+                style = "green"
+                [[aws.environments]]
+                pattern = "prod-(?P<team>.*)"
+                alias = "PROD $team"
+                symbol = "! "
+                production = true
+            })
+            .collect();
+        let expected = Some(format!(
+            "on {}",
+            Color::Red.bold().paint("! PROD payments ")
+        ));
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn profile_set_but_not_shown_because_of_detect_files() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let render = || {
+            ModuleRenderer::new("aws")
+                .path(dir.path())
+                .env("AWS_PROFILE", "astronauts")
+                .env("AWS_ACCESS_KEY_ID", "dummy")
+                .config(toml::toml! {
+                    [aws]
+                    format = "$profile"
+                    detect_files = ["serverless.yml"]
+                })
+                .collect()
+        };
+
+        assert_eq!(None, render());
+
+        File::create(dir.path().join("serverless.yml"))?.sync_all()?;
+        assert_eq!(Some("astronauts".to_string()), render());
+
+        dir.close()
+    }
 }
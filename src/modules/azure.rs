@@ -2,6 +2,7 @@ use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
 
+use super::utils::cloud::{self, CloudEnvironment};
 use super::{Context, Module, ModuleConfig};
 
 use crate::configs::azure::AzureConfig;
@@ -36,6 +37,16 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         return None;
     };
 
+    if !cloud::is_detected(
+        context,
+        &config.detect_env_vars,
+        &config.detect_files,
+        &config.detect_folders,
+        &config.detect_extensions,
+    ) {
+        return None;
+    }
+
     let subscription: Option<Subscription> = get_azure_profile_info(context);
 
     if subscription.is_none() {
@@ -44,24 +55,27 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     }
 
     let subscription = subscription.unwrap();
+    let environment = CloudEnvironment::resolve(
+        &config.environments,
+        &config.subscription_aliases,
+        Some(&subscription.name),
+        config.symbol,
+        config.style,
+    );
 
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
             .map_meta(|variable, _| match variable {
-                "symbol" => Some(config.symbol),
+                "symbol" => Some(environment.symbol),
                 _ => None,
             })
             .map_style(|variable| match variable {
-                "style" => Some(Ok(config.style)),
+                "style" => Some(Ok(environment.style)),
                 _ => None,
             })
             .map(|variable| match variable {
-                "subscription" => Some(Ok(config
-                    .subscription_aliases
-                    .get(&subscription.name)
-                    .copied()
-                    .unwrap_or(&subscription.name))),
-                "username" => Some(Ok(&subscription.user.name)),
+                "subscription" => environment.name.as_deref().map(Ok),
+                "username" => Some(Ok(subscription.user.name.as_str())),
                 _ => None,
             })
             .parse(None, Some(context))
@@ -792,4 +806,51 @@ mod tests {
         bom_file.sync_all()?;
         Ok(bom_file_path)
     }
+
+    #[test]
+    fn subscription_name_with_environment() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let azure_profile_contents = r#"{
+            "installationId": "3deacd2a-b9db-77e1-aa42-23e2f8dfffc3",
+            "subscriptions": [
+              {
+                "id": "f3935dc9-92b5-9a93-da7b-42c325d86939",
+                "name": "Contoso-Production",
+                "state": "Enabled",
+                "user": {
+                  "name": "user@domain.com",
+                  "type": "user"
+                },
+                "isDefault": true,
+                "tenantId": "f0273a19-7779-e40a-00a1-53b8331b3bb6",
+                "environmentName": "AzureCloud",
+                "homeTenantId": "f0273a19-7779-e40a-00a1-53b8331b3bb6",
+                "managedByTenants": []
+              }
+            ]
+          }
+        "#;
+
+        generate_test_config(&dir, azure_profile_contents)?;
+        let dir_path = &dir.path().to_string_lossy();
+        let actual = ModuleRenderer::new("azure")
+            .config(toml::toml! {
+                [azure]
+                format = "on [$symbol($subscription)]($style)"
+                disabled = false
+                [[azure.environments]]
+                pattern = "(?P<company>.*)-Production"
+                alias = "$company (prod)"
+                style = "bold purple"
+            })
+            .env("AZURE_CONFIG_DIR", dir_path.as_ref())
+            .collect();
+        let expected = Some(format!(
+            "on {}",
+            Color::Purple.bold().paint("󰠅 Contoso (prod)")
+        ));
+        assert_eq!(actual, expected);
+        dir.close()
+    }
 }
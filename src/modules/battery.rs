@@ -45,7 +45,11 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                     _ => None,
                 })
                 .map(|variable| match variable {
-                    "percentage" => Some(Ok(format!("{}%", percentage.round()))),
+                    "percentage" => Some(Ok(format!(
+                        "{}{}",
+                        percentage.round(),
+                        context.message("unit_percent")
+                    ))),
                     _ => None,
                 });
 
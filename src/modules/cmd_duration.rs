@@ -1,3 +1,5 @@
+use chrono::{DateTime, Duration, Local};
+
 use super::{Context, Module, ModuleConfig};
 
 use crate::configs::cmd_duration::CmdDurationConfig;
@@ -7,26 +9,44 @@ use crate::utils::render_time;
 /// Outputs the time it took the last command to execute
 ///
 /// Will only print if last command took more than a certain amount of time to
-/// execute. Default is two seconds, but can be set by config option `min_time`.
+/// execute. Default is two seconds, but can be set by config option `min_time`,
+/// or per command by `min_time_by_command` when the shell passes the command on.
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("cmd_duration");
     let config: CmdDurationConfig = CmdDurationConfig::try_load(module.config);
 
-    if config.min_time < 0 {
+    let command = context.get_last_command();
+    let command = command.as_deref();
+    let is_ignored = command.map_or(false, |command| {
+        config
+            .ignored_commands
+            .iter()
+            .any(|ignored| matches_command(command, ignored))
+    });
+    if is_ignored {
+        return None;
+    }
+
+    let min_time = command
+        .and_then(|command| command_min_time(&config, command))
+        .unwrap_or(config.min_time);
+    if min_time < 0 {
         log::warn!(
             "min_time in [cmd_duration] ({}) was less than zero",
-            config.min_time
+            min_time
         );
         return None;
     }
 
     let elapsed = context.get_cmd_duration()?;
-    let config_min = config.min_time as u128;
+    let config_min = min_time as u128;
 
     if elapsed < config_min {
         return None;
     }
 
+    let (start_time, end_time) = command_times(Local::now(), elapsed);
+
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
             .map_style(|variable| match variable {
@@ -34,7 +54,14 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map(|variable| match variable {
-                "duration" => Some(Ok(render_time(elapsed, config.show_milliseconds))),
+                "duration" => Some(Ok(render_time(
+                    elapsed,
+                    config.show_milliseconds,
+                    context.time_units(),
+                ))),
+                "start_time" => Some(Ok(start_time.format(config.time_format).to_string())),
+                "end_time" => Some(Ok(end_time.format(config.time_format).to_string())),
+                "command" => command.map(|command| Ok(command.to_string())),
                 _ => None,
             })
             .parse(None, Some(context))
@@ -51,6 +78,31 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(undistract_me(module, &config, context, elapsed))
 }
 
+/// Whether the command line runs `command`, that is it equals it or continues with arguments
+fn matches_command(command_line: &str, command: &str) -> bool {
+    let command = command.trim();
+    !command.is_empty()
+        && command_line.strip_prefix(command).map_or(false, |rest| {
+            rest.is_empty() || rest.starts_with(char::is_whitespace)
+        })
+}
+
+/// The `min_time` of the longest command in `min_time_by_command` which the command line runs
+fn command_min_time(config: &CmdDurationConfig, command_line: &str) -> Option<i64> {
+    config
+        .min_time_by_command
+        .iter()
+        .filter(|(command, _)| matches_command(command_line, command))
+        .max_by_key(|(command, _)| command.trim().len())
+        .map(|(_, &min_time)| min_time)
+}
+
+/// The times the last command started and ended, given that it ended `now`
+fn command_times(now: DateTime<Local>, elapsed: u128) -> (DateTime<Local>, DateTime<Local>) {
+    let elapsed = Duration::milliseconds(i64::try_from(elapsed).unwrap_or(i64::MAX));
+    (now.checked_sub_signed(elapsed).unwrap_or(now), now)
+}
+
 #[cfg(not(feature = "notify"))]
 fn undistract_me<'a>(
     module: Module<'a>,
@@ -83,9 +135,16 @@ fn undistract_me<'a>(
             };
         }
 
-        let body = format!(
-            "Command execution {}",
-            unstyle(&AnsiStrings(&module.ansi_strings()))
+        let status = context
+            .properties
+            .status_code
+            .as_deref()
+            .and_then(|status| status.parse::<i64>().ok());
+        let failed = status.map_or(false, |status| status != 0);
+        let body = notification_body(
+            context.get_last_command().as_deref(),
+            &unstyle(&AnsiStrings(&module.ansi_strings())),
+            status,
         );
 
         let timeout = match config.notification_timeout {
@@ -95,11 +154,30 @@ fn undistract_me<'a>(
 
         let mut notification = Notification::new();
         notification
-            .summary("Command finished")
+            .summary(match failed {
+                true => "Command failed",
+                false => "Command finished",
+            })
             .body(&body)
             .icon("utilities-terminal")
             .timeout(timeout);
 
+        if failed {
+            if !config.notification_failure_sound.is_empty() {
+                notification.sound_name(config.notification_failure_sound);
+            }
+            #[cfg(all(unix, not(target_os = "macos")))]
+            match notify_rust::Urgency::try_from(config.notification_failure_urgency) {
+                Ok(urgency) => {
+                    notification.urgency(urgency);
+                }
+                Err(_) => log::warn!(
+                    "Unknown notification_failure_urgency in [cmd_duration] ({:?}), expected low, normal or critical",
+                    config.notification_failure_urgency
+                ),
+            }
+        }
+
         if let Err(err) = notification.show() {
             log::trace!("Cannot show notification: {}", err);
         }
@@ -108,9 +186,28 @@ fn undistract_me<'a>(
     module
 }
 
+/// The body of the notification: the command line if known, the rendered module and the exit
+/// status of a failed command
+#[cfg(feature = "notify")]
+fn notification_body(command: Option<&str>, module: &str, status: Option<i64>) -> String {
+    let mut body = String::new();
+    if let Some(command) = command {
+        body.push_str(command);
+        body.push('\n');
+    }
+    body.push_str("Command execution ");
+    body.push_str(module.trim_end());
+    if let Some(status) = status.filter(|&status| status != 0) {
+        body.push_str(&format!("\nExit status {status}"));
+    }
+    body
+}
+
 #[cfg(test)]
 mod tests {
+    use super::*;
     use crate::test::ModuleRenderer;
+    use chrono::TimeZone;
     use nu_ansi_term::Color;
 
     #[test]
@@ -188,4 +285,105 @@ mod tests {
         let expected = Some(format!("underwent {} ", Color::Yellow.bold().paint("5s")));
         assert_eq!(expected, actual);
     }
+
+    #[test]
+    fn ignored_command() {
+        let config = toml::toml! {
+            [cmd_duration]
+            ignored_commands = ["vim", "ssh"]
+        };
+        let actual = ModuleRenderer::new("cmd_duration")
+            .config(config.clone())
+            .last_command("ssh example.com")
+            .cmd_duration(60_000)
+            .collect();
+        assert_eq!(None, actual);
+
+        let actual = ModuleRenderer::new("cmd_duration")
+            .config(config)
+            .last_command("sshfs example.com: /mnt")
+            .cmd_duration(60_000)
+            .collect();
+        let expected = Some(format!("took {} ", Color::Yellow.bold().paint("1m")));
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn min_time_by_command() {
+        let config = toml::toml! {
+            [cmd_duration]
+            format = "($command )[$duration]($style) "
+            [cmd_duration.min_time_by_command]
+            cargo = 10000
+            "cargo test" = 500
+        };
+        let actual = ModuleRenderer::new("cmd_duration")
+            .config(config.clone())
+            .last_command("cargo test --workspace")
+            .cmd_duration(1000)
+            .collect();
+        let expected = Some(format!(
+            "cargo test --workspace {} ",
+            Color::Yellow.bold().paint("1s")
+        ));
+        assert_eq!(expected, actual);
+
+        let actual = ModuleRenderer::new("cmd_duration")
+            .config(config.clone())
+            .last_command("cargo build")
+            .cmd_duration(5000)
+            .collect();
+        assert_eq!(None, actual);
+
+        let actual = ModuleRenderer::new("cmd_duration")
+            .config(config)
+            .cmd_duration(5000)
+            .collect();
+        let expected = Some(format!("{} ", Color::Yellow.bold().paint("5s")));
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn start_and_end_time() {
+        let now = Local.with_ymd_and_hms(2024, 5, 1, 12, 0, 30).unwrap();
+        let (start, end) = command_times(now, 90_500);
+        assert_eq!(start.format("%T%.3f").to_string(), "11:58:59.500");
+        assert_eq!(end, now);
+
+        let actual = ModuleRenderer::new("cmd_duration")
+            .config(toml::toml! {
+                [cmd_duration]
+                format = "$start_time-$end_time"
+                time_format = "%Y"
+            })
+            .cmd_duration(5000)
+            .collect()
+            .unwrap();
+        let year = Local::now().format("%Y").to_string();
+        assert!(actual.ends_with(&format!("-{year}")));
+    }
+
+    #[cfg(feature = "notify")]
+    #[test]
+    fn notification_body_with_command_and_status() {
+        assert_eq!(
+            notification_body(Some("cargo test"), "took 1m ", Some(101)),
+            "cargo test\nCommand execution took 1m\nExit status 101"
+        );
+        assert_eq!(
+            notification_body(None, "took 1m ", Some(0)),
+            "Command execution took 1m"
+        );
+    }
+
+    #[test]
+    fn duration_units_of_locale() {
+        let actual = ModuleRenderer::new("cmd_duration")
+            .env("LC_ALL", "fr_CA.UTF-8")
+            .cmd_duration(90_000)
+            .collect();
+
+        let expected = Some(format!("took {} ", Color::Yellow.bold().paint("1min30s")));
+        assert_eq!(expected, actual);
+    }
 }
@@ -104,7 +104,7 @@ fn get_config<'a>(module_name: &str, context: &'a Context<'a>) -> Option<&'a tom
         }
     }
 
-    let config = context.config.get_custom_module_config(module_name);
+    let config = context.get_module_config(&format!("custom.{module_name}"));
 
     if config.is_some() {
         return config;
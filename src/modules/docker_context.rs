@@ -1,20 +1,36 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+use yaml_rust::{Yaml, YamlLoader};
 
 use super::{Context, Module, ModuleConfig};
 
 use crate::configs::docker_context::DockerContextConfig;
-use crate::formatter::StringFormatter;
+use crate::formatter::{StringFormatter, VariableHolder};
 use crate::utils;
 
+const COMPOSE_FILES: &[&str] = &[
+    "compose.yaml",
+    "compose.yml",
+    "docker-compose.yaml",
+    "docker-compose.yml",
+];
+
+const DEVCONTAINER_FILES: &[&str] = &[".devcontainer/devcontainer.json", ".devcontainer.json"];
+
 /// Creates a module with the currently active Docker context
 ///
 /// Will display the Docker context if the following criteria are met:
 ///     - There is a non-empty environment variable named `DOCKER_HOST`
 ///     - Or there is a non-empty environment variable named `DOCKER_CONTEXT`
+///     - Or there is a non-empty environment variable named `CONTAINER_HOST` or
+///     `CONTAINER_CONNECTION` (Podman)
 ///     - Or there is a file named `$HOME/.docker/config.json`
 ///     - Or a file named `$DOCKER_CONFIG/config.json`
 ///     - The file is JSON and contains a field named `currentContext`
-///     - The value of `currentContext` is not `default`
+///     - Or Podman has a default connection in `podman-connections.json` or `containers.conf`
+///     - The value of `currentContext` is not `default`, unless the current directory belongs
+///     to a compose project or a devcontainer and the format uses `$compose_project`,
+///     `$compose_services` or `$devcontainer`
 ///     - If multiple criteria are met, we use the following order to define the docker context:
 ///     - `DOCKER_HOST`, `DOCKER_CONTEXT`, $HOME/.docker/config.json, $`DOCKER_CONFIG/config.json`
 ///     - (This is the same order docker follows, as `DOCKER_HOST` and `DOCKER_CONTEXT` override the
@@ -34,32 +50,33 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         return None;
     }
 
-    let docker_config = PathBuf::from(
-        &context
-            .get_env_os("DOCKER_CONFIG")
-            .unwrap_or(context.get_home()?.join(".docker").into_os_string()),
-    )
-    .join("config.json");
-
-    let docker_context_env = ["DOCKER_MACHINE_NAME", "DOCKER_HOST", "DOCKER_CONTEXT"]
-        .into_iter()
-        .find_map(|env| context.get_env(env));
-
-    let ctx = match docker_context_env {
-        Some(data) => data,
-        _ => {
-            if !docker_config.exists() {
-                return None;
-            }
-            let json = utils::read_file(docker_config).ok()?;
-            let parsed_json: serde_json::Value = serde_json::from_str(&json).ok()?;
-            parsed_json.get("currentContext")?.as_str()?.to_owned()
-        }
+    let (engine, ctx) = match get_docker_context(context) {
+        Some(ctx) => ("docker", Some(ctx)),
+        None => match get_podman_connection(context) {
+            Some(ctx) => ("podman", Some(ctx)),
+            None => ("docker", None),
+        },
     };
 
-    if ctx == "default" {
-        return None;
-    }
+    // The compose file and devcontainer are only parsed when the format refers to them
+    let variables = StringFormatter::new(config.format)
+        .map(|formatter| formatter.get_variables())
+        .unwrap_or_default();
+    let compose = ["compose_project", "compose_services"]
+        .iter()
+        .any(|variable| variables.contains(*variable))
+        .then(|| get_compose_project(context))
+        .flatten();
+    let devcontainer = variables
+        .contains("devcontainer")
+        .then(|| get_devcontainer_name(context))
+        .flatten();
+
+    let ctx = match ctx {
+        Some(ctx) if ctx != "default" => ctx,
+        _ if compose.is_some() || devcontainer.is_some() => String::from("default"),
+        _ => return None,
+    };
 
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
@@ -72,7 +89,11 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map(|variable| match variable {
-                "context" => Some(Ok(ctx.as_str())),
+                "context" => Some(Ok(ctx.clone())),
+                "engine" => Some(Ok(engine.to_string())),
+                "compose_project" => compose.as_ref().map(|c| Ok(c.name.clone())),
+                "compose_services" => compose.as_ref().map(|c| Ok(c.services.to_string())),
+                "devcontainer" => devcontainer.clone().map(Ok),
                 _ => None,
             })
             .parse(None, Some(context))
@@ -88,8 +109,204 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
+fn get_docker_context(context: &Context) -> Option<String> {
+    let docker_context_env = ["DOCKER_MACHINE_NAME", "DOCKER_HOST", "DOCKER_CONTEXT"]
+        .into_iter()
+        .find_map(|env| context.get_env(env));
+    if docker_context_env.is_some() {
+        return docker_context_env;
+    }
+
+    // Podman's environment variables override the docker configuration file as well
+    if ["CONTAINER_HOST", "CONTAINER_CONNECTION"]
+        .into_iter()
+        .any(|env| context.get_env(env).is_some())
+    {
+        return None;
+    }
+
+    let docker_config = PathBuf::from(
+        &context
+            .get_env_os("DOCKER_CONFIG")
+            .unwrap_or(context.get_home()?.join(".docker").into_os_string()),
+    )
+    .join("config.json");
+
+    if !docker_config.exists() {
+        return None;
+    }
+    let json = utils::read_file(docker_config).ok()?;
+    let parsed_json: serde_json::Value = serde_json::from_str(&json).ok()?;
+    Some(parsed_json.get("currentContext")?.as_str()?.to_owned())
+}
+
+fn get_podman_connection(context: &Context) -> Option<String> {
+    let podman_env = ["CONTAINER_HOST", "CONTAINER_CONNECTION"]
+        .into_iter()
+        .find_map(|env| context.get_env(env));
+    if podman_env.is_some() {
+        return podman_env;
+    }
+
+    let config_dir = context
+        .get_env_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| Some(context.get_home()?.join(".config")))?
+        .join("containers");
+
+    // Podman 4.8+ stores connections added with `podman system connection` in a JSON file
+    let connections = utils::read_file(config_dir.join("podman-connections.json"))
+        .ok()
+        .and_then(|json| serde_json::from_str::<serde_json::Value>(&json).ok())
+        .and_then(|json| Some(json.get("Connection")?.get("Default")?.as_str()?.to_owned()))
+        .filter(|name| !name.is_empty());
+    if connections.is_some() {
+        return connections;
+    }
+
+    let containers_conf = context
+        .get_env_os("CONTAINERS_CONF")
+        .map_or_else(|| config_dir.join("containers.conf"), PathBuf::from);
+    let conf = utils::read_file(containers_conf)
+        .ok()?
+        .parse::<toml::Table>()
+        .ok()?;
+    conf.get("engine")?
+        .get("active_service")?
+        .as_str()
+        .filter(|name| !name.is_empty())
+        .map(ToOwned::to_owned)
+}
+
+struct ComposeProject {
+    name: String,
+    services: usize,
+}
+
+/// Finds the compose file of the current directory the same way `docker compose` does:
+/// either from `COMPOSE_FILE` or by searching the current directory and its parents
+fn get_compose_file(context: &Context) -> Option<PathBuf> {
+    if let Some(compose_files) = context.get_env_os("COMPOSE_FILE") {
+        let file = std::env::split_paths(&compose_files).next()?;
+        return Some(context.current_dir.join(file)).filter(|file| file.is_file());
+    }
+
+    let dir = context
+        .begin_ancestor_scan()
+        .set_files(COMPOSE_FILES)
+        .scan()?;
+    COMPOSE_FILES
+        .iter()
+        .map(|file| dir.join(file))
+        .find(|file| file.is_file())
+}
+
+fn get_compose_project(context: &Context) -> Option<ComposeProject> {
+    let compose_file = get_compose_file(context)?;
+    let contents = utils::read_file(&compose_file).ok()?;
+    let docs = YamlLoader::load_from_str(&contents).ok()?;
+    let doc = docs.first().unwrap_or(&Yaml::BadValue);
+
+    let name = context
+        .get_env("COMPOSE_PROJECT_NAME")
+        .or_else(|| doc["name"].as_str().map(ToOwned::to_owned))
+        .or_else(|| {
+            compose_file
+                .parent()
+                .and_then(Path::file_name)
+                .map(|dir| normalize_project_name(&dir.to_string_lossy()))
+        })?;
+    let services = doc["services"]
+        .as_hash()
+        .map_or(0, |services| services.len());
+
+    Some(ComposeProject { name, services })
+}
+
+/// Compose derives the default project name from the directory name, keeping only lowercase
+/// letters, digits, dashes and underscores
+fn normalize_project_name(name: &str) -> String {
+    name.to_lowercase()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+        .collect()
+}
+
+fn get_devcontainer_name(context: &Context) -> Option<String> {
+    let dir = context
+        .begin_ancestor_scan()
+        .set_files(&[".devcontainer.json"])
+        .set_folders(&[".devcontainer"])
+        .scan()?;
+    let devcontainer_file = DEVCONTAINER_FILES
+        .iter()
+        .map(|file| dir.join(file))
+        .find(|file| file.is_file())?;
+
+    let contents = utils::read_file(devcontainer_file).ok()?;
+    let json: serde_json::Value = serde_json::from_str(&strip_json_comments(&contents)).ok()?;
+    json.get("name")
+        .and_then(serde_json::Value::as_str)
+        .map(ToOwned::to_owned)
+        .or_else(|| Some(dir.file_name()?.to_string_lossy().into_owned()))
+}
+
+/// `devcontainer.json` is JSON with comments, which also allows trailing commas
+fn strip_json_comments(input: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    let mut chars = input.chars().peekable();
+    let mut in_string = false;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            output.push(c);
+            match c {
+                '\\' => output.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match (c, chars.peek()) {
+            ('/', Some('/')) => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        output.push(c);
+                        break;
+                    }
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut previous = None;
+                for c in chars.by_ref() {
+                    if previous == Some('*') && c == '/' {
+                        break;
+                    }
+                    previous = Some(c);
+                }
+            }
+            ('}' | ']', _) => {
+                let trimmed = output.trim_end();
+                if trimmed.ends_with(',') {
+                    output.truncate(trimmed.len() - 1);
+                }
+                output.push(c);
+            }
+            _ => {
+                in_string = c == '"';
+                output.push(c);
+            }
+        }
+    }
+
+    output
+}
+
 #[cfg(test)]
 mod tests {
+    use super::strip_json_comments;
     use crate::test::ModuleRenderer;
     use nu_ansi_term::Color;
     use std::fs::File;
@@ -423,4 +640,211 @@ mod tests {
 
         cfg_dir.close()
     }
+
+    #[test]
+    fn test_compose_project() -> io::Result<()> {
+        let cfg_dir = tempfile::tempdir()?;
+        let pwd = tempfile::tempdir()?;
+        let project_dir = pwd.path().join("My Project");
+        let sub_dir = project_dir.join("src");
+        std::fs::create_dir_all(&sub_dir)?;
+        std::fs::write(
+            project_dir.join("compose.yaml"),
+            "services:\n  web:\n    image: nginx\n  db:\n    image: postgres\n",
+        )?;
+
+        let actual = ModuleRenderer::new("docker_context")
+            .env("DOCKER_CONFIG", cfg_dir.path().to_string_lossy())
+            .env("HOME", cfg_dir.path().to_string_lossy())
+            .path(&sub_dir)
+            .config(toml::toml! {
+                [docker_context]
+                only_with_files = false
+                format = "$context $compose_project:$compose_services"
+            })
+            .collect();
+        let expected = Some("default myproject:2".to_string());
+
+        assert_eq!(expected, actual);
+
+        cfg_dir.close()?;
+        pwd.close()
+    }
+
+    #[test]
+    fn test_compose_project_default_context_hidden() -> io::Result<()> {
+        let cfg_dir = tempfile::tempdir()?;
+        let pwd = tempfile::tempdir()?;
+        std::fs::write(
+            pwd.path().join("compose.yaml"),
+            "services:\n  web:\n    image: nginx\n",
+        )?;
+
+        let actual = ModuleRenderer::new("docker_context")
+            .env("DOCKER_CONFIG", cfg_dir.path().to_string_lossy())
+            .env("HOME", cfg_dir.path().to_string_lossy())
+            .path(pwd.path())
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+
+        cfg_dir.close()?;
+        pwd.close()
+    }
+
+    #[test]
+    fn test_compose_project_name() -> io::Result<()> {
+        let cfg_dir = tempfile::tempdir()?;
+        let pwd = tempfile::tempdir()?;
+        std::fs::write(
+            pwd.path().join("docker-compose.yml"),
+            "name: starship\nservices:\n  web:\n    image: nginx\n",
+        )?;
+
+        let renderer = || {
+            ModuleRenderer::new("docker_context")
+                .env("DOCKER_CONFIG", cfg_dir.path().to_string_lossy())
+                .env("HOME", cfg_dir.path().to_string_lossy())
+                .path(pwd.path())
+                .config(toml::toml! {
+                    [docker_context]
+                    format = "$compose_project"
+                })
+        };
+
+        assert_eq!(Some("starship".to_string()), renderer().collect());
+        assert_eq!(
+            Some("override".to_string()),
+            renderer().env("COMPOSE_PROJECT_NAME", "override").collect()
+        );
+
+        cfg_dir.close()?;
+        pwd.close()
+    }
+
+    #[test]
+    fn test_compose_file_env() -> io::Result<()> {
+        let cfg_dir = tempfile::tempdir()?;
+        let pwd = tempfile::tempdir()?;
+        std::fs::create_dir(pwd.path().join("deploy"))?;
+        std::fs::write(
+            pwd.path().join("deploy/stack.yml"),
+            "name: stack\nservices:\n  web:\n    image: nginx\n",
+        )?;
+
+        let actual = ModuleRenderer::new("docker_context")
+            .env("DOCKER_CONFIG", cfg_dir.path().to_string_lossy())
+            .env("HOME", cfg_dir.path().to_string_lossy())
+            .env("COMPOSE_FILE", "deploy/stack.yml")
+            .path(pwd.path())
+            .config(toml::toml! {
+                [docker_context]
+                only_with_files = false
+                format = "$compose_project:$compose_services"
+            })
+            .collect();
+        let expected = Some("stack:1".to_string());
+
+        assert_eq!(expected, actual);
+
+        cfg_dir.close()?;
+        pwd.close()
+    }
+
+    #[test]
+    fn test_devcontainer() -> io::Result<()> {
+        let cfg_dir = tempfile::tempdir()?;
+        let pwd = tempfile::tempdir()?;
+        std::fs::create_dir(pwd.path().join(".devcontainer"))?;
+        std::fs::write(
+            pwd.path().join(".devcontainer/devcontainer.json"),
+            r#"{
+    // The name shown in the UI
+    "name": "Rust // dev",
+    "image": "mcr.microsoft.com/devcontainers/rust:1", /* pinned */
+    "features": {},
+}"#,
+        )?;
+
+        let actual = ModuleRenderer::new("docker_context")
+            .env("DOCKER_CONFIG", cfg_dir.path().to_string_lossy())
+            .env("HOME", cfg_dir.path().to_string_lossy())
+            .path(pwd.path())
+            .config(toml::toml! {
+                [docker_context]
+                format = "via [$symbol$context( \\($devcontainer\\))]($style) "
+            })
+            .collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Blue.bold().paint("🐳 default (Rust // dev)")
+        ));
+
+        assert_eq!(expected, actual);
+
+        cfg_dir.close()?;
+        pwd.close()
+    }
+
+    #[test]
+    fn test_podman_container_host_env() {
+        let actual = ModuleRenderer::new("docker_context")
+            .env("CONTAINER_HOST", "unix:///run/user/1000/podman/podman.sock")
+            .config(toml::toml! {
+                [docker_context]
+                only_with_files = false
+                format = "$engine $context"
+            })
+            .collect();
+        let expected = Some("podman unix:///run/user/1000/podman/podman.sock".to_string());
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_podman_containers_conf() -> io::Result<()> {
+        let home = tempfile::tempdir()?;
+        let containers_dir = home.path().join(".config/containers");
+        std::fs::create_dir_all(&containers_dir)?;
+        std::fs::write(
+            containers_dir.join("containers.conf"),
+            "[engine]\nactive_service = \"remote\"\n",
+        )?;
+
+        let renderer = || {
+            ModuleRenderer::new("docker_context")
+                .env("HOME", home.path().to_string_lossy())
+                .env(
+                    "DOCKER_CONFIG",
+                    home.path().join(".docker").to_string_lossy(),
+                )
+                .config(toml::toml! {
+                    [docker_context]
+                    only_with_files = false
+                    format = "$engine $context"
+                })
+        };
+
+        assert_eq!(Some("podman remote".to_string()), renderer().collect());
+
+        // `podman-connections.json` takes precedence over `containers.conf`
+        std::fs::write(
+            containers_dir.join("podman-connections.json"),
+            r#"{"Connection": {"Default": "machine", "Connections": {}}}"#,
+        )?;
+        assert_eq!(Some("podman machine".to_string()), renderer().collect());
+
+        home.close()
+    }
+
+    #[test]
+    fn test_strip_json_comments() {
+        let input = r#"{"a": "http://b", /* c */ "d": [1, 2,], // e
+"f": "\"//\"",}"#;
+        let actual: serde_json::Value = serde_json::from_str(&strip_json_comments(input)).unwrap();
+        let expected = serde_json::json!({"a": "http://b", "d": [1, 2], "f": "\"//\""});
+
+        assert_eq!(expected, actual);
+    }
 }
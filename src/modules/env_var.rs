@@ -14,11 +14,9 @@ use crate::formatter::StringFormatter;
 pub fn module<'a>(name: Option<&str>, context: &'a Context) -> Option<Module<'a>> {
     let toml_config = match name {
         Some(name) => context
-            .config
-            .get_config(&["env_var", name])
+            .get_module_config(&format!("env_var.{name}"))
             .map(Cow::Borrowed),
         None => context
-            .config
             .get_module_config("env_var")
             .and_then(filter_config)
             .map(Cow::Owned)
@@ -3,6 +3,7 @@ use super::{Context, Module, ModuleConfig};
 use crate::configs::fossil_branch::FossilBranchConfig;
 use crate::formatter::StringFormatter;
 use crate::modules::utils::truncate::truncate_text;
+use crate::modules::utils::vcs::fossil::{get_current_branch, CHECKOUT_DB};
 
 /// Creates a module with the Fossil branch of the check-out in the current directory
 ///
@@ -17,15 +18,10 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         return None;
     };
 
-    let checkout_db = if cfg!(windows) {
-        "_FOSSIL_"
-    } else {
-        ".fslckout"
-    };
     // See if we're in a check-out by scanning upwards for a directory containing the checkout_db file
     context
         .begin_ancestor_scan()
-        .set_files(&[checkout_db])
+        .set_files(&[CHECKOUT_DB])
         .scan()?;
 
     let len = if config.truncation_length <= 0 {
@@ -39,8 +35,8 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     };
 
     let truncated_branch_name = {
-        let output = context.exec_cmd("fossil", &["branch", "current"])?.stdout;
-        truncate_text(output.trim(), len, config.truncation_symbol)
+        let branch_name = get_current_branch(context)?;
+        truncate_text(&branch_name, len, config.truncation_symbol)
     };
 
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
@@ -4,6 +4,7 @@ use std::borrow::Cow;
 use std::path::Path;
 use std::path::PathBuf;
 
+use super::utils::cloud::{self, CloudEnvironment};
 use super::{Context, Module, ModuleConfig};
 
 use crate::configs::gcloud::GcloudConfig;
@@ -84,22 +85,39 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("gcloud");
     let config: GcloudConfig = GcloudConfig::try_load(module.config);
 
-    if !(context.detect_env_vars(&config.detect_env_vars)) {
+    if !cloud::is_detected(
+        context,
+        &config.detect_env_vars,
+        &config.detect_files,
+        &config.detect_folders,
+        &config.detect_extensions,
+    ) {
         return None;
     }
 
     let (config_name, config_path) = get_current_config(context)?;
     let gcloud_context = GcloudContext::new(&config_name, &config_path);
     let account: Lazy<Option<Account<'_>>, _> = Lazy::new(|| gcloud_context.get_account());
+    let project = context
+        .get_env("CLOUDSDK_CORE_PROJECT")
+        .map(Cow::Owned)
+        .or_else(|| gcloud_context.get_project().map(Cow::Borrowed));
+    let environment = CloudEnvironment::resolve(
+        &config.environments,
+        &config.project_aliases,
+        project.as_deref(),
+        config.symbol,
+        config.style,
+    );
 
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
             .map_meta(|variable, _| match variable {
-                "symbol" => Some(config.symbol),
+                "symbol" => Some(environment.symbol),
                 _ => None,
             })
             .map_style(|variable| match variable {
-                "style" => Some(Ok(config.style)),
+                "style" => Some(Ok(environment.style)),
                 _ => None,
             })
             .map(|variable| match variable {
@@ -113,21 +131,9 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                     .map(Ok),
                 "region" => gcloud_context
                     .get_region()
-                    .map(|region| config.region_aliases.get(region).copied().unwrap_or(region))
-                    .map(Cow::Borrowed)
-                    .map(Ok),
-                "project" => context
-                    .get_env("CLOUDSDK_CORE_PROJECT")
-                    .map(Cow::Owned)
-                    .or_else(|| gcloud_context.get_project().map(Cow::Borrowed))
-                    .map(|project| {
-                        config
-                            .project_aliases
-                            .get(project.as_ref())
-                            .copied()
-                            .map_or(project, Cow::Borrowed)
-                    })
+                    .map(|region| cloud::alias_or_value(&config.region_aliases, region))
                     .map(Ok),
+                "project" => environment.name.clone().map(Ok),
                 "active" => Some(Ok(Cow::Borrowed(&gcloud_context.config_name))),
                 _ => None,
             })
@@ -496,7 +502,69 @@ project = overridden
                 format = "on [$symbol$project]($style) "
             })
             .collect();
-        let expected = Some(format!("on {} ", Color::Blue.bold().paint("☁️  overridden")));
+        let expected = Some(format!(
+            "on {} ",
+            Color::Blue.bold().paint("☁️  overridden")
+        ));
+
+        assert_eq!(actual, expected);
+        dir.close()
+    }
+
+    #[test]
+    fn project_set_with_environment() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let active_config_path = dir.path().join("active_config");
+        let mut active_config_file = File::create(active_config_path)?;
+        active_config_file.write_all(b"default")?;
+
+        create_dir(dir.path().join("configurations"))?;
+        let config_default_path = dir.path().join("configurations").join("config_default");
+        let mut config_default_file = File::create(config_default_path)?;
+        config_default_file.write_all(
+            b"\
+[core]
+project = acme-prod-42
+",
+        )?;
+
+        let actual = ModuleRenderer::new("gcloud")
+            .env("CLOUDSDK_CONFIG", dir.path().to_string_lossy())
+            .config(toml::toml! {
+                [gcloud]
+                format = "on [$symbol$project]($style) "
+                [[gcloud.environments]]
+                pattern = "acme-prod-\\d+"
+                production = true
+            })
+            .collect();
+        let expected = Some(format!(
+            "on {} ",
+            Color::Red.bold().paint("☁️  acme-prod-42")
+        ));
+
+        assert_eq!(actual, expected);
+        dir.close()
+    }
+
+    #[test]
+    fn project_set_with_regex_alias() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let active_config_path = dir.path().join("active_config");
+        let mut active_config_file = File::create(active_config_path)?;
+        active_config_file.write_all(b"default")?;
+
+        let actual = ModuleRenderer::new("gcloud")
+            .env("CLOUDSDK_CONFIG", dir.path().to_string_lossy())
+            .env("CLOUDSDK_CORE_PROJECT", "acme-staging-1234")
+            .config(toml::toml! {
+                [gcloud]
+                format = "on [$symbol$project]($style) "
+                [gcloud.project_aliases]
+                "acme-(?P<stage>[a-z]+)-\\d+" = "$stage"
+            })
+            .collect();
+        let expected = Some(format!("on {} ", Color::Blue.bold().paint("☁️  staging")));
 
         assert_eq!(actual, expected);
         dir.close()
@@ -1,5 +1,6 @@
 use unicode_segmentation::UnicodeSegmentation;
 
+use super::utils::forge::ForgeRemote;
 use super::{Context, Module, ModuleConfig};
 
 use crate::configs::git_branch::GitBranchConfig;
@@ -26,6 +27,11 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
 
     let repo = context.get_repo().ok()?;
 
+    // The enabled `vcs` module shows the state of colocated jj repositories
+    if context.is_jj_colocated_shown_by_vcs(repo) {
+        return None;
+    }
+
     if config.only_attached && repo.open().head().ok()?.is_detached() {
         return None;
     }
@@ -67,6 +73,12 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         }
     }
 
+    let forge = repo
+        .remote
+        .as_ref()
+        .and_then(|remote| remote.url.as_deref())
+        .and_then(ForgeRemote::parse);
+
     let show_remote = config.always_show_remote
         || (!graphemes.eq(&remote_branch_graphemes) && !remote_branch_graphemes.is_empty());
 
@@ -96,7 +108,10 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                         None
                     }
                 }
-                _ => None,
+                _ => forge
+                    .as_ref()?
+                    .get_variable(variable, &config.forge_symbols)
+                    .map(Ok),
             })
             .parse(None, Some(context))
     });
@@ -139,6 +154,42 @@ mod tests {
         repo_dir.close()
     }
 
+    #[test]
+    fn show_nothing_in_jj_colocated_repo_with_vcs() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+        std::fs::create_dir(repo_dir.path().join(".jj"))?;
+
+        let actual = ModuleRenderer::new("git_branch")
+            .path(repo_dir.path())
+            .config(toml::toml! {
+                [vcs]
+                disabled = false
+            })
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn show_branch_in_jj_colocated_repo_by_default() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+        std::fs::create_dir(repo_dir.path().join(".jj"))?;
+
+        let actual = ModuleRenderer::new("git_branch")
+            .path(repo_dir.path())
+            .config(toml::toml! {
+                [git_branch]
+                format = "$branch"
+            })
+            .collect();
+        let expected = Some("master");
+
+        assert_eq!(expected, actual.as_deref());
+        repo_dir.close()
+    }
+
     #[test]
     fn test_changed_truncation_symbol() -> io::Result<()> {
         test_truncate_length_with_config(
@@ -428,6 +479,53 @@ mod tests {
         remote_dir.close()
     }
 
+    #[test]
+    fn test_forge() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+
+        create_command("git")?
+            .args([
+                "remote",
+                "set-url",
+                "origin",
+                "git@gitlab.com:team/sub/repo.git",
+            ])
+            .current_dir(repo_dir.path())
+            .output()?;
+
+        let actual = ModuleRenderer::new("git_branch")
+            .path(repo_dir.path())
+            .config(toml::toml! {
+                [git_branch]
+                format = "$branch $forge_symbol$forge:$remote_owner/$remote_repo \\($remote_protocol\\)"
+                forge_symbols = { gitlab = "GL " }
+            })
+            .collect();
+
+        let expected = Some("master GL gitlab:team/sub/repo (ssh)");
+
+        assert_eq!(expected, actual.as_deref());
+        repo_dir.close()
+    }
+
+    #[test]
+    fn test_forge_local_remote() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+
+        let actual = ModuleRenderer::new("git_branch")
+            .path(repo_dir.path())
+            .config(toml::toml! {
+                [git_branch]
+                format = "$branch( on $remote_host)"
+            })
+            .collect();
+
+        let expected = Some("master");
+
+        assert_eq!(expected, actual.as_deref());
+        repo_dir.close()
+    }
+
     // This test is not possible until we switch to `git status --porcelain`
     // where we can mock the env for the specific git process. This is because
     // git2 does not care about our mocking and when we set the real `GIT_DIR`
@@ -1,5 +1,9 @@
+use super::utils::forge::ForgeRemote;
+use super::utils::truncate::truncate_text;
 use super::{Context, Module, ModuleConfig};
 use gix::commit::describe::SelectRef::AllTags;
+use once_cell::sync::OnceCell;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::configs::git_commit::GitCommitConfig;
 use crate::context::Repo;
@@ -13,6 +17,12 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let config: GitCommitConfig = GitCommitConfig::try_load(module.config);
 
     let repo = context.get_repo().ok()?;
+
+    // The enabled `vcs` module shows the state of colocated jj repositories
+    if context.is_jj_colocated_shown_by_vcs(repo) {
+        return None;
+    }
+
     let git_repo = repo.open();
     let git_head = git_repo.head().ok()?;
 
@@ -21,6 +31,21 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         return None;
     };
 
+    let forge = repo
+        .remote
+        .as_ref()
+        .and_then(|remote| remote.url.as_deref())
+        .and_then(ForgeRemote::parse);
+
+    let commit_info = OnceCell::new();
+    let get_commit_info = || commit_info.get_or_init(|| git_commit_info(repo)).as_ref();
+    let describe = OnceCell::new();
+    let get_describe = || {
+        describe
+            .get_or_init(|| git_describe(repo, &config))
+            .as_ref()
+    };
+
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
             .map_style(|variable| match variable {
@@ -34,7 +59,35 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                     config.tag_symbol,
                     git_tag(context.get_repo().ok()?, &config)?
                 ))),
-                _ => None,
+                "age" => {
+                    let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs();
+                    let age = now.saturating_sub(get_commit_info()?.time);
+                    Some(Ok(format_age(age)))
+                }
+                "author" => Some(Ok(get_commit_info()?.author.clone())),
+                "author_email" => Some(Ok(get_commit_info()?.author_email.clone())),
+                "subject" => Some(Ok(truncate_text(
+                    &get_commit_info()?.subject,
+                    config.subject_max_length,
+                    config.truncation_symbol,
+                ))),
+                "signed" => get_commit_info()?
+                    .is_signed
+                    .then(|| Ok(config.signed_symbol.to_string())),
+                "describe" => get_describe().map(|(name, depth)| {
+                    Ok(match depth {
+                        0 => name.clone(),
+                        depth => format!("{name}+{depth}"),
+                    })
+                }),
+                "commits_since_tag" => get_describe().map(|(_, depth)| Ok(depth.to_string())),
+                "stash_count" => git_stash_count(repo)
+                    .filter(|count| *count > 0)
+                    .map(|count| Ok(count.to_string())),
+                _ => forge
+                    .as_ref()?
+                    .get_variable(variable, &config.forge_symbols)
+                    .map(Ok),
             })
             .parse(None, Some(context))
     });
@@ -66,6 +119,71 @@ fn git_tag(repo: &Repo, config: &GitCommitConfig) -> Option<String> {
     Some(formatter.name?.to_string())
 }
 
+/// The last tag reachable from `HEAD`, and the number of commits since that tag
+fn git_describe(repo: &Repo, config: &GitCommitConfig) -> Option<(String, u32)> {
+    let mut git_repo = repo.open();
+    git_repo.object_cache_size_if_unset(4 * 1024 * 1024);
+    let head_commit = git_repo.head_commit().ok()?;
+
+    let describe_platform = head_commit
+        .describe()
+        .names(AllTags)
+        .max_candidates(config.describe_max_candidates)
+        .traverse_first_parent(true);
+    let formatter = describe_platform.try_format().ok()??;
+
+    Some((formatter.name?.to_string(), formatter.depth))
+}
+
+struct CommitInfo {
+    /// The commit time in seconds since the unix epoch
+    time: u64,
+    author: String,
+    author_email: String,
+    subject: String,
+    is_signed: bool,
+}
+
+fn git_commit_info(repo: &Repo) -> Option<CommitInfo> {
+    let git_repo = repo.open();
+    let head_commit = git_repo.head_commit().ok()?;
+    let commit = head_commit.decode().ok()?;
+    let author = commit.author();
+
+    Some(CommitInfo {
+        time: u64::from(commit.committer().time.seconds_since_unix_epoch),
+        author: author.name.to_string(),
+        author_email: author.email.to_string(),
+        subject: commit.message().summary().to_string(),
+        is_signed: commit.extra_headers().pgp_signature().is_some(),
+    })
+}
+
+fn git_stash_count(repo: &Repo) -> Option<usize> {
+    let git_repo = repo.open();
+    let stash = git_repo.find_reference("refs/stash").ok()?;
+    let mut log = stash.log_iter();
+    let count = log.all().ok()??.count();
+    Some(count)
+}
+
+/// Formats a duration in seconds with its largest unit, e.g. `3h` or `2w`
+fn format_age(seconds: u64) -> String {
+    const UNITS: [(u64, &str); 6] = [
+        (365 * 24 * 60 * 60, "y"),
+        (30 * 24 * 60 * 60, "mo"),
+        (7 * 24 * 60 * 60, "w"),
+        (24 * 60 * 60, "d"),
+        (60 * 60, "h"),
+        (60, "m"),
+    ];
+
+    UNITS.iter().find(|(unit, _)| seconds >= *unit).map_or_else(
+        || format!("{seconds}s"),
+        |(unit, suffix)| format!("{}{suffix}", seconds / unit),
+    )
+}
+
 fn git_hash(repo: &Repo, config: &GitCommitConfig) -> Option<String> {
     let git_repo = repo.open();
     let head_id = git_repo.head_id().ok()?;
@@ -79,7 +197,9 @@ fn git_hash(repo: &Repo, config: &GitCommitConfig) -> Option<String> {
 #[cfg(test)]
 mod tests {
     use nu_ansi_term::Color;
-    use std::{io, str};
+    use std::{fs, io, str};
+
+    use super::format_age;
 
     use crate::test::{fixture_repo, FixtureProvider, ModuleRenderer};
     use crate::utils::create_command;
@@ -98,6 +218,26 @@ mod tests {
         repo_dir.close()
     }
 
+    #[test]
+    fn show_nothing_in_jj_colocated_repo_with_vcs() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+        std::fs::create_dir(repo_dir.path().join(".jj"))?;
+
+        let actual = ModuleRenderer::new("git_commit")
+            .config(toml::toml! {
+                [git_commit]
+                    only_detached = false
+                [vcs]
+                disabled = false
+            })
+            .path(repo_dir.path())
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
     #[test]
     fn test_render_commit_hash() -> io::Result<()> {
         let repo_dir = fixture_repo(FixtureProvider::Git)?;
@@ -127,6 +267,39 @@ mod tests {
         repo_dir.close()
     }
 
+    #[test]
+    fn test_render_forge_on_detached() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+
+        create_command("git")?
+            .args([
+                "remote",
+                "set-url",
+                "origin",
+                "https://github.com/starship/starship",
+            ])
+            .current_dir(repo_dir.path())
+            .output()?;
+
+        create_command("git")?
+            .args(["checkout", "--detach"])
+            .current_dir(repo_dir.path())
+            .output()?;
+
+        let actual = ModuleRenderer::new("git_commit")
+            .config(toml::toml! {
+                [git_commit]
+                    format = "$forge_symbol$remote_host/$remote_owner/$remote_repo"
+            })
+            .path(repo_dir.path())
+            .collect();
+
+        let expected = Some(String::from("\u{f09b} github.com/starship/starship"));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
     #[test]
     fn test_render_commit_hash_len_override() -> io::Result<()> {
         let repo_dir = fixture_repo(FixtureProvider::Git)?;
@@ -462,4 +635,103 @@ mod tests {
         assert_eq!(expected, actual);
         Ok(())
     }
+
+    #[test]
+    fn test_render_commit_metadata() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+
+        create_command("git")?
+            .args(["tag", "v1.4.2"])
+            .current_dir(repo_dir.path())
+            .output()?;
+
+        for message in [
+            "First change",
+            "A subject line which is rather long\n\nAnd a body",
+        ] {
+            fs::write(repo_dir.path().join("readme.md"), message)?;
+            create_command("git")?
+                .args(["commit", "-am", message, "--no-gpg-sign"])
+                .args(["--author", "Jane Doe <jane@example.com>"])
+                .current_dir(repo_dir.path())
+                .output()?;
+        }
+
+        fs::write(repo_dir.path().join("readme.md"), "Stashed change")?;
+        create_command("git")?
+            .args(["stash"])
+            .current_dir(repo_dir.path())
+            .output()?;
+
+        let actual = ModuleRenderer::new("git_commit")
+            .config(toml::toml! {
+                [git_commit]
+                    only_detached = false
+                    subject_max_length = 20
+                    format = "$describe \\($commits_since_tag\\) $author <$author_email> $subject( $signed)( $stash_count)"
+            })
+            .path(repo_dir.path())
+            .collect();
+
+        let expected = Some(String::from(
+            "v1.4.2+2 (2) Jane Doe <jane@example.com> A subject line which… 1",
+        ));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn test_render_signed_commit() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+
+        // Forge a signature header, the signature itself isn't verified
+        let commit = create_command("git")?
+            .args(["cat-file", "commit", "HEAD"])
+            .current_dir(repo_dir.path())
+            .output()?
+            .stdout;
+        let commit = str::from_utf8(&commit).unwrap().replacen(
+            "\n\n",
+            "\ngpgsig -----BEGIN PGP SIGNATURE-----\n -----END PGP SIGNATURE-----\n\n",
+            1,
+        );
+        let signed_file = repo_dir.path().join("signed-commit");
+        fs::write(&signed_file, commit)?;
+        let signed_id = create_command("git")?
+            .args(["hash-object", "-t", "commit", "-w"])
+            .arg(&signed_file)
+            .current_dir(repo_dir.path())
+            .output()?
+            .stdout;
+        create_command("git")?
+            .args([
+                "reset",
+                "--soft",
+                str::from_utf8(&signed_id).unwrap().trim(),
+            ])
+            .current_dir(repo_dir.path())
+            .output()?;
+
+        let actual = ModuleRenderer::new("git_commit")
+            .config(toml::toml! {
+                [git_commit]
+                    only_detached = false
+                    format = "$signed"
+            })
+            .path(repo_dir.path())
+            .collect();
+
+        assert_eq!(Some(String::from("🔏")), actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn test_format_age() {
+        assert_eq!(format_age(0), "0s");
+        assert_eq!(format_age(59), "59s");
+        assert_eq!(format_age(3 * 60 * 60 + 59), "3h");
+        assert_eq!(format_age(13 * 24 * 60 * 60), "1w");
+        assert_eq!(format_age(400 * 24 * 60 * 60), "1y");
+    }
 }
@@ -0,0 +1,256 @@
+use std::path::{Path, PathBuf};
+
+use gix::repository::Kind;
+
+use super::directory::contract_path;
+use super::{Context, Module, ModuleConfig};
+
+use crate::configs::git_context::GitContextConfig;
+use crate::formatter::StringFormatter;
+
+/// Creates a module with the kind of checkout of the git repo in the current directory
+///
+/// Will display the name of a linked worktree, the superproject of a submodule, and whether
+/// the repo is bare, a sparse checkout or a partial clone. Nothing is shown in a regular clone.
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("git_context");
+    let config: GitContextConfig = GitContextConfig::try_load(module.config);
+
+    let repo = context.get_repo().ok()?;
+    let git_repo = repo.open();
+
+    let kind = git_repo.kind();
+    let worktree = git_repo
+        .worktree()
+        .and_then(|worktree| worktree.id().map(|id| id.to_string()));
+    let main_worktree = worktree
+        .as_ref()
+        .and_then(|_| get_main_worktree(git_repo.common_dir(), &context.current_dir));
+    let superproject = match kind {
+        Kind::Submodule => repo.workdir.as_deref().and_then(get_superproject),
+        _ => None,
+    };
+
+    let git_config = git_repo.config_snapshot();
+    let is_sparse = git_config
+        .boolean("core.sparseCheckout")
+        .unwrap_or_default();
+    let is_partial_clone = git_config.string("extensions.partialClone").is_some();
+    let is_bare = matches!(kind, Kind::Bare);
+
+    let flags: Vec<&str> = [
+        (is_sparse, config.sparse),
+        (is_partial_clone, config.partial_clone),
+        (is_bare, config.bare),
+    ]
+    .iter()
+    .filter(|(active, label)| *active && !label.is_empty())
+    .map(|(_, label)| *label)
+    .collect();
+
+    if worktree.is_none() && superproject.is_none() && flags.is_empty() {
+        return None;
+    }
+
+    let home = context.get_home();
+    let display_path = |path: &Path| match &home {
+        Some(home) => contract_path(path, home, "~").to_string(),
+        None => path.to_string_lossy().to_string(),
+    };
+
+    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+        formatter
+            .map_meta(|variable, _| match variable {
+                "worktree_symbol" => Some(config.worktree_symbol),
+                "submodule_symbol" => Some(config.submodule_symbol),
+                _ => None,
+            })
+            .map_style(|variable| match variable {
+                "style" => Some(Ok(config.style)),
+                _ => None,
+            })
+            .map(|variable| match variable {
+                "worktree" => worktree.clone().map(Ok),
+                "main_worktree" => main_worktree.as_deref().map(display_path).map(Ok),
+                "superproject" => superproject.as_deref().map(display_path).map(Ok),
+                "sparse" => is_sparse.then(|| Ok(config.sparse.to_string())),
+                "partial_clone" => is_partial_clone.then(|| Ok(config.partial_clone.to_string())),
+                "bare" => is_bare.then(|| Ok(config.bare.to_string())),
+                "flags" => (!flags.is_empty()).then(|| Ok(flags.join(" "))),
+                _ => None,
+            })
+            .parse(None, Some(context))
+    });
+
+    module.set_segments(match parsed {
+        Ok(segments) => segments,
+        Err(error) => {
+            log::warn!("Error in module `git_context`:\n{}", error);
+            return None;
+        }
+    });
+
+    Some(module)
+}
+
+/// The main worktree is the parent of the common `.git` directory, unless the main repo is bare
+fn get_main_worktree(common_dir: &Path, current_dir: &Path) -> Option<PathBuf> {
+    // The common dir of linked worktrees is relative, e.g. `.git/worktrees/name/../..`
+    let common_dir = gix::path::normalize(common_dir, current_dir)?;
+    match common_dir.parent() {
+        Some(parent) if common_dir.file_name().map_or(false, |name| name == ".git") => {
+            Some(parent.to_path_buf())
+        }
+        _ => Some(common_dir.into_owned()),
+    }
+}
+
+/// The closest ancestor of the submodule which is a working tree itself
+fn get_superproject(workdir: &Path) -> Option<PathBuf> {
+    workdir
+        .ancestors()
+        .skip(1)
+        .find(|dir| dir.join(".git").exists())
+        .map(Path::to_path_buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use nu_ansi_term::Color;
+    use std::io;
+    use std::path::Path;
+
+    use crate::test::{fixture_repo, FixtureProvider, ModuleRenderer};
+    use crate::utils::create_command;
+
+    fn git(args: &[&str], dir: &Path) -> io::Result<()> {
+        create_command("git")?
+            .args(args)
+            .current_dir(dir)
+            .output()?;
+        Ok(())
+    }
+
+    #[test]
+    fn show_nothing_on_empty_dir() -> io::Result<()> {
+        let repo_dir = tempfile::tempdir()?;
+
+        let actual = ModuleRenderer::new("git_context")
+            .path(repo_dir.path())
+            .collect();
+
+        assert_eq!(None, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn show_nothing_in_regular_clone() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+
+        let actual = ModuleRenderer::new("git_context")
+            .path(repo_dir.path())
+            .collect();
+
+        assert_eq!(None, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn test_linked_worktree() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+        let worktrees_dir = tempfile::tempdir()?;
+        let worktree_dir = worktrees_dir.path().join("hotfix");
+        create_command("git")?
+            .args(["worktree", "add", "-b", "hotfix"])
+            .arg(&worktree_dir)
+            .current_dir(repo_dir.path())
+            .output()?;
+
+        let actual = ModuleRenderer::new("git_context")
+            .path(&worktree_dir)
+            .collect();
+        let expected = Some(format!("{} ", Color::Cyan.bold().paint("🌲 hotfix")));
+        assert_eq!(expected, actual);
+
+        let actual = ModuleRenderer::new("git_context")
+            .path(&worktree_dir)
+            .config(toml::toml! {
+                [git_context]
+                format = "$worktree of $main_worktree"
+            })
+            .collect();
+        let expected = Some(format!(
+            "hotfix of {}",
+            repo_dir.path().to_string_lossy().replace('\\', "/")
+        ));
+        assert_eq!(expected, actual);
+
+        repo_dir.close()?;
+        worktrees_dir.close()
+    }
+
+    #[test]
+    fn test_sparse_partial_clone() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+        git(&["config", "core.sparseCheckout", "true"], repo_dir.path())?;
+        git(
+            &["config", "extensions.partialClone", "origin"],
+            repo_dir.path(),
+        )?;
+
+        let actual = ModuleRenderer::new("git_context")
+            .path(repo_dir.path())
+            .collect();
+        let expected = Some(format!("{} ", Color::Cyan.bold().paint("[sparse partial]")));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn test_bare_repo() -> io::Result<()> {
+        let repo_dir = tempfile::tempdir()?;
+        git(&["init", "--bare"], repo_dir.path())?;
+
+        let actual = ModuleRenderer::new("git_context")
+            .path(repo_dir.path())
+            .config(toml::toml! {
+                [git_context]
+                format = "$bare"
+                bare = "B"
+            })
+            .collect();
+
+        assert_eq!(Some(String::from("B")), actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn test_submodule() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+        let lib_dir = fixture_repo(FixtureProvider::Git)?;
+        create_command("git")?
+            .args(["-c", "protocol.file.allow=always", "submodule", "add"])
+            .arg(lib_dir.path())
+            .arg("lib")
+            .current_dir(repo_dir.path())
+            .output()?;
+        let submodule_dir = repo_dir.path().join("lib");
+
+        let actual = ModuleRenderer::new("git_context")
+            .path(&submodule_dir)
+            .config(toml::toml! {
+                [git_context]
+                format = "$submodule_symbol$superproject"
+            })
+            .collect();
+        let expected = Some(format!(
+            "↳ {}",
+            repo_dir.path().to_string_lossy().replace('\\', "/")
+        ));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()?;
+        lib_dir.close()
+    }
+}
@@ -4,6 +4,7 @@ use std::ffi::OsStr;
 use crate::{
     config::ModuleConfig, configs::git_metrics::GitMetricsConfig,
     formatter::string_formatter::StringFormatterError, formatter::StringFormatter, module::Module,
+    utils::render_number,
 };
 
 use super::Context;
@@ -49,8 +50,10 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map(|variable| match variable {
-                "added" => GitDiff::get_variable(config.only_nonzero_diffs, stats.added),
-                "deleted" => GitDiff::get_variable(config.only_nonzero_diffs, stats.deleted),
+                "added" => GitDiff::get_variable(config.only_nonzero_diffs, stats.added, context),
+                "deleted" => {
+                    GitDiff::get_variable(config.only_nonzero_diffs, stats.deleted, context)
+                }
                 _ => None,
             })
             .parse(None, Some(context))
@@ -94,16 +97,15 @@ impl<'a> GitDiff<'a> {
         }
     }
 
+    /// The count of changed lines with the separators of the locale
     pub fn get_variable(
         only_nonzero_diffs: bool,
         changed: &str,
-    ) -> Option<Result<&str, StringFormatterError>> {
-        match only_nonzero_diffs {
-            true => match changed {
-                "0" => None,
-                _ => Some(Ok(changed)),
-            },
-            false => Some(Ok(changed)),
+        context: &Context,
+    ) -> Option<Result<String, StringFormatterError>> {
+        match (only_nonzero_diffs, changed) {
+            (true, "0") => None,
+            _ => Some(Ok(render_number(changed, context.number_separators()))),
         }
     }
 }
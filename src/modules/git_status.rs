@@ -8,6 +8,7 @@ use crate::formatter::StringFormatter;
 use crate::segment::Segment;
 use std::ffi::OsStr;
 use std::sync::Arc;
+use std::time::Duration;
 
 const ALL_STATUS_FORMAT: &str =
     "$conflicted$stashed$deleted$renamed$modified$typechanged$staged$untracked";
@@ -21,6 +22,10 @@ const ALL_STATUS_FORMAT: &str =
 ///   - `⇣` – This branch is behind of the branch being tracked
 ///   - `⇕` – This branch has diverged from the branch being tracked
 ///   - `` – This branch is up-to-date with the branch being tracked
+///   - `⊘` – The branch being tracked was deleted
+///   - `⇢` – This branch is ahead of the branch it is pushed to, if that's not the tracked one
+///   - `⇠` – This branch is behind of the branch it is pushed to
+///   - `⇄` – This branch has diverged from the branch it is pushed to
 ///   - `?` — There are untracked files in the working directory
 ///   - `$` — A stash exists for the local repository
 ///   - `!` — There are file modifications in the working directory
@@ -34,7 +39,12 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let info = Arc::new(GitStatusInfo::load(context, config.clone()));
 
     //Return None if not in git repository
-    context.get_repo().ok()?;
+    let repo = context.get_repo().ok()?;
+
+    // The enabled `vcs` module shows the state of colocated jj repositories
+    if context.is_jj_colocated_shown_by_vcs(repo) {
+        return None;
+    }
 
     if let Some(git_status) = git_status_wsl(context, &config) {
         if git_status.is_empty() {
@@ -60,27 +70,40 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                     "stashed" => info.get_stashed().and_then(|count| {
                         format_count(config.stashed, "git_status.stashed", context, count)
                     }),
-                    "ahead_behind" => info.get_ahead_behind().and_then(|(ahead, behind)| {
-                        let (ahead, behind) = (ahead?, behind?);
-                        if ahead > 0 && behind > 0 {
-                            format_text(
-                                config.diverged,
-                                "git_status.diverged",
+                    "ahead_behind" => match info.get_upstream()? {
+                        Upstream::Tracking(ahead, behind) => format_ahead_behind(
+                            ahead,
+                            behind,
+                            [
+                                (config.ahead, "git_status.ahead"),
+                                (config.behind, "git_status.behind"),
+                                (config.diverged, "git_status.diverged"),
+                                (config.up_to_date, "git_status.up_to_date"),
+                            ],
+                            context,
+                        ),
+                        Upstream::Gone => {
+                            format_symbol(config.upstream_gone, "git_status.upstream_gone", context)
+                        }
+                        Upstream::None => {
+                            format_symbol(config.no_upstream, "git_status.no_upstream", context)
+                        }
+                    },
+                    "push_ahead_behind" => {
+                        info.get_push_ahead_behind().and_then(|(ahead, behind)| {
+                            format_ahead_behind(
+                                ahead,
+                                behind,
+                                [
+                                    (config.push_ahead, "git_status.push_ahead"),
+                                    (config.push_behind, "git_status.push_behind"),
+                                    (config.push_diverged, "git_status.push_diverged"),
+                                    (config.push_up_to_date, "git_status.push_up_to_date"),
+                                ],
                                 context,
-                                |variable| match variable {
-                                    "ahead_count" => Some(ahead.to_string()),
-                                    "behind_count" => Some(behind.to_string()),
-                                    _ => None,
-                                },
                             )
-                        } else if ahead > 0 && behind == 0 {
-                            format_count(config.ahead, "git_status.ahead", context, ahead)
-                        } else if behind > 0 && ahead == 0 {
-                            format_count(config.behind, "git_status.behind", context, behind)
-                        } else {
-                            format_symbol(config.up_to_date, "git_status.up_to_date", context)
-                        }
-                    }),
+                        })
+                    }
                     "conflicted" => info.get_conflicted().and_then(|count| {
                         format_count(config.conflicted, "git_status.conflicted", context, count)
                     }),
@@ -96,8 +119,27 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                     "staged" => info.get_staged().and_then(|count| {
                         format_count(config.staged, "git_status.staged", context, count)
                     }),
-                    "untracked" => info.get_untracked().and_then(|count| {
-                        format_count(config.untracked, "git_status.untracked", context, count)
+                    "untracked" => info.get_untracked().and_then(|(count, is_complete)| {
+                        if is_complete {
+                            return format_count(
+                                config.untracked,
+                                "git_status.untracked",
+                                context,
+                                count,
+                            );
+                        }
+                        // The scan ran out of time, so there are at least `count` files
+                        let count = (count > 0)
+                            .then(|| format!("{}{}", config.partial_count_prefix, count))?;
+                        format_text(
+                            config.untracked,
+                            "git_status.untracked",
+                            context,
+                            |variable| match variable {
+                                "count" => Some(count.clone()),
+                                _ => None,
+                            },
+                        )
                     }),
                     "typechanged" => info.get_typechanged().and_then(|count| {
                         format_count(config.typechanged, "git_status.typechanged", context, count)
@@ -131,6 +173,7 @@ struct GitStatusInfo<'a> {
     config: GitStatusConfig<'a>,
     repo_status: OnceCell<Option<RepoStatus>>,
     stashed_count: OnceCell<Option<usize>>,
+    push_ahead_behind: OnceCell<Option<(usize, usize)>>,
 }
 
 impl<'a> GitStatusInfo<'a> {
@@ -140,11 +183,18 @@ impl<'a> GitStatusInfo<'a> {
             config,
             repo_status: OnceCell::new(),
             stashed_count: OnceCell::new(),
+            push_ahead_behind: OnceCell::new(),
         }
     }
 
-    pub fn get_ahead_behind(&self) -> Option<(Option<usize>, Option<usize>)> {
-        self.get_repo_status().map(|data| (data.ahead, data.behind))
+    pub fn get_upstream(&self) -> Option<Upstream> {
+        self.get_repo_status().as_ref()?.get_upstream()
+    }
+
+    pub fn get_push_ahead_behind(&self) -> Option<(usize, usize)> {
+        *self
+            .push_ahead_behind
+            .get_or_init(|| get_push_ahead_behind(self.context))
     }
 
     pub fn get_repo_status(&self) -> &Option<RepoStatus> {
@@ -189,8 +239,10 @@ impl<'a> GitStatusInfo<'a> {
         self.get_repo_status().map(|data| data.staged)
     }
 
-    pub fn get_untracked(&self) -> Option<usize> {
-        self.get_repo_status().map(|data| data.untracked)
+    /// The number of untracked files, and whether they were all counted
+    pub fn get_untracked(&self) -> Option<(usize, bool)> {
+        self.get_repo_status()
+            .map(|data| (data.untracked, !data.is_untracked_partial))
     }
 
     pub fn get_typechanged(&self) -> Option<usize> {
@@ -214,13 +266,17 @@ fn get_repo_status(context: &Context, config: &GitStatusConfig) -> Option<RepoSt
     // for performance reasons, only pass flags if necessary...
     let has_ahead_behind = !config.ahead.is_empty() || !config.behind.is_empty();
     let has_up_to_date_diverged = !config.up_to_date.is_empty() || !config.diverged.is_empty();
-    if has_ahead_behind || has_up_to_date_diverged {
+    let has_upstream_state = !config.upstream_gone.is_empty() || !config.no_upstream.is_empty();
+    if has_ahead_behind || has_up_to_date_diverged || has_upstream_state {
         args.push(OsStr::new("--branch"));
     }
 
     // ... and add flags that omit information the user doesn't want
     let has_untracked = !config.untracked.is_empty();
-    if !has_untracked {
+    // With a budget, untracked files are counted separately, so the count can be cut short
+    let untracked_budget = (has_untracked && config.untracked_scan_budget_ms > 0)
+        .then(|| Duration::from_millis(config.untracked_scan_budget_ms));
+    if !has_untracked || untracked_budget.is_some() {
         args.push(OsStr::new("--untracked-files=no"));
     }
     if config.ignore_submodules {
@@ -235,14 +291,50 @@ fn get_repo_status(context: &Context, config: &GitStatusConfig) -> Option<RepoSt
     statuses.for_each(|status| {
         if status.starts_with("# branch.ab ") {
             repo_status.set_ahead_behind(status);
+        } else if let Some(head) = status.strip_prefix("# branch.head ") {
+            repo_status.is_detached = Some(head == "(detached)");
+        } else if status.starts_with("# branch.upstream ") {
+            repo_status.has_upstream = true;
         } else if !status.starts_with('#') {
             repo_status.add(status);
         }
     });
 
+    if let Some(budget) = untracked_budget {
+        match count_untracked(context, budget) {
+            Some((count, is_complete)) => {
+                repo_status.untracked = count;
+                repo_status.is_untracked_partial = !is_complete;
+            }
+            None => log::debug!("count_untracked: git ls-files execution failed"),
+        }
+    }
+
     Some(repo_status)
 }
 
+/// Counts the untracked files like `git status` does, collapsing untracked directories, until
+/// the `budget` runs out
+fn count_untracked(context: &Context, budget: Duration) -> Option<(usize, bool)> {
+    context.count_cmd_lines(
+        "git",
+        &[
+            OsStr::new("-C"),
+            context.current_dir.as_os_str(),
+            OsStr::new("--no-optional-locks"),
+            OsStr::new("ls-files"),
+            OsStr::new("--others"),
+            OsStr::new("--exclude-standard"),
+            OsStr::new("--directory"),
+            OsStr::new("--no-empty-directory"),
+            OsStr::new("--"),
+            // the whole work tree, not just the current directory
+            OsStr::new(":/"),
+        ],
+        budget,
+    )
+}
+
 fn get_stashed_count(context: &Context) -> Option<usize> {
     let stash_output = context.exec_cmd(
         "git",
@@ -258,10 +350,61 @@ fn get_stashed_count(context: &Context) -> Option<usize> {
     Some(stash_output.stdout.trim().lines().count())
 }
 
+/// The number of commits ahead and behind the branch being pushed to, when it's on another
+/// remote than the upstream, e.g. in triangular workflows pulling from `origin` and pushing to a
+/// fork.
+fn get_push_ahead_behind(context: &Context) -> Option<(usize, usize)> {
+    let repo = context.get_repo().ok()?;
+    let branch = repo.branch.as_deref()?;
+    let git_repo = repo.open();
+    let git_config = git_repo.config_snapshot();
+
+    let push_remote = git_config
+        .string(format!("branch.{branch}.pushRemote").as_str())
+        .or_else(|| git_config.string("remote.pushDefault"))?
+        .to_string();
+    let upstream_remote = repo
+        .remote
+        .as_ref()
+        .and_then(|remote| remote.name.as_deref());
+    if upstream_remote == Some(push_remote.as_str()) {
+        return None;
+    }
+
+    let range = format!("HEAD...refs/remotes/{push_remote}/{branch}");
+    let output = context.exec_cmd(
+        "git",
+        &[
+            OsStr::new("-C"),
+            context.current_dir.as_os_str(),
+            OsStr::new("--no-optional-locks"),
+            OsStr::new("rev-list"),
+            OsStr::new("--left-right"),
+            OsStr::new("--count"),
+            OsStr::new(&range),
+        ],
+    )?;
+
+    let (ahead, behind) = output.stdout.trim().split_once('\t')?;
+    Some((ahead.parse().ok()?, behind.parse().ok()?))
+}
+
+/// The state of the branch being tracked, as reported by `git status --branch`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Upstream {
+    /// How many commits this branch is ahead and behind of its upstream
+    Tracking(usize, usize),
+    /// The upstream is configured, but the branch was deleted on the remote
+    Gone,
+    None,
+}
+
 #[derive(Default, Debug, Copy, Clone)]
 struct RepoStatus {
     ahead: Option<usize>,
     behind: Option<usize>,
+    is_detached: Option<bool>,
+    has_upstream: bool,
     conflicted: usize,
     deleted: usize,
     renamed: usize,
@@ -269,6 +412,7 @@ struct RepoStatus {
     staged: usize,
     typechanged: usize,
     untracked: usize,
+    is_untracked_partial: bool,
 }
 
 impl RepoStatus {
@@ -326,6 +470,19 @@ impl RepoStatus {
         }
     }
 
+    /// Branch headers are only available if `--branch` was passed, and a detached `HEAD` has
+    /// no upstream
+    fn get_upstream(&self) -> Option<Upstream> {
+        if self.is_detached? {
+            return None;
+        }
+        Some(match (self.has_upstream, self.ahead, self.behind) {
+            (true, Some(ahead), Some(behind)) => Upstream::Tracking(ahead, behind),
+            (true, _, _) => Upstream::Gone,
+            (false, _, _) => Upstream::None,
+        })
+    }
+
     fn set_ahead_behind(&mut self, s: &str) {
         let re = Regex::new(r"branch\.ab \+([0-9]+) \-([0-9]+)").unwrap();
 
@@ -381,6 +538,36 @@ fn format_symbol(format_str: &str, config_path: &str, context: &Context) -> Opti
     format_text(format_str, config_path, context, |_variable| None)
 }
 
+/// Formats the ahead, behind, diverged or up-to-date symbol, given in that order with their
+/// config paths
+fn format_ahead_behind(
+    ahead: usize,
+    behind: usize,
+    symbols: [(&str, &str); 4],
+    context: &Context,
+) -> Option<Vec<Segment>> {
+    let [ahead_symbol, behind_symbol, diverged_symbol, up_to_date_symbol] = symbols;
+
+    if ahead > 0 && behind > 0 {
+        format_text(
+            diverged_symbol.0,
+            diverged_symbol.1,
+            context,
+            |variable| match variable {
+                "ahead_count" => Some(ahead.to_string()),
+                "behind_count" => Some(behind.to_string()),
+                _ => None,
+            },
+        )
+    } else if ahead > 0 && behind == 0 {
+        format_count(ahead_symbol.0, ahead_symbol.1, context, ahead)
+    } else if behind > 0 && ahead == 0 {
+        format_count(behind_symbol.0, behind_symbol.1, context, behind)
+    } else {
+        format_symbol(up_to_date_symbol.0, up_to_date_symbol.1, context)
+    }
+}
+
 #[cfg(target_os = "linux")]
 fn git_status_wsl(context: &Context, conf: &GitStatusConfig) -> Option<String> {
     use crate::utils::create_command;
@@ -500,7 +687,7 @@ mod tests {
     use std::path::Path;
 
     use crate::test::{fixture_repo, FixtureProvider, ModuleRenderer};
-    use crate::utils::create_command;
+    use crate::utils::{create_command, CommandOutput};
 
     #[allow(clippy::unnecessary_wraps)]
     fn format_output(symbols: &str) -> Option<String> {
@@ -523,6 +710,25 @@ mod tests {
         repo_dir.close()
     }
 
+    #[test]
+    fn show_nothing_in_jj_colocated_repo_with_vcs() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+        std::fs::create_dir(repo_dir.path().join(".jj"))?;
+        create_untracked(repo_dir.path())?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .path(repo_dir.path())
+            .config(toml::toml! {
+                [vcs]
+                disabled = false
+            })
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
     #[test]
     fn shows_behind() -> io::Result<()> {
         let repo_dir = fixture_repo(FixtureProvider::Git)?;
@@ -644,6 +850,96 @@ mod tests {
         repo_dir.close()
     }
 
+    #[test]
+    fn shows_upstream_gone() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+
+        create_command("git")?
+            .args(["update-ref", "-d", "refs/remotes/origin/master"])
+            .current_dir(repo_dir.path())
+            .output()?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .path(repo_dir.path())
+            .collect();
+        let expected = format_output("⊘");
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_no_upstream() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+
+        create_command("git")?
+            .args(["checkout", "-b", "local"])
+            .current_dir(repo_dir.path())
+            .output()?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                no_upstream = "∅"
+            })
+            .path(repo_dir.path())
+            .collect();
+        let expected = format_output("∅");
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_push_remote_ahead_behind() -> io::Result<()> {
+        let fork_dir = fixture_repo(FixtureProvider::Git)?;
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+
+        behind(fork_dir.path())?;
+        create_command("git")?
+            .args(["remote", "add", "--fetch", "fork"])
+            .arg(fork_dir.path())
+            .current_dir(repo_dir.path())
+            .output()?;
+        create_command("git")?
+            .args(["config", "remote.pushDefault", "fork"])
+            .current_dir(repo_dir.path())
+            .output()?;
+        ahead(repo_dir.path())?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                push_ahead = "⇢$count"
+            })
+            .path(repo_dir.path())
+            .collect();
+        let expected = format_output("⇡⇢2");
+
+        assert_eq!(expected, actual);
+        repo_dir.close()?;
+        fork_dir.close()
+    }
+
+    #[test]
+    fn ignores_push_remote_same_as_upstream() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+
+        create_command("git")?
+            .args(["config", "branch.master.pushRemote", "origin"])
+            .current_dir(repo_dir.path())
+            .output()?;
+        ahead(repo_dir.path())?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .path(repo_dir.path())
+            .collect();
+        let expected = format_output("⇡");
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
     #[test]
     fn shows_conflicted() -> io::Result<()> {
         let repo_dir = fixture_repo(FixtureProvider::Git)?;
@@ -712,6 +1008,60 @@ mod tests {
         repo_dir.close()
     }
 
+    #[test]
+    fn shows_untracked_file_with_scan_budget() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+
+        create_untracked(repo_dir.path())?;
+        fs::create_dir_all(repo_dir.path().join("untracked_dir/nested"))?;
+        File::create(repo_dir.path().join("untracked_dir/nested/a"))?.sync_all()?;
+        File::create(repo_dir.path().join("untracked_dir/b"))?.sync_all()?;
+        let sub_dir = repo_dir.path().join("untracked_dir");
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                untracked = "?$count"
+                untracked_scan_budget_ms = 10000
+            })
+            .path(sub_dir)
+            .collect();
+        let expected = format_output("?2");
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_untracked_count_of_scan() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+        let scan_command = format!(
+            "git -C {} --no-optional-locks ls-files --others --exclude-standard --directory \
+             --no-empty-directory -- :/",
+            repo_dir.path().display()
+        );
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                untracked = "?$count"
+                untracked_scan_budget_ms = 10000
+            })
+            .cmd(
+                &scan_command,
+                Some(CommandOutput {
+                    stdout: String::from("a\nb/\nc\n"),
+                    stderr: String::default(),
+                }),
+            )
+            .path(repo_dir.path())
+            .collect();
+        let expected = format_output("?3");
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
     #[test]
     fn doesnt_show_untracked_file_if_disabled() -> io::Result<()> {
         let repo_dir = fixture_repo(FixtureProvider::Git)?;
@@ -1,12 +1,9 @@
-use std::io::Error;
-use std::path::Path;
-
 use super::utils::truncate::truncate_text;
+use super::utils::vcs::hg::{get_branch_name, get_current_bookmark, get_topic_name};
 use super::{Context, Module, ModuleConfig};
 
 use crate::configs::hg_branch::HgBranchConfig;
 use crate::formatter::StringFormatter;
-use crate::utils::read_file;
 
 /// Creates a module with the Hg bookmark or branch in the current directory
 ///
@@ -32,12 +29,11 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     };
 
     let repo_root = context.begin_ancestor_scan().set_folders(&[".hg"]).scan()?;
-    let branch_name = get_hg_current_bookmark(repo_root).unwrap_or_else(|_| {
-        get_hg_branch_name(repo_root).unwrap_or_else(|_| String::from("default"))
-    });
+    let branch_name = get_current_bookmark(repo_root)
+        .unwrap_or_else(|_| get_branch_name(repo_root).unwrap_or_else(|_| String::from("default")));
 
     let branch_graphemes = truncate_text(&branch_name, len, config.truncation_symbol);
-    let topic_graphemes = if let Ok(topic) = get_hg_topic_name(repo_root) {
+    let topic_graphemes = if let Ok(topic) = get_topic_name(repo_root) {
         truncate_text(&topic, len, config.truncation_symbol)
     } else {
         String::new()
@@ -72,21 +68,6 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
-fn get_hg_branch_name(hg_root: &Path) -> Result<String, Error> {
-    match read_file(hg_root.join(".hg").join("branch")) {
-        Ok(b) => Ok(b.trim().to_string()),
-        Err(e) => Err(e),
-    }
-}
-
-fn get_hg_current_bookmark(hg_root: &Path) -> Result<String, Error> {
-    read_file(hg_root.join(".hg").join("bookmarks.current"))
-}
-
-fn get_hg_topic_name(hg_root: &Path) -> Result<String, Error> {
-    read_file(hg_root.join(".hg").join("topic"))
-}
-
 #[cfg(test)]
 mod tests {
     use nu_ansi_term::{Color, Style};
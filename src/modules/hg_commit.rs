@@ -0,0 +1,173 @@
+use super::utils::vcs::hg::{get_parents, get_tag};
+use super::{Context, Module, ModuleConfig};
+
+use crate::configs::hg_commit::HgCommitConfig;
+use crate::formatter::StringFormatter;
+
+/// Creates a module with the Mercurial changeset in the current directory
+///
+/// Will display the changeset id of the working directory parent if the current directory is an
+/// hg repo. It is read from `.hg/dirstate`, so `hg` doesn't have to run.
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("hg_commit");
+    let config: HgCommitConfig = HgCommitConfig::try_load(module.config);
+
+    // As we default to disabled=true, we have to check here after loading our config module,
+    // before it was only checking against whatever is in the config starship.toml
+    if config.disabled {
+        return None;
+    };
+
+    let repo_root = context.begin_ancestor_scan().set_folders(&[".hg"]).scan()?;
+    let (node, _) = get_parents(repo_root)?;
+
+    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+        formatter
+            .map_style(|variable| match variable {
+                "style" => Some(Ok(config.style)),
+                _ => None,
+            })
+            .map(|variable| match variable {
+                "hash" => Some(Ok(node.chars().take(config.hash_length).collect())),
+                "tag" if !config.tag_disabled => Some(Ok(format!(
+                    "{}{}",
+                    config.tag_symbol,
+                    get_tag(repo_root, &node)?
+                ))),
+                _ => None,
+            })
+            .parse(None, Some(context))
+    });
+
+    module.set_segments(match parsed {
+        Ok(segments) => segments,
+        Err(error) => {
+            log::warn!("Error in module `hg_commit`:\n{}", error);
+            return None;
+        }
+    });
+
+    Some(module)
+}
+
+#[cfg(test)]
+mod tests {
+    use nu_ansi_term::Color;
+    use std::fs;
+    use std::io;
+    use std::path::Path;
+
+    use crate::test::{fixture_repo, FixtureProvider, ModuleRenderer};
+    use crate::utils::create_command;
+
+    const NODE: &str = "0123456789abcdef0123456789abcdef01234567";
+
+    fn fake_repo() -> io::Result<tempfile::TempDir> {
+        let repo_dir = tempfile::tempdir()?;
+        fs::create_dir(repo_dir.path().join(".hg"))?;
+        let mut dirstate = (0..NODE.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&NODE[i..i + 2], 16).unwrap())
+            .collect::<Vec<u8>>();
+        dirstate.extend([0; 20]);
+        fs::write(repo_dir.path().join(".hg/dirstate"), dirstate)?;
+        Ok(repo_dir)
+    }
+
+    #[test]
+    fn show_nothing_on_empty_dir() -> io::Result<()> {
+        let repo_dir = tempfile::tempdir()?;
+
+        let actual = ModuleRenderer::new("hg_commit")
+            .path(repo_dir.path())
+            .config(toml::toml! {
+                [hg_commit]
+                disabled = false
+            })
+            .collect();
+
+        assert_eq!(None, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn show_nothing_if_disabled() -> io::Result<()> {
+        let repo_dir = fake_repo()?;
+
+        let actual = ModuleRenderer::new("hg_commit")
+            .path(repo_dir.path())
+            .collect();
+
+        assert_eq!(None, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_hash() -> io::Result<()> {
+        let repo_dir = fake_repo()?;
+
+        let actual = ModuleRenderer::new("hg_commit")
+            .path(repo_dir.path())
+            .config(toml::toml! {
+                [hg_commit]
+                disabled = false
+            })
+            .collect();
+        let expected = Some(format!("{} ", Color::Green.bold().paint("(0123456789ab)")));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_hash_and_tag() -> io::Result<()> {
+        let repo_dir = fake_repo()?;
+        fs::write(repo_dir.path().join(".hgtags"), format!("{NODE} v1.2.0\n"))?;
+
+        let actual = ModuleRenderer::new("hg_commit")
+            .path(repo_dir.path())
+            .config(toml::toml! {
+                [hg_commit]
+                disabled = false
+                hash_length = 7
+                tag_disabled = false
+                tag_symbol = " @"
+            })
+            .collect();
+        let expected = Some(format!(
+            "{} ",
+            Color::Green.bold().paint("(0123456 @v1.2.0)")
+        ));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    #[ignore]
+    fn shows_hash_of_fixture_repo() -> io::Result<()> {
+        let tempdir = fixture_repo(FixtureProvider::Hg)?;
+        let repo_dir = tempdir.path();
+        let expected_hash = run_hg(&["id", "-i", "--debug"], repo_dir)?;
+
+        let actual = ModuleRenderer::new("hg_commit")
+            .path(repo_dir)
+            .config(toml::toml! {
+                [hg_commit]
+                disabled = false
+                format = "$hash"
+            })
+            .collect();
+
+        assert_eq!(Some(expected_hash.trim()[..12].to_string()), actual);
+        tempdir.close()
+    }
+
+    fn run_hg(args: &[&str], repo_dir: &Path) -> io::Result<String> {
+        let output = create_command("hg")?
+            .args(args)
+            .current_dir(repo_dir)
+            .output()?;
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
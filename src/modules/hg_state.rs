@@ -0,0 +1,139 @@
+use super::utils::vcs::hg::get_operation;
+use super::utils::vcs::Operation;
+use super::{Context, Module, ModuleConfig};
+
+use crate::configs::hg_state::HgStateConfig;
+use crate::formatter::StringFormatter;
+
+/// Creates a module with the state of the Mercurial repository at the current directory
+///
+/// During a merge, rebase, graft, histedit, unshelve or bisect, the prompt will include
+/// a breakdown of the current state, read from the state files in `.hg`.
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("hg_state");
+    let config: HgStateConfig = HgStateConfig::try_load(module.config);
+
+    // As we default to disabled=true, we have to check here after loading our config module,
+    // before it was only checking against whatever is in the config starship.toml
+    if config.disabled {
+        return None;
+    };
+
+    let repo_root = context.begin_ancestor_scan().set_folders(&[".hg"]).scan()?;
+    let state = match get_operation(repo_root)? {
+        Operation::Merge => config.merge,
+        Operation::Rebase => config.rebase,
+        Operation::Graft => config.graft,
+        Operation::Histedit => config.histedit,
+        Operation::Unshelve => config.unshelve,
+        Operation::Bisect => config.bisect,
+        _ => return None,
+    };
+
+    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+        formatter
+            .map_meta(|variable, _| match variable {
+                "state" => Some(state),
+                _ => None,
+            })
+            .map_style(|variable| match variable {
+                "style" => Some(Ok(config.style)),
+                _ => None,
+            })
+            .parse(None, Some(context))
+    });
+
+    module.set_segments(match parsed {
+        Ok(segments) => segments,
+        Err(error) => {
+            log::warn!("Error in module `hg_state`:\n{}", error);
+            return None;
+        }
+    });
+
+    Some(module)
+}
+
+#[cfg(test)]
+mod tests {
+    use nu_ansi_term::Color;
+    use std::fs;
+    use std::io;
+
+    use crate::test::ModuleRenderer;
+
+    #[test]
+    fn show_nothing_on_empty_dir() -> io::Result<()> {
+        let repo_dir = tempfile::tempdir()?;
+
+        let actual = ModuleRenderer::new("hg_state")
+            .path(repo_dir.path())
+            .config(toml::toml! {
+                [hg_state]
+                disabled = false
+            })
+            .collect();
+
+        assert_eq!(None, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn show_nothing_without_operation() -> io::Result<()> {
+        let repo_dir = tempfile::tempdir()?;
+        fs::create_dir(repo_dir.path().join(".hg"))?;
+
+        let actual = ModuleRenderer::new("hg_state")
+            .path(repo_dir.path())
+            .config(toml::toml! {
+                [hg_state]
+                disabled = false
+            })
+            .collect();
+
+        assert_eq!(None, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_rebasing() -> io::Result<()> {
+        let repo_dir = tempfile::tempdir()?;
+        fs::create_dir(repo_dir.path().join(".hg"))?;
+        fs::write(repo_dir.path().join(".hg/rebasestate"), "")?;
+        let sub_dir = repo_dir.path().join("src");
+        fs::create_dir(&sub_dir)?;
+
+        let actual = ModuleRenderer::new("hg_state")
+            .path(&sub_dir)
+            .config(toml::toml! {
+                [hg_state]
+                disabled = false
+            })
+            .collect();
+        let expected = Some(format!("({}) ", Color::Yellow.bold().paint("REBASING")));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_grafting_over_merge() -> io::Result<()> {
+        let repo_dir = tempfile::tempdir()?;
+        fs::create_dir_all(repo_dir.path().join(".hg/merge"))?;
+        fs::write(repo_dir.path().join(".hg/merge/state2"), "")?;
+        fs::write(repo_dir.path().join(".hg/graftstate"), "")?;
+
+        let actual = ModuleRenderer::new("hg_state")
+            .path(repo_dir.path())
+            .config(toml::toml! {
+                [hg_state]
+                disabled = false
+                graft = "GRAFT"
+            })
+            .collect();
+        let expected = Some(format!("({}) ", Color::Yellow.bold().paint("GRAFT")));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+}
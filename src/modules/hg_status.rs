@@ -0,0 +1,253 @@
+use super::utils::vcs::hg::{get_ahead_behind, get_status};
+use super::{Context, Module, ModuleConfig};
+
+use crate::configs::hg_status::HgStatusConfig;
+use crate::formatter::StringFormatter;
+use crate::segment::Segment;
+
+const ALL_STATUS_FORMAT: &str = "$removed$modified$added$unknown";
+
+/// Creates a module with the status of the Mercurial working directory
+///
+/// Will display the status if the current directory is an hg repo
+/// By default, the following symbols will be used to represent the status:
+///   - `✘` — A file was removed, or deleted without `hg remove`
+///   - `!` — A file was modified
+///   - `+` — A file was added
+///   - `?` — There are unknown files in the working directory
+///   - `⇡` — There are changesets which were not pushed yet (draft or secret)
+///   - `⇣` — Public changesets were pulled but are not checked out
+///   - `⇕` — Both of the above
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("hg_status");
+    let config: HgStatusConfig = HgStatusConfig::try_load(module.config);
+
+    // As we default to disabled=true, we have to check here after loading our config module,
+    // before it was only checking against whatever is in the config starship.toml
+    if config.disabled {
+        return None;
+    };
+
+    let repo_root = context.begin_ancestor_scan().set_folders(&[".hg"]).scan()?;
+
+    // Listing unknown files needs `hg`, so only do so if they are shown
+    let with_unknown = !config.unknown.is_empty();
+    let status = get_status(context, repo_root, with_unknown)?;
+
+    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+        formatter
+            .map_meta(|variable, _| match variable {
+                "all_status" => Some(ALL_STATUS_FORMAT),
+                _ => None,
+            })
+            .map_style(|variable: &str| match variable {
+                "style" => Some(Ok(config.style)),
+                _ => None,
+            })
+            .map_variables_to_segments(|variable: &str| {
+                let segments = match variable {
+                    "removed" => {
+                        format_count(config.removed, "hg_status.removed", context, status.removed)
+                    }
+                    "modified" => format_count(
+                        config.modified,
+                        "hg_status.modified",
+                        context,
+                        status.modified,
+                    ),
+                    "added" => format_count(config.added, "hg_status.added", context, status.added),
+                    "unknown" => format_count(
+                        config.unknown,
+                        "hg_status.unknown",
+                        context,
+                        status.unknown.unwrap_or_default(),
+                    ),
+                    "ahead_behind" => {
+                        get_ahead_behind(context, repo_root).and_then(|(ahead, behind)| {
+                            format_ahead_behind(&config, context, ahead, behind)
+                        })
+                    }
+                    _ => None,
+                };
+                segments.map(Ok)
+            })
+            .parse(None, Some(context))
+    });
+
+    module.set_segments(match parsed {
+        Ok(segments) => {
+            if segments.is_empty() {
+                return None;
+            } else {
+                segments
+            }
+        }
+        Err(error) => {
+            log::warn!("Error in module `hg_status`:\n{}", error);
+            return None;
+        }
+    });
+
+    Some(module)
+}
+
+fn format_count(
+    format_str: &str,
+    config_path: &str,
+    context: &Context,
+    count: usize,
+) -> Option<Vec<Segment>> {
+    if count == 0 {
+        return None;
+    }
+
+    match StringFormatter::new(format_str) {
+        Ok(formatter) => formatter
+            .map(|variable| match variable {
+                "count" => Some(Ok(count.to_string())),
+                _ => None,
+            })
+            .parse(None, Some(context))
+            .ok(),
+        Err(_) => {
+            log::warn!("Error parsing format string `{}`", &config_path);
+            None
+        }
+    }
+}
+
+fn format_ahead_behind(
+    config: &HgStatusConfig,
+    context: &Context,
+    ahead: usize,
+    behind: usize,
+) -> Option<Vec<Segment>> {
+    if ahead > 0 && behind > 0 {
+        StringFormatter::new(config.diverged)
+            .map_err(|_| log::warn!("Error parsing format string `hg_status.diverged`"))
+            .ok()?
+            .map(|variable| match variable {
+                "ahead_count" => Some(Ok(ahead.to_string())),
+                "behind_count" => Some(Ok(behind.to_string())),
+                _ => None,
+            })
+            .parse(None, Some(context))
+            .ok()
+    } else if ahead > 0 {
+        format_count(config.ahead, "hg_status.ahead", context, ahead)
+    } else if behind > 0 {
+        format_count(config.behind, "hg_status.behind", context, behind)
+    } else {
+        StringFormatter::new(config.up_to_date)
+            .map_err(|_| log::warn!("Error parsing format string `hg_status.up_to_date`"))
+            .ok()?
+            .parse(None, Some(context))
+            .ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nu_ansi_term::Color;
+    use std::fs;
+    use std::io;
+    use std::path::Path;
+
+    use crate::test::{fixture_repo, FixtureProvider, ModuleRenderer};
+    use crate::utils::create_command;
+
+    fn format_output(symbols: &str) -> Option<String> {
+        Some(format!(
+            "{} ",
+            Color::Red.bold().paint(format!("[{symbols}]"))
+        ))
+    }
+
+    fn render(repo_dir: &Path) -> Option<String> {
+        ModuleRenderer::new("hg_status")
+            .path(repo_dir)
+            .config(toml::toml! {
+                [hg_status]
+                disabled = false
+            })
+            .collect()
+    }
+
+    #[test]
+    fn show_nothing_on_empty_dir() -> io::Result<()> {
+        let repo_dir = tempfile::tempdir()?;
+
+        assert_eq!(None, render(repo_dir.path()));
+        repo_dir.close()
+    }
+
+    #[test]
+    #[ignore]
+    fn show_nothing_on_clean_repo() -> io::Result<()> {
+        let tempdir = fixture_repo(FixtureProvider::Hg)?;
+
+        assert_eq!(None, render(tempdir.path()));
+        tempdir.close()
+    }
+
+    #[test]
+    #[ignore]
+    fn shows_all_status() -> io::Result<()> {
+        let tempdir = fixture_repo(FixtureProvider::Hg)?;
+        let repo_dir = tempdir.path();
+        fs::write(repo_dir.join("modified.txt"), "tracked\n")?;
+        fs::write(repo_dir.join("removed.txt"), "tracked\n")?;
+        run_hg(&["add", "modified.txt", "removed.txt"], repo_dir)?;
+        run_hg(
+            &[
+                "commit",
+                "-m",
+                "tracked files",
+                "-u",
+                "fake user <fake@user>",
+            ],
+            repo_dir,
+        )?;
+
+        fs::write(repo_dir.join("modified.txt"), "modified\n")?;
+        run_hg(&["remove", "removed.txt"], repo_dir)?;
+        fs::write(repo_dir.join("added.txt"), "added\n")?;
+        run_hg(&["add", "added.txt"], repo_dir)?;
+        fs::write(repo_dir.join("unknown.txt"), "unknown\n")?;
+
+        assert_eq!(format_output("✘!+?"), render(repo_dir));
+        tempdir.close()
+    }
+
+    #[test]
+    #[ignore]
+    fn shows_counts_without_unknown() -> io::Result<()> {
+        let tempdir = fixture_repo(FixtureProvider::Hg)?;
+        let repo_dir = tempdir.path();
+        fs::write(repo_dir.join("added.txt"), "added\n")?;
+        fs::write(repo_dir.join("added2.txt"), "added\n")?;
+        run_hg(&["add", "added.txt", "added2.txt"], repo_dir)?;
+        fs::write(repo_dir.join("unknown.txt"), "unknown\n")?;
+
+        let actual = ModuleRenderer::new("hg_status")
+            .path(repo_dir)
+            .config(toml::toml! {
+                [hg_status]
+                disabled = false
+                added = "+$count"
+                unknown = ""
+            })
+            .collect();
+
+        assert_eq!(format_output("+2"), actual);
+        tempdir.close()
+    }
+
+    fn run_hg(args: &[&str], repo_dir: &Path) -> io::Result<String> {
+        let output = create_command("hg")?
+            .args(args)
+            .current_dir(repo_dir)
+            .output()?;
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
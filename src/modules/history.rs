@@ -0,0 +1,285 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs;
+use std::path::Path;
+
+use super::{Context, Module, ModuleConfig};
+
+use crate::configs::history::HistoryConfig;
+use crate::formatter::StringFormatter;
+use crate::utils::{self, render_number, render_time};
+
+/// The outcome of a command as the shell passed it on, without its command line
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+struct Outcome {
+    status: i64,
+    duration: u128,
+}
+
+/// The commands of a shell session, persisted in the starship cache directory next to the log
+/// of the session. Only the outcomes of the most recent commands are kept.
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct SessionHistory {
+    last_command_id: Option<String>,
+    commands: u64,
+    failures: u64,
+    duration: u128,
+    recent: VecDeque<Outcome>,
+}
+
+impl SessionHistory {
+    fn load(history_file: &Path) -> Self {
+        utils::read_file(history_file)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    /// Adds the outcome of the command with the given id, unless the prompt is only drawn again
+    /// for the command which was recorded last. Returns whether the history changed.
+    fn record(&mut self, command_id: &str, outcome: Outcome, size: usize) -> bool {
+        if self.last_command_id.as_deref() == Some(command_id) {
+            return false;
+        }
+
+        self.last_command_id = Some(command_id.to_string());
+
+        self.commands += 1;
+        if outcome.status != 0 {
+            self.failures += 1;
+        }
+        self.duration += outcome.duration;
+        self.recent.push_back(outcome);
+        while self.recent.len() > size {
+            self.recent.pop_front();
+        }
+        true
+    }
+
+    fn save(&self, history_file: &Path) {
+        let result = history_file
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| {
+                let contents = serde_json::to_string(self)?;
+                fs::write(history_file, contents)
+            });
+        if let Err(e) = result {
+            log::debug!("Unable to write history {:?}: {}", history_file, e);
+        }
+    }
+}
+
+/// Creates a module with the exit statuses of the recent commands of the shell session
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("history");
+    let config = HistoryConfig::try_load(module.config);
+
+    // As we default to disabled=true, we have to check here after loading our config module,
+    // before it was only checking against whatever is in the config starship.toml
+    if config.disabled {
+        return None;
+    }
+
+    let session_key = context
+        .get_env("STARSHIP_SESSION_KEY")
+        .filter(|key| !key.is_empty())?;
+    let history_file = context
+        .get_cache_dir()
+        .join(format!("session_{session_key}.history"));
+
+    let mut history = SessionHistory::load(&history_file);
+    let status = context
+        .properties
+        .status_code
+        .as_deref()
+        .and_then(|status| status.parse::<i64>().ok());
+    // The shell passes on an id which is unique to each command, like its start time, so redraws
+    // of the prompt for the same command are not recorded again
+    let command_id = context
+        .get_env("STARSHIP_COMMAND_ID")
+        .filter(|id| !id.is_empty());
+    if let (Some(command_id), Some(status), Some(duration)) =
+        (command_id, status, context.get_cmd_duration())
+    {
+        if history.record(&command_id, Outcome { status, duration }, config.size) {
+            history.save(&history_file);
+        }
+    }
+
+    if history.commands == 0 {
+        return None;
+    }
+
+    let shown = history.recent.len().saturating_sub(config.size);
+    let sparkline: String = history
+        .recent
+        .iter()
+        .skip(shown)
+        .map(|outcome| match outcome.status {
+            0 => config.success_symbol,
+            _ => config.failure_symbol,
+        })
+        .collect();
+
+    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+        formatter
+            .map_meta(|variable, _| match variable {
+                "sparkline" => Some(sparkline.as_str()),
+                _ => None,
+            })
+            .map_style(|variable| match variable {
+                "style" => Some(Ok(config.style)),
+                _ => None,
+            })
+            .map(|variable| match variable {
+                "failures" => match history.failures {
+                    0 => None,
+                    failures => Some(Ok(render_number(
+                        &failures.to_string(),
+                        context.number_separators(),
+                    ))),
+                },
+                "count" => Some(Ok(render_number(
+                    &history.commands.to_string(),
+                    context.number_separators(),
+                ))),
+                "duration" => Some(Ok(render_time(
+                    history.duration,
+                    false,
+                    context.time_units(),
+                ))),
+                _ => None,
+            })
+            .parse(None, Some(context))
+    });
+
+    module.set_segments(match parsed {
+        Ok(segments) => segments,
+        Err(error) => {
+            log::warn!("Error in module `history`:\n{}", error);
+            return None;
+        }
+    });
+
+    Some(module)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::ModuleRenderer;
+    use nu_ansi_term::{AnsiStrings, Color, Style};
+    use std::io;
+
+    fn render(cache: &Path, command_id: &str, status: i64, duration: u64) -> Option<String> {
+        ModuleRenderer::new("history")
+            .config(toml::toml! {
+                [history]
+                size = 3
+                disabled = false
+            })
+            .env("STARSHIP_SESSION_KEY", "0123456789abcdef")
+            .env("STARSHIP_CACHE", cache.to_string_lossy())
+            .env("STARSHIP_COMMAND_ID", command_id)
+            .status(status)
+            .cmd_duration(duration)
+            .collect()
+    }
+
+    #[test]
+    fn sparkline_of_recent_commands() -> io::Result<()> {
+        let cache = tempfile::tempdir()?;
+
+        render(cache.path(), "1", 0, 100);
+        render(cache.path(), "2", 101, 120);
+        render(cache.path(), "3", 0, 110);
+        let actual = render(cache.path(), "4", 0, 90);
+
+        let expected = Some(format!(
+            "{} ",
+            AnsiStrings(&[
+                Color::Red.paint("✗"),
+                Color::Green.paint("✓"),
+                Color::Green.paint("✓"),
+                Style::new().paint(" "),
+                Color::Red.paint("1 failed"),
+            ])
+        ));
+        assert_eq!(expected, actual);
+        assert!(cache
+            .path()
+            .join("session_0123456789abcdef.history")
+            .exists());
+        cache.close()
+    }
+
+    #[test]
+    fn redraw_does_not_record_again() -> io::Result<()> {
+        let cache = tempfile::tempdir()?;
+
+        render(cache.path(), "1", 0, 100);
+        render(cache.path(), "1", 0, 100);
+        let actual = render(cache.path(), "1", 0, 100);
+
+        let expected = Some(format!("{} ", Color::Green.paint("✓")));
+        assert_eq!(expected, actual);
+        cache.close()
+    }
+
+    #[test]
+    fn same_outcome_of_different_commands_is_recorded() -> io::Result<()> {
+        let cache = tempfile::tempdir()?;
+
+        render(cache.path(), "1", 0, 100);
+        let actual = render(cache.path(), "2", 0, 100);
+
+        let expected = Some(format!("{} ", Color::Green.paint("✓✓")));
+        assert_eq!(expected, actual);
+        cache.close()
+    }
+
+    #[test]
+    fn no_command_id() -> io::Result<()> {
+        let cache = tempfile::tempdir()?;
+
+        let actual = render(cache.path(), "", 1, 100);
+        assert_eq!(None, actual);
+        cache.close()
+    }
+
+    #[test]
+    fn no_session_key() {
+        let actual = ModuleRenderer::new("history")
+            .config(toml::toml! {
+                [history]
+                disabled = false
+            })
+            .status(1)
+            .cmd_duration(100)
+            .collect();
+        assert_eq!(None, actual);
+    }
+
+    #[test]
+    fn record_counts_whole_session() {
+        let mut history = SessionHistory::default();
+        for (id, status, duration) in [("1", 1, 1000), ("2", 0, 2000), ("3", 2, 500)] {
+            assert!(history.record(id, Outcome { status, duration }, 2));
+        }
+        assert!(!history.record(
+            "3",
+            Outcome {
+                status: 2,
+                duration: 500
+            },
+            2
+        ));
+
+        assert_eq!(history.commands, 3);
+        assert_eq!(history.failures, 2);
+        assert_eq!(history.duration, 3500);
+        let statuses: Vec<_> = history.recent.iter().map(|o| o.status).collect();
+        assert_eq!(statuses, [0, 2]);
+    }
+}
@@ -1,40 +1,108 @@
+use serde::{Deserialize, Serialize};
 use yaml_rust::YamlLoader;
 
 use std::borrow::Cow;
 use std::collections::HashMap;
 use std::env;
-use std::path;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
+use super::utils::cloud;
 use super::{Context, Module, ModuleConfig};
 
-use crate::configs::kubernetes::KubernetesConfig;
+use crate::configs::kubernetes::{KubernetesConfig, KubernetesContextConfig};
 use crate::formatter::StringFormatter;
 use crate::utils;
 
+const KUBECONFIG_CACHE_FILE: &str = "kubeconfig_cache.json";
+
+#[derive(Clone, Default, Deserialize, Serialize)]
 struct KubeCtxComponents {
     user: Option<String>,
     namespace: Option<String>,
     cluster: Option<String>,
 }
 
-fn get_kube_context(filename: path::PathBuf) -> Option<String> {
-    let contents = utils::read_file(filename).ok()?;
+/// The parts of a kubeconfig file used by the module
+#[derive(Clone, Default, Deserialize, Serialize)]
+struct KubeConfig {
+    current_context: Option<String>,
+    contexts: HashMap<String, KubeCtxComponents>,
+}
 
-    let yaml_docs = YamlLoader::load_from_str(&contents).ok()?;
-    if yaml_docs.is_empty() {
-        return None;
+#[derive(Deserialize, Serialize)]
+struct CachedKubeConfig {
+    modified: SystemTime,
+    len: u64,
+    config: KubeConfig,
+}
+
+/// Parsed kubeconfig files keyed by path, persisted in the starship cache directory.
+/// An entry is only reused as long as the modification time and size of its file match.
+#[derive(Default, Deserialize, Serialize)]
+struct KubeConfigCache {
+    files: HashMap<String, CachedKubeConfig>,
+    #[serde(skip)]
+    dirty: bool,
+}
+
+impl KubeConfigCache {
+    fn load(cache_file: &Path) -> Self {
+        utils::read_file(cache_file)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
     }
-    let conf = &yaml_docs[0];
 
-    let current_ctx = conf["current-context"].as_str()?;
+    fn get_or_parse(&mut self, filename: &Path) -> Option<KubeConfig> {
+        let metadata = fs::metadata(filename).ok()?;
+        let modified = metadata.modified().ok()?;
+        let len = metadata.len();
+        let key = filename.to_string_lossy().into_owned();
+
+        if let Some(cached) = self.files.get(&key) {
+            if cached.modified == modified && cached.len == len {
+                log::trace!("Using cached kubeconfig {:?}", filename);
+                return Some(cached.config.clone());
+            }
+        }
 
-    if current_ctx.is_empty() {
-        return None;
+        let config = parse_kubeconfig(filename)?;
+        self.files.insert(
+            key,
+            CachedKubeConfig {
+                modified,
+                len,
+                config: config.clone(),
+            },
+        );
+        self.dirty = true;
+        Some(config)
+    }
+
+    fn save(mut self, cache_file: &Path) {
+        if !self.dirty {
+            return;
+        }
+        // Forget about files which no longer exist
+        self.files
+            .retain(|filename, _| Path::new(filename).exists());
+
+        let result = cache_file
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| {
+                let contents = serde_json::to_string(&self)?;
+                fs::write(cache_file, contents)
+            });
+        if let Err(e) = result {
+            log::debug!("Unable to write kubeconfig cache {:?}: {}", cache_file, e);
+        }
     }
-    Some(current_ctx.to_string())
 }
 
-fn get_kube_ctx_component(filename: path::PathBuf, current_ctx: &str) -> Option<KubeCtxComponents> {
+fn parse_kubeconfig(filename: &Path) -> Option<KubeConfig> {
     let contents = utils::read_file(filename).ok()?;
 
     let yaml_docs = YamlLoader::load_from_str(&contents).ok()?;
@@ -43,67 +111,48 @@ fn get_kube_ctx_component(filename: path::PathBuf, current_ctx: &str) -> Option<
     }
     let conf = &yaml_docs[0];
 
-    let ctx_yaml = conf["contexts"].as_vec().and_then(|contexts| {
-        contexts
-            .iter()
-            .filter_map(|ctx| Some((ctx, ctx["name"].as_str()?)))
-            .find(|(_, name)| *name == current_ctx)
-    });
-
-    let ctx_components = KubeCtxComponents {
-        user: ctx_yaml
-            .and_then(|(ctx, _)| ctx["context"]["user"].as_str())
-            .and_then(|s| {
-                if s.is_empty() {
-                    return None;
-                }
-                Some(s.to_owned())
-            }),
-        namespace: ctx_yaml
-            .and_then(|(ctx, _)| ctx["context"]["namespace"].as_str())
-            .and_then(|s| {
-                if s.is_empty() {
-                    return None;
-                }
-                Some(s.to_owned())
-            }),
-        cluster: ctx_yaml
-            .and_then(|(ctx, _)| ctx["context"]["cluster"].as_str())
-            .and_then(|s| {
-                if s.is_empty() {
-                    return None;
-                }
-                Some(s.to_owned())
-            }),
-    };
-
-    Some(ctx_components)
-}
-
-fn get_kube_user<'a>(config: &'a KubernetesConfig, kube_user: &'a str) -> Cow<'a, str> {
-    return get_alias(&config.user_aliases, kube_user).unwrap_or(Cow::Borrowed(kube_user));
-}
-
-fn get_kube_context_name<'a>(config: &'a KubernetesConfig, kube_ctx: &'a str) -> Cow<'a, str> {
-    return get_alias(&config.context_aliases, kube_ctx).unwrap_or(Cow::Borrowed(kube_ctx));
+    let non_empty = |s: Option<&str>| s.filter(|s| !s.is_empty()).map(str::to_owned);
+
+    let current_context = non_empty(conf["current-context"].as_str());
+
+    let contexts = conf["contexts"]
+        .as_vec()
+        .map(|contexts| {
+            contexts
+                .iter()
+                .filter_map(|ctx| {
+                    let name = ctx["name"].as_str()?;
+                    let components = KubeCtxComponents {
+                        user: non_empty(ctx["context"]["user"].as_str()),
+                        namespace: non_empty(ctx["context"]["namespace"].as_str()),
+                        cluster: non_empty(ctx["context"]["cluster"].as_str()),
+                    };
+                    Some((name.to_owned(), components))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Some(KubeConfig {
+        current_context,
+        contexts,
+    })
 }
 
-fn get_alias<'a>(
-    aliases: &'a HashMap<String, &'a str>,
-    alias_candidate: &'a str,
-) -> Option<Cow<'a, str>> {
-    if let Some(val) = aliases.get(alias_candidate) {
-        return Some(Cow::Borrowed(val));
-    }
-
-    return aliases.iter().find_map(|(k, v)| {
-        let re = regex::Regex::new(&format!("^{k}$")).ok()?;
-        let replaced = re.replace(alias_candidate, *v);
-        match replaced {
-            Cow::Owned(replaced) => Some(Cow::Owned(replaced)),
-            _ => None,
-        }
-    });
+fn get_context_config<'a, 'b>(
+    config: &'b KubernetesConfig<'a>,
+    kube_ctx: &str,
+    components: &KubeCtxComponents,
+) -> Option<&'b KubernetesContextConfig<'a>> {
+    config.contexts.iter().find(|ctx_config| {
+        cloud::matches_pattern(ctx_config.context_pattern, Some(kube_ctx))
+            && cloud::matches_pattern(ctx_config.user_pattern, components.user.as_deref())
+            && cloud::matches_pattern(ctx_config.cluster_pattern, components.cluster.as_deref())
+            && cloud::matches_pattern(
+                ctx_config.namespace_pattern,
+                components.namespace.as_deref(),
+            )
+    })
 }
 
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
@@ -116,20 +165,13 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         return None;
     };
 
-    // If we have some config for doing the directory scan then we use it but if we don't then we
-    // assume we should treat it like the module is enabled to preserve backward compatibility.
-    let have_scan_config = !(config.detect_files.is_empty()
-        && config.detect_folders.is_empty()
-        && config.detect_extensions.is_empty());
-
-    let is_kube_project = context
-        .try_begin_scan()?
-        .set_files(&config.detect_files)
-        .set_folders(&config.detect_folders)
-        .set_extensions(&config.detect_extensions)
-        .is_match();
-
-    if have_scan_config && !is_kube_project {
+    if !cloud::is_detected(
+        context,
+        &config.detect_env_vars,
+        &config.detect_files,
+        &config.detect_folders,
+        &config.detect_extensions,
+    ) {
         return None;
     }
 
@@ -139,38 +181,84 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         .get_env("KUBECONFIG")
         .unwrap_or(default_config_file.to_str()?.to_string());
 
-    let kube_ctx = env::split_paths(&kube_cfg).find_map(get_kube_context)?;
+    let cache_file = context.get_cache_dir().join(KUBECONFIG_CACHE_FILE);
+    let mut cache = KubeConfigCache::load(&cache_file);
+    let kube_configs: Vec<KubeConfig> = env::split_paths(&kube_cfg)
+        .filter_map(|filename: PathBuf| cache.get_or_parse(&filename))
+        .collect();
+    cache.save(&cache_file);
+
+    let kube_ctx = kube_configs
+        .iter()
+        .find_map(|kube| kube.current_context.as_deref())?;
 
-    let ctx_components: Vec<KubeCtxComponents> = env::split_paths(&kube_cfg)
-        .filter_map(|filename| get_kube_ctx_component(filename, &kube_ctx))
+    // Each field is taken from the first file that defines it for the current context
+    let ctx_components: Vec<&KubeCtxComponents> = kube_configs
+        .iter()
+        .filter_map(|kube| kube.contexts.get(kube_ctx))
         .collect();
+    let components = KubeCtxComponents {
+        user: ctx_components.iter().find_map(|kube| kube.user.clone()),
+        namespace: ctx_components
+            .iter()
+            .find_map(|kube| kube.namespace.clone()),
+        cluster: ctx_components.iter().find_map(|kube| kube.cluster.clone()),
+    };
+
+    let ctx_config = get_context_config(&config, kube_ctx, &components);
+
+    let display_context = match ctx_config {
+        Some(KubernetesContextConfig {
+            context_pattern,
+            context_alias: Some(alias),
+            ..
+        }) => cloud::expand_alias(context_pattern, alias, kube_ctx),
+        _ => cloud::alias_or_value(&config.context_aliases, kube_ctx),
+    };
+
+    let display_user = components.user.as_deref().map(|user| match ctx_config {
+        Some(KubernetesContextConfig {
+            user_pattern,
+            user_alias: Some(alias),
+            ..
+        }) => cloud::expand_alias(user_pattern, alias, user),
+        _ => cloud::alias_or_value(&config.user_aliases, user),
+    });
+
+    let symbol = ctx_config
+        .and_then(|ctx_config| ctx_config.symbol)
+        .unwrap_or(config.symbol);
+    let style = match ctx_config {
+        Some(ctx_config) if ctx_config.production => {
+            ctx_config.style.unwrap_or(cloud::PRODUCTION_STYLE)
+        }
+        Some(ctx_config) => ctx_config.style.unwrap_or(config.style),
+        None => config.style,
+    };
 
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
             .map_meta(|variable, _| match variable {
-                "symbol" => Some(config.symbol),
+                "symbol" => Some(symbol),
                 _ => None,
             })
             .map_style(|variable| match variable {
-                "style" => Some(Ok(config.style)),
+                "style" => Some(Ok(style)),
                 _ => None,
             })
             .map(|variable| match variable {
-                "context" => Some(Ok(get_kube_context_name(&config, &kube_ctx))),
+                "context" => Some(Ok(display_context.clone())),
 
-                "namespace" => ctx_components
-                    .iter()
-                    .find_map(|kube| kube.namespace.as_deref())
+                "namespace" => components
+                    .namespace
+                    .as_deref()
                     .map(|namespace| Ok(Cow::Borrowed(namespace))),
 
-                "user" => ctx_components
-                    .iter()
-                    .find_map(|kube| kube.user.as_deref())
-                    .map(|user| Ok(get_kube_user(&config, user))),
+                "user" => display_user.clone().map(Ok),
 
-                "cluster" => ctx_components
-                    .iter()
-                    .find_map(|kube| kube.cluster.as_deref())
+                "cluster" => components
+                    .cluster
+                    .as_deref()
                     .map(|cluster| Ok(Cow::Borrowed(cluster))),
                 _ => None,
             })
@@ -932,4 +1020,221 @@ users: []
         assert_eq!(expected, actual);
         dir.close()
     }
+
+    fn base_test_ctx_rules(config: toml::Table, expected: &str) -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let filename = dir.path().join("config");
+
+        let mut file = File::create(&filename)?;
+        file.write_all(
+            b"
+apiVersion: v1
+clusters: []
+contexts:
+  - context:
+      cluster: prod-cluster-eu
+      user: admin@prod
+      namespace: payments
+    name: prod-eu
+current-context: prod-eu
+kind: Config
+preferences: {}
+users: []
+",
+        )?;
+        file.sync_all()?;
+
+        let actual = ModuleRenderer::new("kubernetes")
+            .path(dir.path())
+            .env("KUBECONFIG", filename.to_string_lossy().as_ref())
+            .config(config)
+            .collect();
+
+        assert_eq!(Some(expected.to_string()), actual);
+        dir.close()
+    }
+
+    #[test]
+    fn test_ctx_rule_style_and_symbol() -> io::Result<()> {
+        base_test_ctx_rules(
+            toml::toml! {
+                [kubernetes]
+                disabled = false
+                format = "[$symbol$context]($style)"
+                [[kubernetes.contexts]]
+                context_pattern = "dev-.*"
+                style = "green"
+                [[kubernetes.contexts]]
+                context_pattern = "prod-.*"
+                style = "red bold"
+                symbol = "! "
+            },
+            &Color::Red.bold().paint("! prod-eu").to_string(),
+        )
+    }
+
+    #[test]
+    fn test_ctx_rule_alias_captures() -> io::Result<()> {
+        base_test_ctx_rules(
+            toml::toml! {
+                [kubernetes]
+                disabled = false
+                format = "$context $user"
+                [[kubernetes.contexts]]
+                context_pattern = "prod-(?P<region>.*)"
+                context_alias = "production ($region)"
+                user_pattern = "(?P<name>.*)@prod"
+                user_alias = "$name"
+            },
+            "production (eu) admin",
+        )
+    }
+
+    #[test]
+    fn test_ctx_rule_literal_alias_without_pattern() -> io::Result<()> {
+        base_test_ctx_rules(
+            toml::toml! {
+                [kubernetes]
+                disabled = false
+                format = "$context"
+                [[kubernetes.contexts]]
+                namespace_pattern = "payments"
+                context_alias = "$payments"
+            },
+            "$payments",
+        )
+    }
+
+    #[test]
+    fn test_ctx_rule_all_patterns_must_match() -> io::Result<()> {
+        base_test_ctx_rules(
+            toml::toml! {
+                [kubernetes]
+                disabled = false
+                format = "$symbol$context"
+                [[kubernetes.contexts]]
+                context_pattern = "prod-.*"
+                namespace_pattern = "kube-system"
+                symbol = "system "
+                [[kubernetes.contexts]]
+                cluster_pattern = "prod-cluster-.*"
+                namespace_pattern = "pay.*"
+                symbol = "payments "
+            },
+            "payments prod-eu",
+        )
+    }
+
+    #[test]
+    fn test_ctx_rule_takes_precedence_over_aliases() -> io::Result<()> {
+        base_test_ctx_rules(
+            toml::toml! {
+                [kubernetes]
+                disabled = false
+                format = "$context"
+                [kubernetes.context_aliases]
+                "prod-eu" = "from aliases"
+                [[kubernetes.contexts]]
+                context_pattern = "prod-eu"
+                context_alias = "from rule"
+            },
+            "from rule",
+        )
+    }
+
+    #[test]
+    fn test_ctx_rule_broken_regex() -> io::Result<()> {
+        base_test_ctx_rules(
+            toml::toml! {
+                [kubernetes]
+                disabled = false
+                format = "$symbol$context"
+                [[kubernetes.contexts]]
+                context_pattern = "prod-[.*"
+                symbol = "broken "
+            },
+            "☸ prod-eu",
+        )
+    }
+
+    #[test]
+    fn test_kubeconfig_cache() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let cache_dir = tempfile::tempdir()?;
+
+        let filename = dir.path().join("config");
+        let write_config = |ctx_name: &str| -> io::Result<()> {
+            let mut file = File::create(&filename)?;
+            file.write_all(
+                format!(
+                    "
+apiVersion: v1
+clusters: []
+contexts: []
+current-context: {ctx_name}
+kind: Config
+preferences: {{}}
+users: []
+"
+                )
+                .as_bytes(),
+            )?;
+            file.sync_all()
+        };
+        let render = || {
+            ModuleRenderer::new("kubernetes")
+                .path(dir.path())
+                .env("KUBECONFIG", filename.to_string_lossy().as_ref())
+                .env(
+                    "STARSHIP_CACHE",
+                    cache_dir.path().to_string_lossy().as_ref(),
+                )
+                .config(toml::toml! {
+                    [kubernetes]
+                    format = "$context"
+                    disabled = false
+                })
+                .collect()
+        };
+
+        write_config("first_context")?;
+        assert_eq!(Some("first_context".to_string()), render());
+
+        let cache_file = cache_dir.path().join("kubeconfig_cache.json");
+        let cache = std::fs::read_to_string(&cache_file)?;
+        assert!(cache.contains("first_context"));
+
+        // A change to the kubeconfig invalidates the cached entry
+        write_config("second_context_with_a_longer_name")?;
+        assert_eq!(
+            Some("second_context_with_a_longer_name".to_string()),
+            render()
+        );
+
+        // A corrupted cache is ignored
+        std::fs::write(&cache_file, "not json")?;
+        assert_eq!(
+            Some("second_context_with_a_longer_name".to_string()),
+            render()
+        );
+
+        dir.close()?;
+        cache_dir.close()
+    }
+
+    #[test]
+    fn test_ctx_rule_production() -> io::Result<()> {
+        base_test_ctx_rules(
+            toml::toml! {
+                [kubernetes]
+                disabled = false
+                format = "[$symbol$context]($style)"
+                [[kubernetes.contexts]]
+                context_pattern = "prod-.*"
+                production = true
+            },
+            &Color::Red.bold().paint("☸ prod-eu").to_string(),
+        )
+    }
 }
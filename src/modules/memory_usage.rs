@@ -8,8 +8,8 @@ use super::{Context, Module, ModuleConfig};
 use crate::configs::memory_usage::MemoryConfig;
 use crate::formatter::StringFormatter;
 
-// Display a `ByteSize` in a human readable format.
-fn display_bs(bs: ByteSize) -> String {
+// Display a `ByteSize` in a human readable format, with the byte unit of the locale.
+fn display_bs(bs: ByteSize, byte_unit: &str) -> String {
     let mut display_bytes = bs.to_string_as(true);
     let mut keep = true;
     // Skip decimals and the space before the byte unit.
@@ -24,6 +24,10 @@ fn display_bs(bs: ByteSize) -> String {
         }
         _ => keep,
     });
+    if display_bytes.ends_with('B') {
+        display_bytes.pop();
+        display_bytes.push_str(byte_unit);
+    }
     display_bytes
 }
 
@@ -33,11 +37,11 @@ fn pct(total: ByteSize, free: ByteSize) -> f64 {
 }
 
 // Print usage string used/total
-fn format_usage_total(total: ByteSize, free: ByteSize) -> String {
+fn format_usage_total(total: ByteSize, free: ByteSize, byte_unit: &str) -> String {
     format!(
         "{}/{}",
-        display_bs(saturating_sub_bytes(total, free)),
-        display_bs(total)
+        display_bs(saturating_sub_bytes(total, free), byte_unit),
+        display_bs(total, byte_unit)
     )
 }
 
@@ -78,6 +82,8 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     };
 
     let used_pct = pct(memory.total, memory.free);
+    let byte_unit = context.message("unit_byte");
+    let percent = context.message("unit_percent");
 
     if (used_pct.round() as i64) < config.threshold {
         return None;
@@ -94,14 +100,15 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map(|variable| match variable {
-                "ram" => Some(Ok(format_usage_total(memory.total, memory.free))),
-                "ram_pct" => Some(Ok(format!("{used_pct:.0}%"))),
+                "ram" => Some(Ok(format_usage_total(memory.total, memory.free, byte_unit))),
+                "ram_pct" => Some(Ok(format!("{used_pct:.0}{percent}"))),
                 "swap" => Some(Ok(format_usage_total(
                     swap.as_ref()?.total,
                     swap.as_ref()?.free,
+                    byte_unit,
                 ))),
                 "swap_pct" => Some(Ok(format!(
-                    "{:.0}%",
+                    "{:.0}{percent}",
                     pct(swap.as_ref()?.total, swap.as_ref()?.free)
                 ))),
                 _ => None,
@@ -129,20 +136,29 @@ mod test {
     #[test]
     fn test_format_usage_total() {
         assert_eq!(
-            format_usage_total(ByteSize(1024 * 1024 * 1024), ByteSize(1024 * 1024 * 1024)),
+            format_usage_total(
+                ByteSize(1024 * 1024 * 1024),
+                ByteSize(1024 * 1024 * 1024),
+                "B"
+            ),
             "0B/1GiB"
         );
         assert_eq!(
             format_usage_total(
                 ByteSize(1024 * 1024 * 1024),
-                ByteSize(1024 * 1024 * 1024 / 2)
+                ByteSize(1024 * 1024 * 1024 / 2),
+                "B"
             ),
             "512MiB/1GiB"
         );
         assert_eq!(
-            format_usage_total(ByteSize(1024 * 1024 * 1024), ByteSize(0)),
+            format_usage_total(ByteSize(1024 * 1024 * 1024), ByteSize(0), "B"),
             "1GiB/1GiB"
         );
+        assert_eq!(
+            format_usage_total(ByteSize(1024 * 1024 * 1024), ByteSize(0), "o"),
+            "1Gio/1Gio"
+        );
     }
 
     #[test]
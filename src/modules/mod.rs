@@ -28,6 +28,7 @@ mod fossil_branch;
 mod gcloud;
 mod git_branch;
 mod git_commit;
+mod git_context;
 mod git_metrics;
 mod git_state;
 mod git_status;
@@ -38,6 +39,10 @@ mod haskell;
 mod haxe;
 mod helm;
 mod hg_branch;
+mod hg_commit;
+mod hg_state;
+mod hg_status;
+mod history;
 mod hostname;
 mod java;
 mod jobs;
@@ -82,6 +87,7 @@ mod time;
 mod username;
 mod utils;
 mod vagrant;
+mod vcs;
 mod vcsh;
 mod vlang;
 mod zig;
@@ -132,6 +138,7 @@ pub fn handle<'a>(module: &str, context: &'a Context) -> Option<Module<'a>> {
             "gcloud" => gcloud::module(context),
             "git_branch" => git_branch::module(context),
             "git_commit" => git_commit::module(context),
+            "git_context" => git_context::module(context),
             "git_metrics" => git_metrics::module(context),
             "git_state" => git_state::module(context),
             "git_status" => git_status::module(context),
@@ -142,6 +149,10 @@ pub fn handle<'a>(module: &str, context: &'a Context) -> Option<Module<'a>> {
             "haxe" => haxe::module(context),
             "helm" => helm::module(context),
             "hg_branch" => hg_branch::module(context),
+            "hg_commit" => hg_commit::module(context),
+            "hg_state" => hg_state::module(context),
+            "hg_status" => hg_status::module(context),
+            "history" => history::module(context),
             "hostname" => hostname::module(context),
             "java" => java::module(context),
             "jobs" => jobs::module(context),
@@ -187,6 +198,7 @@ pub fn handle<'a>(module: &str, context: &'a Context) -> Option<Module<'a>> {
             "username" => username::module(context),
             "vlang" => vlang::module(context),
             "vagrant" => vagrant::module(context),
+            "vcs" => vcs::module(context),
             "vcsh" => vcsh::module(context),
             "zig" => zig::module(context),
             env if env.starts_with("env_var.") => {
@@ -247,6 +259,9 @@ pub fn description(module: &str) -> &'static str {
         "gcloud" => "The current GCP client configuration",
         "git_branch" => "The active branch of the repo in your current directory",
         "git_commit" => "The active commit (and tag if any) of the repo in your current directory",
+        "git_context" => {
+            "The linked worktree, submodule or checkout kind of the repo in your current directory"
+        }
         "git_metrics" => "The currently added/deleted lines in your repo",
         "git_state" => "The current git operation, and it's progress",
         "git_status" => "Symbol representing the state of the repo",
@@ -257,6 +272,10 @@ pub fn description(module: &str) -> &'static str {
         "haxe" => "The currently installed version of Haxe",
         "helm" => "The currently installed version of Helm",
         "hg_branch" => "The active branch and topic of the repo in your current directory",
+        "hg_commit" => "The active changeset of the repo in your current directory",
+        "hg_state" => "The current hg operation, and it's progress",
+        "hg_status" => "Symbol representing the state of the repo",
+        "history" => "The exit statuses of the recent commands of the session",
         "hostname" => "The system hostname",
         "java" => "The currently installed version of Java",
         "jobs" => "The current number of jobs running",
@@ -302,6 +321,7 @@ pub fn description(module: &str) -> &'static str {
         "time" => "The current local time",
         "username" => "The active user's username",
         "vagrant" => "The currently installed version of Vagrant",
+        "vcs" => "The branch and state of the repo in the current directory, for any supported VCS",
         "vcsh" => "The currently active VCSH repository",
         "vlang" => "The currently installed version of V",
         "zig" => "The currently installed version of Zig",
@@ -1,5 +1,6 @@
 use yaml_rust::YamlLoader;
 
+use super::utils::cloud::{self, CloudEnvironment};
 use super::{Context, Module, ModuleConfig};
 
 use crate::configs::openstack::OspConfig;
@@ -50,23 +51,44 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("openstack");
     let config: OspConfig = OspConfig::try_load(module.config);
 
+    if !cloud::is_detected(
+        context,
+        &config.detect_env_vars,
+        &config.detect_files,
+        &config.detect_folders,
+        &config.detect_extensions,
+    ) {
+        return None;
+    }
+
     let (osp_cloud, osp_project) = get_osp_cloud_and_project(context);
 
     osp_cloud.as_ref()?;
 
+    let environment = CloudEnvironment::resolve(
+        &config.environments,
+        &config.cloud_aliases,
+        osp_cloud.as_deref(),
+        config.symbol,
+        config.style,
+    );
+    let osp_project = osp_project
+        .as_deref()
+        .map(|project| cloud::alias_or_value(&config.project_aliases, project));
+
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
             .map_meta(|variable, _| match variable {
-                "symbol" => Some(config.symbol),
+                "symbol" => Some(environment.symbol),
                 _ => None,
             })
             .map_style(|variable| match variable {
-                "style" => Some(Ok(config.style)),
+                "style" => Some(Ok(environment.style)),
                 _ => None,
             })
             .map(|variable| match variable {
-                "cloud" => osp_cloud.as_ref().map(Ok),
-                "project" => osp_project.as_ref().map(Ok),
+                "cloud" => environment.name.as_deref().map(Ok),
+                "project" => osp_project.as_deref().map(Ok),
                 _ => None,
             })
             .parse(None, Some(context))
@@ -164,4 +186,43 @@ dummy_yaml
         assert_eq!(actual, expected);
         dir.close()
     }
+
+    #[test]
+    fn cloud_with_alias_and_environment() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let actual = ModuleRenderer::new("openstack")
+            .env("PWD", dir.path().to_str().unwrap())
+            .env("OS_CLOUD", "corp-prod")
+            .env("OS_PROJECT_NAME", "team-infrastructure")
+            .config(toml::toml! {
+                [openstack]
+                [openstack.project_aliases]
+                "team-(?P<name>.*)" = "$name"
+                [[openstack.environments]]
+                pattern = ".*-prod"
+                production = true
+            })
+            .collect();
+        let expected = Some(format!(
+            "on {} ",
+            Color::Red.bold().paint("☁️  corp-prod(infrastructure)")
+        ));
+
+        assert_eq!(actual, expected);
+        dir.close()
+    }
+
+    #[test]
+    fn cloud_not_shown_because_of_detect_env_vars() {
+        let actual = ModuleRenderer::new("openstack")
+            .env("OS_CLOUD", "corp")
+            .env("OS_PROJECT_NAME", "testproject")
+            .config(toml::toml! {
+                [openstack]
+                detect_env_vars = ["OS_AUTH_URL"]
+            })
+            .collect();
+
+        assert_eq!(actual, None);
+    }
 }
@@ -1,4 +1,5 @@
 use super::utils::truncate::truncate_text;
+use super::utils::vcs::pijul::get_current_channel;
 use super::{Context, Module, ModuleConfig};
 
 use crate::configs::pijul_channel::PijulConfig;
@@ -25,7 +26,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         return None;
     };
 
-    let channel_name = get_pijul_current_channel(context)?;
+    let channel_name = get_current_channel(context)?;
 
     let truncated_text = truncate_text(
         &channel_name,
@@ -61,15 +62,6 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
-fn get_pijul_current_channel(ctx: &Context) -> Option<String> {
-    let output = ctx.exec_cmd("pijul", &["channel"])?.stdout;
-
-    output
-        .lines()
-        .find_map(|l| l.strip_prefix("* "))
-        .map(str::to_owned)
-}
-
 #[cfg(test)]
 mod tests {
     use nu_ansi_term::{Color, Style};
@@ -116,7 +116,10 @@ fn format_exit_code<'a>(
 
     let hex_status = format!("0x{exit_code_int:X}");
 
-    let common_meaning = status_common_meaning(exit_code_int);
+    let common_meaning = status_common_meaning(exit_code_int).map(|meaning| match meaning {
+        "" => meaning,
+        key => context.message(key),
+    });
 
     let raw_signal_number = match config.recognize_signal_code {
         true => status_to_signal(exit_code_int),
@@ -180,6 +183,7 @@ fn format_exit_code<'a>(
     })
 }
 
+/// The key of the message of the common meaning of an exit code
 fn status_common_meaning(ex: ExitCode) -> Option<&'static str> {
     // Over 128 are Signal exit code
     if ex > 128 {
@@ -187,10 +191,10 @@ fn status_common_meaning(ex: ExitCode) -> Option<&'static str> {
     }
     match ex {
         0 => Some(""), // SUCCESS can be defined by $success_symbol if the user wishes too.
-        1 => Some("ERROR"),
-        2 => Some("USAGE"),
-        126 => Some("NOPERM"),
-        127 => Some("NOTFOUND"),
+        1 => Some("status_error"),
+        2 => Some("status_usage"),
+        126 => Some("status_noperm"),
+        127 => Some("status_notfound"),
         _ => None,
     }
 }
@@ -435,6 +439,28 @@ mod tests {
         }
     }
 
+    #[test]
+    fn exit_code_name_localized() {
+        let exit_values = [1, 127, 130];
+        let exit_values_name = [Some("FEHLER"), Some("NICHT DA"), Some("INT")];
+
+        for (status, name) in exit_values.iter().zip(&exit_values_name) {
+            let expected = name.map(std::string::ToString::to_string);
+            let actual = ModuleRenderer::new("status")
+                .config(toml::toml! {
+                    [status]
+                    format = "$common_meaning$signal_name"
+                    disabled = false
+                    [locales.de]
+                    status_notfound = "NICHT DA"
+                })
+                .env("LANG", "de_DE.UTF-8")
+                .status(*status)
+                .collect();
+            assert_eq!(expected, actual);
+        }
+    }
+
     #[test]
     fn maybe_exit_code_number() {
         let exit_values = [1, 2, 126, 127, 130, 101, 6, -3];
@@ -6,9 +6,9 @@ use crate::utils;
 
 use crate::formatter::VersionFormatter;
 use std::io;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-/// Creates a module with the current Terraform version and workspace
+/// Creates a module with the current Terraform (or OpenTofu) version, workspace and backend
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("terraform");
     let config: TerraformConfig = TerraformConfig::try_load(module.config);
@@ -24,6 +24,15 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         return None;
     }
 
+    let datadir = get_terraform_datadir(context);
+    let backend = get_terraform_backend(&datadir);
+
+    // Prefer OpenTofu in projects which pin an OpenTofu version
+    let mut commands = config.commands.clone();
+    if context.current_dir.join(".opentofu-version").is_file() {
+        commands.sort_by_key(|command| command.first() != Some(&"tofu"));
+    }
+
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
             .map_meta(|variable, _| match variable {
@@ -37,7 +46,10 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
             .map(|variable| match variable {
                 "version" => {
                     let terraform_version = parse_terraform_version(
-                        context.exec_cmd("terraform", &["version"])?.stdout.as_str(),
+                        context
+                            .exec_cmds_return_first(commands.clone())?
+                            .stdout
+                            .as_str(),
                     )?;
                     VersionFormatter::format_module_version(
                         module.get_name(),
@@ -46,7 +58,11 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                     )
                 }
                 .map(Ok),
-                "workspace" => get_terraform_workspace(context).map(Ok),
+                "workspace" => get_terraform_workspace(context, &datadir).map(Ok),
+                "backend" => backend.as_ref().map(|(kind, _)| Ok(kind.clone())),
+                "backend_key" => backend.as_ref().and_then(|(_, key)| key.clone()).map(Ok),
+                "init_needed" => is_init_needed(context, &datadir)
+                    .then(|| Ok(config.init_needed_symbol.to_string())),
                 _ => None,
             })
             .parse(None, Some(context))
@@ -63,19 +79,22 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
+// Data directory containing current workspace can be overwritten by an env var
+fn get_terraform_datadir(context: &Context) -> PathBuf {
+    match context.get_env("TF_DATA_DIR") {
+        Some(s) => PathBuf::from(s),
+        None => context.current_dir.join(".terraform"),
+    }
+}
+
 // Determines the currently selected workspace (see https://github.com/hashicorp/terraform/blob/master/command/meta.go for the original implementation)
-fn get_terraform_workspace(context: &Context) -> Option<String> {
+fn get_terraform_workspace(context: &Context, datadir: &Path) -> Option<String> {
     // Workspace can be explicitly overwritten by an env var
     let workspace_override = context.get_env("TF_WORKSPACE");
     if workspace_override.is_some() {
         return workspace_override;
     }
 
-    // Data directory containing current workspace can be overwritten by an env var
-    let datadir = match context.get_env("TF_DATA_DIR") {
-        Some(s) => PathBuf::from(s),
-        None => context.current_dir.join(".terraform"),
-    };
     match utils::read_file(datadir.join("environment")) {
         Err(ref e) if e.kind() == io::ErrorKind::NotFound => Some("default".to_string()),
         Ok(s) => Some(s),
@@ -83,14 +102,64 @@ fn get_terraform_workspace(context: &Context) -> Option<String> {
     }
 }
 
+// `terraform init` records the configured backend in the data directory, e.g.
+// {"version": 3, "backend": {"type": "s3", "config": {"bucket": "...", "key": "..."}}}
+fn get_terraform_backend(datadir: &Path) -> Option<(String, Option<String>)> {
+    let contents = utils::read_file(datadir.join("terraform.tfstate")).ok()?;
+    let state: serde_json::Value = serde_json::from_str(&contents).ok()?;
+    let backend = &state["backend"];
+    let kind = backend["type"].as_str()?.to_string();
+    // Backends use different names for the location of the state
+    let key = ["key", "prefix", "path"]
+        .iter()
+        .find_map(|name| backend["config"][name].as_str())
+        .map(ToString::to_string);
+    Some((kind, key))
+}
+
+// Lists the providers selected in the dependency lock file, e.g.
+// provider "registry.terraform.io/hashicorp/aws" {
+//   version = "5.0.0"
+fn parse_lock_file(contents: &str) -> Vec<(&str, &str)> {
+    let mut providers = Vec::new();
+    let mut source = None;
+    for line in contents.lines().map(str::trim) {
+        if let Some(rest) = line.strip_prefix("provider ") {
+            source = rest.split('"').nth(1);
+        } else if let (Some(name), Some(rest)) = (source, line.strip_prefix("version")) {
+            if let Some(version) = rest.trim_start().strip_prefix('=') {
+                providers.push((name, version.trim().trim_matches('"')));
+                source = None;
+            }
+        }
+    }
+    providers
+}
+
+// `terraform init` has to be run again if the dependency lock file selects providers which
+// are not installed in the data directory, e.g. after pulling changes to the lock file.
+// Comparing modification times is not enough, as `init` itself rewrites the lock file after
+// installing the providers.
+fn is_init_needed(context: &Context, datadir: &Path) -> bool {
+    let Ok(contents) = utils::read_file(context.current_dir.join(".terraform.lock.hcl")) else {
+        return false;
+    };
+    let providers_dir = datadir.join("providers");
+    parse_lock_file(&contents)
+        .iter()
+        .any(|(source, version)| !providers_dir.join(source).join(version).is_dir())
+}
+
 fn parse_terraform_version(version: &str) -> Option<String> {
     // `terraform version` output looks like this
     // Terraform v0.12.14
     // With potential extra output if it detects you are not running the latest version
+    // `tofu version` prints `OpenTofu v1.6.0` instead
     let version = version
         .lines()
         .next()?
         .trim_start_matches("Terraform ")
+        .trim_start_matches("OpenTofu ")
         .trim()
         .trim_start_matches('v');
 
@@ -289,4 +358,179 @@ is 0.12.14. You can update by downloading from www.terraform.io/downloads.html
         assert_eq!(expected, actual);
         dir.close()
     }
+
+    #[test]
+    fn test_parse_opentofu_version() {
+        let input = "OpenTofu v1.6.0\non linux_amd64";
+        assert_eq!(parse_terraform_version(input), Some("1.6.0".to_string()));
+    }
+
+    #[test]
+    fn test_parse_lock_file() {
+        let input = r#"
+# This file is maintained automatically by "terraform init".
+# Manual edits may be lost in future updates.
+
+provider "registry.terraform.io/hashicorp/aws" {
+  version     = "5.0.0"
+  constraints = "~> 5.0"
+  hashes = [
+    "h1:dummy=",
+  ]
+}
+
+provider "registry.opentofu.org/hashicorp/random" {
+  version = "3.5.1"
+}
+"#;
+        assert_eq!(
+            parse_lock_file(input),
+            vec![
+                ("registry.terraform.io/hashicorp/aws", "5.0.0"),
+                ("registry.opentofu.org/hashicorp/random", "3.5.1")
+            ]
+        );
+    }
+
+    #[test]
+    fn folder_with_backend() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let tf_dir = dir.path().join(".terraform");
+        fs::create_dir(&tf_dir)?;
+        fs::write(
+            tf_dir.join("terraform.tfstate"),
+            r#"{
+    "version": 3,
+    "serial": 1,
+    "backend": {
+        "type": "s3",
+        "config": {
+            "bucket": "acme-terraform-state",
+            "key": "network/terraform.tfstate",
+            "region": "eu-west-1"
+        },
+        "hash": 1234
+    }
+}"#,
+        )?;
+
+        let actual = ModuleRenderer::new("terraform")
+            .path(dir.path())
+            .config(toml::toml! {
+                [terraform]
+                format = "$workspace $backend:$backend_key"
+            })
+            .collect();
+        let expected = Some("default s3:network/terraform.tfstate".to_string());
+
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_local_backend_without_key() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let tf_dir = dir.path().join(".terraform");
+        fs::create_dir(&tf_dir)?;
+        fs::write(
+            tf_dir.join("terraform.tfstate"),
+            r#"{"version": 3, "backend": {"type": "local", "config": {"path": null}}}"#,
+        )?;
+
+        let actual = ModuleRenderer::new("terraform")
+            .path(dir.path())
+            .config(toml::toml! {
+                [terraform]
+                format = "$backend(:$backend_key)"
+            })
+            .collect();
+        let expected = Some("local".to_string());
+
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_providers_not_installed() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let tf_dir = dir.path().join(".terraform");
+        fs::create_dir(&tf_dir)?;
+        fs::write(
+            dir.path().join(".terraform.lock.hcl"),
+            r#"provider "registry.terraform.io/hashicorp/aws" {
+  version = "5.0.0"
+}
+"#,
+        )?;
+        let installed = tf_dir.join("providers/registry.terraform.io/hashicorp/aws/4.67.0");
+        fs::create_dir_all(installed)?;
+
+        let actual = ModuleRenderer::new("terraform").path(dir.path()).collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Fixed(105).bold().paint("💠 default ⚠")
+        ));
+        assert_eq!(expected, actual);
+
+        let installed = tf_dir.join("providers/registry.terraform.io/hashicorp/aws/5.0.0");
+        fs::create_dir_all(installed)?;
+
+        let actual = ModuleRenderer::new("terraform").path(dir.path()).collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Fixed(105).bold().paint("💠 default")
+        ));
+        assert_eq!(expected, actual);
+
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_opentofu_version_pin() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join(".opentofu-version"))?.sync_all()?;
+
+        let actual = ModuleRenderer::new("terraform")
+            .path(dir.path())
+            .cmd(
+                "tofu version",
+                Some(crate::utils::CommandOutput {
+                    stdout: String::from("OpenTofu v1.6.0\non linux_amd64\n"),
+                    stderr: String::default(),
+                }),
+            )
+            .config(toml::toml! {
+                [terraform]
+                format = "via [$symbol$version]($style) "
+            })
+            .collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Fixed(105).bold().paint("💠 v1.6.0")
+        ));
+
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_terraform_version_pin() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join(".terraform-version"))?.sync_all()?;
+
+        let actual = ModuleRenderer::new("terraform")
+            .path(dir.path())
+            .config(toml::toml! {
+                [terraform]
+                format = "via [$symbol$version]($style) "
+            })
+            .collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Fixed(105).bold().paint("💠 v0.12.14")
+        ));
+
+        assert_eq!(expected, actual);
+        dir.close()
+    }
 }
@@ -0,0 +1,229 @@
+//! Helpers shared by the cloud provider modules: `aws`, `azure`, `gcloud`, `kubernetes` and
+//! `openstack`.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use regex::Regex;
+
+use crate::configs::cloud::CloudEnvironmentConfig;
+use crate::context::Context;
+
+/// The style of environments flagged as `production` which don't set a style of their own
+pub const PRODUCTION_STYLE: &str = "bold red";
+
+/// Compiles a pattern which has to match an entire value
+pub fn compile_pattern(pattern: &str) -> Option<Regex> {
+    Regex::new(&format!("^{pattern}$"))
+        .map_err(|e| log::warn!("Invalid pattern `{}`: {}", pattern, e))
+        .ok()
+}
+
+/// An empty pattern matches anything, otherwise the pattern has to match the entire value
+pub fn matches_pattern(pattern: &str, value: Option<&str>) -> bool {
+    if pattern.is_empty() {
+        return true;
+    }
+    match (compile_pattern(pattern), value) {
+        (Some(re), Some(value)) => re.is_match(value),
+        _ => false,
+    }
+}
+
+/// Expands capture groups of `pattern` (e.g. `$cluster`) in `alias`
+pub fn expand_alias<'a>(pattern: &str, alias: &'a str, value: &str) -> Cow<'a, str> {
+    if pattern.is_empty() {
+        return Cow::Borrowed(alias);
+    }
+    match compile_pattern(pattern) {
+        Some(re) => Cow::Owned(re.replace(value, alias).into_owned()),
+        None => Cow::Borrowed(alias),
+    }
+}
+
+/// Looks up the alias of `value` in an alias table.
+///
+/// Literal keys take precedence. Otherwise the keys are used as regular expressions which
+/// have to match the entire value, and capture groups can be referenced in the alias.
+pub fn get_alias<'a>(aliases: &'a HashMap<String, &'a str>, value: &str) -> Option<Cow<'a, str>> {
+    if let Some(alias) = aliases.get(value) {
+        return Some(Cow::Borrowed(alias));
+    }
+
+    aliases.iter().find_map(|(pattern, alias)| {
+        let re = Regex::new(&format!("^{pattern}$")).ok()?;
+        match re.replace(value, *alias) {
+            Cow::Owned(replaced) => Some(Cow::Owned(replaced)),
+            Cow::Borrowed(_) => None,
+        }
+    })
+}
+
+/// Returns the alias of `value`, or `value` itself if there is none
+pub fn alias_or_value<'a>(aliases: &'a HashMap<String, &'a str>, value: &'a str) -> Cow<'a, str> {
+    get_alias(aliases, value).unwrap_or(Cow::Borrowed(value))
+}
+
+/// Checks the `detect_*` options of a cloud module.
+///
+/// If `detect_env_vars` is not empty, one of the variables has to be set. If any of the
+/// directory scan options is not empty, the current directory has to match them.
+pub fn is_detected<'a>(
+    context: &'a Context,
+    detect_env_vars: &'a [&'a str],
+    detect_files: &'a [&'a str],
+    detect_folders: &'a [&'a str],
+    detect_extensions: &'a [&'a str],
+) -> bool {
+    if !context.detect_env_vars(detect_env_vars) {
+        return false;
+    }
+
+    if detect_files.is_empty() && detect_folders.is_empty() && detect_extensions.is_empty() {
+        return true;
+    }
+
+    context.try_begin_scan().map_or(false, |scan| {
+        scan.set_files(detect_files)
+            .set_folders(detect_folders)
+            .set_extensions(detect_extensions)
+            .is_match()
+    })
+}
+
+/// The symbol, style and displayed name of a cloud module in its current environment
+pub struct CloudEnvironment<'a> {
+    pub name: Option<Cow<'a, str>>,
+    pub symbol: &'a str,
+    pub style: &'a str,
+}
+
+impl<'a> CloudEnvironment<'a> {
+    /// Applies the first of `environments` whose pattern matches `name`.
+    /// Without a matching environment `aliases` are used for the displayed name.
+    pub fn resolve(
+        environments: &'a [CloudEnvironmentConfig<'a>],
+        aliases: &'a HashMap<String, &'a str>,
+        name: Option<&'a str>,
+        symbol: &'a str,
+        style: &'a str,
+    ) -> Self {
+        let environment = environments
+            .iter()
+            .find(|environment| matches_pattern(environment.pattern, name));
+
+        let Some(environment) = environment else {
+            return CloudEnvironment {
+                name: name.map(|name| alias_or_value(aliases, name)),
+                symbol,
+                style,
+            };
+        };
+
+        let name = name.map(|name| match environment.alias {
+            Some(alias) => expand_alias(environment.pattern, alias, name),
+            None => alias_or_value(aliases, name),
+        });
+        let default_style = if environment.production {
+            PRODUCTION_STYLE
+        } else {
+            style
+        };
+
+        CloudEnvironment {
+            name,
+            symbol: environment.symbol.unwrap_or(symbol),
+            style: environment.style.unwrap_or(default_style),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alias_literal_has_precedence() {
+        let aliases = HashMap::from([
+            (String::from(".*"), "regex"),
+            (String::from("us-east-1"), "va"),
+        ]);
+        assert_eq!(alias_or_value(&aliases, "us-east-1"), "va");
+        assert_eq!(alias_or_value(&aliases, "eu-west-1"), "regex");
+    }
+
+    #[test]
+    fn alias_regex_replace() {
+        let aliases = HashMap::from([(String::from("prod-(?P<region>.*)"), "prod ($region)")]);
+        assert_eq!(alias_or_value(&aliases, "prod-eu"), "prod (eu)");
+        assert_eq!(alias_or_value(&aliases, "dev-eu"), "dev-eu");
+    }
+
+    #[test]
+    fn alias_broken_regex() {
+        let aliases = HashMap::from([(String::from("input[.*"), "broken")]);
+        assert_eq!(alias_or_value(&aliases, "input"), "input");
+    }
+
+    #[test]
+    fn pattern_must_match_entire_value() {
+        assert!(matches_pattern("", None));
+        assert!(matches_pattern("prod", Some("prod")));
+        assert!(!matches_pattern("prod", Some("preprod")));
+        assert!(!matches_pattern("prod", None));
+        assert!(!matches_pattern("prod[", Some("prod[")));
+    }
+
+    #[test]
+    fn environment_without_match_uses_defaults() {
+        let environments = vec![CloudEnvironmentConfig {
+            pattern: "prod",
+            style: Some("red"),
+            ..Default::default()
+        }];
+        let aliases = HashMap::from([(String::from("dev"), "development")]);
+        let env = CloudEnvironment::resolve(&environments, &aliases, Some("dev"), "c ", "blue");
+
+        assert_eq!(env.name.as_deref(), Some("development"));
+        assert_eq!(env.symbol, "c ");
+        assert_eq!(env.style, "blue");
+    }
+
+    #[test]
+    fn environment_production_defaults_to_danger_style() {
+        let environments = vec![
+            CloudEnvironmentConfig {
+                pattern: "prod-(?P<region>.*)",
+                alias: Some("PROD $region"),
+                production: true,
+                ..Default::default()
+            },
+            CloudEnvironmentConfig {
+                pattern: ".*",
+                style: Some("green"),
+                ..Default::default()
+            },
+        ];
+        let aliases = HashMap::new();
+        let env = CloudEnvironment::resolve(&environments, &aliases, Some("prod-eu"), "c ", "blue");
+
+        assert_eq!(env.name.as_deref(), Some("PROD eu"));
+        assert_eq!(env.style, PRODUCTION_STYLE);
+    }
+
+    #[test]
+    fn environment_style_overrides_production_style() {
+        let environments = vec![CloudEnvironmentConfig {
+            production: true,
+            style: Some("bold purple"),
+            symbol: Some("! "),
+            ..Default::default()
+        }];
+        let aliases = HashMap::new();
+        let env = CloudEnvironment::resolve(&environments, &aliases, None, "c ", "blue");
+
+        assert_eq!(env.name, None);
+        assert_eq!(env.symbol, "! ");
+        assert_eq!(env.style, "bold purple");
+    }
+}
@@ -0,0 +1,186 @@
+//! Parses git remote URLs to tell which forge hosts a repository, used by `git_branch` and
+//! `git_commit`.
+
+use indexmap::IndexMap;
+
+use crate::configs::git_branch::default_forge_symbols;
+
+/// The forge, owner and name of a repository, parsed from the URL of its remote
+#[derive(Debug, PartialEq, Eq)]
+pub struct ForgeRemote {
+    /// `github`, `gitlab`, `bitbucket`, `gitea` or `self-hosted`
+    pub forge: &'static str,
+    pub host: String,
+    /// The user or organization, including any subgroups on GitLab
+    pub owner: String,
+    pub repo: String,
+    /// `ssh`, `https`, `http` or `git`
+    pub protocol: String,
+}
+
+impl ForgeRemote {
+    /// Parses URLs like `https://host/owner/repo.git`, `ssh://git@host:22/owner/repo` and
+    /// the scp-like `git@host:owner/repo.git`. Local paths have no forge.
+    pub fn parse(url: &str) -> Option<Self> {
+        let (protocol, rest) = match url.split_once("://") {
+            Some((scheme, rest)) => (scheme.to_lowercase(), rest),
+            // scp-like syntax, which needs a colon before the first slash
+            None => {
+                let colon = url.find(':')?;
+                if url[..colon].contains('/') {
+                    return None;
+                }
+                (String::from("ssh"), url)
+            }
+        };
+        if protocol == "file" {
+            return None;
+        }
+
+        let (authority, path) = if protocol == "ssh" && !url.contains("://") {
+            rest.split_once(':')?
+        } else {
+            rest.split_once('/')?
+        };
+        let host = authority.rsplit('@').next()?;
+        // Drop the port, but keep IPv6 addresses intact
+        let host = match host.rsplit_once(':') {
+            Some((host, port)) if port.chars().all(|c| c.is_ascii_digit()) => host,
+            _ => host,
+        }
+        .to_lowercase();
+
+        let path = path.trim_matches('/');
+        let path = path.strip_suffix(".git").unwrap_or(path);
+        let (owner, repo) = path.rsplit_once('/')?;
+        // Scp-like URLs may point to a path below the home directory of the user
+        let owner = owner.trim_start_matches('~');
+        if host.is_empty() || owner.is_empty() || repo.is_empty() {
+            return None;
+        }
+
+        Some(ForgeRemote {
+            forge: detect_forge(&host),
+            host,
+            owner: owner.to_string(),
+            repo: repo.to_string(),
+            protocol,
+        })
+    }
+
+    /// The symbol of the forge in `symbols`, falling back to the default symbols
+    pub fn symbol<'a>(&self, symbols: &IndexMap<String, &'a str>) -> Option<&'a str> {
+        symbols
+            .get(self.forge)
+            .copied()
+            .or_else(|| default_forge_symbols().get(self.forge).copied())
+    }
+
+    /// The value of the formatter variables `git_branch` and `git_commit` share
+    pub fn get_variable(&self, variable: &str, symbols: &IndexMap<String, &str>) -> Option<String> {
+        match variable {
+            "forge" => Some(self.forge.to_string()),
+            "forge_symbol" => self.symbol(symbols).map(str::to_string),
+            "remote_host" => Some(self.host.clone()),
+            "remote_owner" => Some(self.owner.clone()),
+            "remote_repo" => Some(self.repo.clone()),
+            "remote_protocol" => Some(self.protocol.clone()),
+            _ => None,
+        }
+    }
+}
+
+fn detect_forge(host: &str) -> &'static str {
+    // Hosted instances, and self-hosted ones which have the product in their host name
+    [
+        ("github", "github"),
+        ("gitlab", "gitlab"),
+        ("bitbucket", "bitbucket"),
+        ("gitea", "gitea"),
+        ("codeberg.org", "gitea"),
+    ]
+    .iter()
+    .find(|(pattern, _)| host.contains(pattern))
+    .map_or("self-hosted", |(_, forge)| forge)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn remote(
+        forge: &'static str,
+        host: &str,
+        owner: &str,
+        repo: &str,
+        protocol: &str,
+    ) -> ForgeRemote {
+        ForgeRemote {
+            forge,
+            host: host.to_string(),
+            owner: owner.to_string(),
+            repo: repo.to_string(),
+            protocol: protocol.to_string(),
+        }
+    }
+
+    #[test]
+    fn parse_https() {
+        assert_eq!(
+            ForgeRemote::parse("https://github.com/starship/starship.git"),
+            Some(remote(
+                "github",
+                "github.com",
+                "starship",
+                "starship",
+                "https"
+            ))
+        );
+        assert_eq!(
+            ForgeRemote::parse("https://user@gitlab.com/team/sub/repo"),
+            Some(remote("gitlab", "gitlab.com", "team/sub", "repo", "https"))
+        );
+    }
+
+    #[test]
+    fn parse_ssh() {
+        assert_eq!(
+            ForgeRemote::parse("git@bitbucket.org:team/repo.git"),
+            Some(remote("bitbucket", "bitbucket.org", "team", "repo", "ssh"))
+        );
+        assert_eq!(
+            ForgeRemote::parse("ssh://git@codeberg.org:2222/owner/repo.git/"),
+            Some(remote("gitea", "codeberg.org", "owner", "repo", "ssh"))
+        );
+        assert_eq!(
+            ForgeRemote::parse("git.example.com:~alice/dotfiles"),
+            Some(remote(
+                "self-hosted",
+                "git.example.com",
+                "alice",
+                "dotfiles",
+                "ssh"
+            ))
+        );
+    }
+
+    #[test]
+    fn parse_local_paths() {
+        assert_eq!(ForgeRemote::parse("/srv/git/repo.git"), None);
+        assert_eq!(ForgeRemote::parse("../repo"), None);
+        assert_eq!(ForgeRemote::parse("file:///srv/git/repo.git"), None);
+        assert_eq!(ForgeRemote::parse("./dir:with/colon"), None);
+    }
+
+    #[test]
+    fn symbol_falls_back_to_default() {
+        let symbols = IndexMap::from([(String::from("gitlab"), "GL ")]);
+        let github = ForgeRemote::parse("https://github.com/a/b").unwrap();
+        let gitlab = ForgeRemote::parse("https://gitlab.com/a/b").unwrap();
+        let other = ForgeRemote::parse("https://example.com/a/b").unwrap();
+
+        assert_eq!(gitlab.symbol(&symbols), Some("GL "));
+        assert_eq!(github.symbol(&symbols), Some("\u{f09b} "));
+        assert_eq!(other.symbol(&symbols), None);
+    }
+}
@@ -1,3 +1,5 @@
+pub mod cloud;
+
 pub mod directory;
 
 #[cfg(target_os = "windows")]
@@ -6,6 +8,10 @@ pub mod directory_win;
 #[cfg(not(target_os = "windows"))]
 pub mod directory_nix;
 
+pub mod forge;
+
 pub mod path;
 
 pub mod truncate;
+
+pub mod vcs;
@@ -0,0 +1,49 @@
+use std::path::{Path, PathBuf};
+
+use super::Vcs;
+use crate::context::Context;
+
+/// The file marking the root of a Fossil check-out
+pub const CHECKOUT_DB: &str = if cfg!(windows) {
+    "_FOSSIL_"
+} else {
+    ".fslckout"
+};
+
+pub struct Fossil<'a> {
+    context: &'a Context<'a>,
+    root: PathBuf,
+}
+
+impl<'a> Fossil<'a> {
+    pub fn open(context: &'a Context, root: &Path) -> Self {
+        Fossil {
+            context,
+            root: root.to_path_buf(),
+        }
+    }
+}
+
+impl<'a> Vcs for Fossil<'a> {
+    fn name(&self) -> &'static str {
+        "fossil"
+    }
+
+    fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn branch(&self) -> Option<String> {
+        get_current_branch(self.context)
+    }
+
+    fn is_dirty(&self) -> Option<bool> {
+        let output = self.context.exec_cmd("fossil", &["changes"])?.stdout;
+        Some(!output.trim().is_empty())
+    }
+}
+
+pub fn get_current_branch(context: &Context) -> Option<String> {
+    let output = context.exec_cmd("fossil", &["branch", "current"])?.stdout;
+    Some(output.trim().to_string())
+}
@@ -0,0 +1,129 @@
+use std::ffi::OsStr;
+use std::path::Path;
+
+use gix::state::InProgress;
+use once_cell::sync::OnceCell;
+
+use super::{Operation, Vcs};
+use crate::context::{Context, Repo};
+
+pub struct Git<'a> {
+    context: &'a Context<'a>,
+    repo: &'a Repo,
+    root: &'a Path,
+    status: OnceCell<Option<Status>>,
+}
+
+#[derive(Default)]
+struct Status {
+    dirty: bool,
+    ahead_behind: Option<(usize, usize)>,
+}
+
+impl<'a> Git<'a> {
+    pub fn open(context: &'a Context) -> Option<Self> {
+        let repo = context.get_repo().ok()?;
+        let root = repo.workdir.as_deref()?;
+        Some(Git {
+            context,
+            repo,
+            root,
+            status: OnceCell::new(),
+        })
+    }
+
+    /// Runs `git status` once for both the dirty state and the ahead/behind counts
+    fn status(&self) -> Option<&Status> {
+        self.status
+            .get_or_init(|| {
+                let output = self.context.exec_cmd(
+                    "git",
+                    &[
+                        OsStr::new("-C"),
+                        self.context.current_dir.as_os_str(),
+                        OsStr::new("--no-optional-locks"),
+                        OsStr::new("status"),
+                        OsStr::new("--porcelain=2"),
+                        OsStr::new("--branch"),
+                    ],
+                )?;
+                Some(parse_status(&output.stdout))
+            })
+            .as_ref()
+    }
+}
+
+fn parse_status(output: &str) -> Status {
+    output.lines().fold(Status::default(), |mut status, line| {
+        if let Some(ab) = line.strip_prefix("# branch.ab ") {
+            let mut counts = ab
+                .split(' ')
+                .map(|count| count.trim_start_matches(['+', '-']).parse().ok());
+            if let (Some(Some(ahead)), Some(Some(behind))) = (counts.next(), counts.next()) {
+                status.ahead_behind = Some((ahead, behind));
+            }
+        } else if !line.starts_with('#') && !line.starts_with('!') {
+            status.dirty = true;
+        }
+        status
+    })
+}
+
+impl<'a> Vcs for Git<'a> {
+    fn name(&self) -> &'static str {
+        "git"
+    }
+
+    fn root(&self) -> &Path {
+        self.root
+    }
+
+    fn branch(&self) -> Option<String> {
+        self.repo.branch.clone()
+    }
+
+    fn commit(&self) -> Option<String> {
+        let repo = self.repo.open();
+        let id = repo.head_id().ok()?;
+        Some(id.to_hex_with_len(7).to_string())
+    }
+
+    fn is_dirty(&self) -> Option<bool> {
+        self.status().map(|status| status.dirty)
+    }
+
+    fn ahead_behind(&self) -> Option<(usize, usize)> {
+        self.status()?.ahead_behind
+    }
+
+    fn operation(&self) -> Option<Operation> {
+        Some(match self.repo.state.as_ref()? {
+            InProgress::Merge => Operation::Merge,
+            InProgress::Revert | InProgress::RevertSequence => Operation::Revert,
+            InProgress::CherryPick | InProgress::CherryPickSequence => Operation::CherryPick,
+            InProgress::Bisect => Operation::Bisect,
+            InProgress::ApplyMailbox => Operation::ApplyMailbox,
+            InProgress::ApplyMailboxRebase | InProgress::Rebase | InProgress::RebaseInteractive => {
+                Operation::Rebase
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_porcelain_status() {
+        let status = parse_status(
+            "# branch.oid 5a2b\n# branch.head main\n# branch.upstream origin/main\n# branch.ab +2 -1\n",
+        );
+        assert!(!status.dirty);
+        assert_eq!(status.ahead_behind, Some((2, 1)));
+
+        let status = parse_status("# branch.head main\n? new.txt\n");
+        assert!(status.dirty);
+        assert_eq!(status.ahead_behind, None);
+    }
+}
@@ -0,0 +1,771 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::Error;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+
+use super::{Operation, Vcs};
+use crate::context::Context;
+use crate::utils::{encode_to_hex, read_file};
+
+/// Mercurial abbreviates changeset ids to 12 hex digits
+pub const SHORT_HASH_LENGTH: usize = 12;
+
+const NODE_LENGTH: usize = 20;
+const DIRSTATE_V2_MARKER: &[u8] = b"dirstate-v2\n";
+const AHEAD_BEHIND_CACHE_FILE: &str = "hg_ahead_behind_cache.json";
+
+pub struct Hg<'a> {
+    context: &'a Context<'a>,
+    root: PathBuf,
+    status: OnceCell<Option<HgStatus>>,
+}
+
+impl<'a> Hg<'a> {
+    pub fn open(context: &'a Context, root: &Path) -> Self {
+        Hg {
+            context,
+            root: root.to_path_buf(),
+            status: OnceCell::new(),
+        }
+    }
+}
+
+impl<'a> Vcs for Hg<'a> {
+    fn name(&self) -> &'static str {
+        "hg"
+    }
+
+    fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn branch(&self) -> Option<String> {
+        Some(get_current_bookmark(&self.root).unwrap_or_else(|_| {
+            get_branch_name(&self.root).unwrap_or_else(|_| String::from("default"))
+        }))
+    }
+
+    fn commit(&self) -> Option<String> {
+        let (parent, _) = get_parents(&self.root)?;
+        Some(parent[..SHORT_HASH_LENGTH].to_string())
+    }
+
+    fn is_dirty(&self) -> Option<bool> {
+        // Changes to tracked files are found without running `hg` in most cases
+        if get_dirstate_status(&self.root).map_or(false, |status| status.is_dirty()) {
+            return Some(true);
+        }
+        self.status
+            .get_or_init(|| get_status(self.context, &self.root, true))
+            .as_ref()
+            .map(HgStatus::is_dirty)
+    }
+
+    fn ahead_behind(&self) -> Option<(usize, usize)> {
+        get_ahead_behind(self.context, &self.root)
+    }
+
+    fn operation(&self) -> Option<Operation> {
+        get_operation(&self.root)
+    }
+}
+
+pub fn get_branch_name(hg_root: &Path) -> Result<String, Error> {
+    match read_file(hg_root.join(".hg").join("branch")) {
+        Ok(b) => Ok(b.trim().to_string()),
+        Err(e) => Err(e),
+    }
+}
+
+pub fn get_current_bookmark(hg_root: &Path) -> Result<String, Error> {
+    read_file(hg_root.join(".hg").join("bookmarks.current"))
+}
+
+pub fn get_topic_name(hg_root: &Path) -> Result<String, Error> {
+    read_file(hg_root.join(".hg").join("topic"))
+}
+
+/// Reads the full hex ids of the working directory parents from `.hg/dirstate`.
+///
+/// The second parent is only set while a merge is in progress.
+pub fn get_parents(hg_root: &Path) -> Option<(String, Option<String>)> {
+    let dirstate = fs::read(hg_root.join(".hg").join("dirstate")).ok()?;
+    let (p1, p2) = match dirstate.strip_prefix(DIRSTATE_V2_MARKER) {
+        // dirstate-v2 pads both parents to 32 bytes
+        Some(docket) => (
+            docket.get(..NODE_LENGTH)?,
+            docket.get(32..32 + NODE_LENGTH)?,
+        ),
+        None => (
+            dirstate.get(..NODE_LENGTH)?,
+            dirstate.get(NODE_LENGTH..2 * NODE_LENGTH)?,
+        ),
+    };
+
+    let is_null = |node: &[u8]| node.iter().all(|byte| *byte == 0);
+    if is_null(p1) {
+        return None;
+    }
+    let p2 = (!is_null(p2)).then(|| encode_to_hex(p2));
+    Some((encode_to_hex(p1), p2))
+}
+
+/// Looks up the tag of a changeset in `.hgtags` and `.hg/localtags`, where later entries win
+pub fn get_tag(hg_root: &Path, node: &str) -> Option<String> {
+    [
+        hg_root.join(".hgtags"),
+        hg_root.join(".hg").join("localtags"),
+    ]
+    .iter()
+    .filter_map(|file| read_file(file).ok())
+    .flat_map(|tags| {
+        tags.lines()
+            .filter_map(|line| {
+                let (tag_node, tag) = line.split_once(' ')?;
+                (tag_node == node).then(|| tag.trim().to_string())
+            })
+            .collect::<Vec<_>>()
+    })
+    .last()
+}
+
+/// The store of the repository, which a share made with `hg share` keeps in the repository it
+/// was shared from. `.hg/sharedpath` names the `.hg` directory of that repository, relative to
+/// the own `.hg` directory with the `relshared` format.
+pub fn get_store(hg_root: &Path) -> PathBuf {
+    let hg_dir = hg_root.join(".hg");
+    match read_file(hg_dir.join("sharedpath")) {
+        Ok(shared) => hg_dir.join(shared.trim()).join("store"),
+        Err(_) => hg_dir.join("store"),
+    }
+}
+
+struct ChangelogEntry {
+    /// The revision numbers of the parents, `None` for the null revision
+    parents: [Option<usize>; 2],
+    node: String,
+}
+
+/// Reads the parents and node of every revision from the changelog index `00changelog.i` of the
+/// store, a revlog-v1 whose data may be inlined after each entry.
+fn read_changelog(store: &Path) -> Option<Vec<ChangelogEntry>> {
+    const ENTRY_LENGTH: usize = 64;
+    const INLINE_FLAG: u32 = 1 << 16;
+
+    let index = fs::read(store.join("00changelog.i")).ok()?;
+    // The first entry starts with the version and flags of the revlog
+    let header = u32::from_be_bytes(index.get(..4)?.try_into().ok()?);
+    if header & 0xffff != 1 {
+        return None;
+    }
+
+    let mut entries = Vec::new();
+    let mut offset = 0;
+    while offset < index.len() {
+        // Offset and flags (8 bytes), compressed and uncompressed length, base and link revision,
+        // both parents (big-endian i32 each) and the node (20 bytes, padded to 32)
+        let entry = index.get(offset..offset + ENTRY_LENGTH)?;
+        let field = |start: usize| {
+            i32::from_be_bytes(entry[start..start + 4].try_into().unwrap_or_default())
+        };
+        entries.push(ChangelogEntry {
+            parents: [
+                usize::try_from(field(24)).ok(),
+                usize::try_from(field(28)).ok(),
+            ],
+            node: encode_to_hex(&entry[32..32 + NODE_LENGTH]),
+        });
+        offset += ENTRY_LENGTH;
+        if header & INLINE_FLAG != 0 {
+            offset += usize::try_from(field(8)).ok()?;
+        }
+    }
+    Some(entries)
+}
+
+/// The modification time and size of a file, which tell whether it changed
+#[derive(PartialEq, Eq, Deserialize, Serialize)]
+struct FileStamp {
+    modified: SystemTime,
+    len: u64,
+}
+
+impl FileStamp {
+    fn of(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        Some(FileStamp {
+            modified: metadata.modified().ok()?,
+            len: metadata.len(),
+        })
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+struct CachedAheadBehind {
+    changelog: FileStamp,
+    phaseroots: Option<FileStamp>,
+    parent: String,
+    ahead: usize,
+    behind: usize,
+}
+
+/// Ahead and behind counts keyed by store, persisted in the starship cache directory, as counting
+/// reads the whole changelog. An entry is only reused as long as the working directory parent and
+/// the modification time and size of the changelog and the phase roots match.
+#[derive(Default, Deserialize, Serialize)]
+struct AheadBehindCache {
+    stores: HashMap<String, CachedAheadBehind>,
+}
+
+impl AheadBehindCache {
+    fn load(cache_file: &Path) -> Self {
+        read_file(cache_file)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(mut self, cache_file: &Path) {
+        // Forget about stores which no longer exist
+        self.stores.retain(|store, _| Path::new(store).exists());
+
+        let result = cache_file
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| {
+                let contents = serde_json::to_string(&self)?;
+                fs::write(cache_file, contents)
+            });
+        if let Err(e) = result {
+            log::debug!(
+                "Unable to write hg ahead/behind cache {:?}: {}",
+                cache_file,
+                e
+            );
+        }
+    }
+}
+
+/// Counts how many changesets the working directory is ahead and behind, using phases instead of
+/// asking the remote repository.
+///
+/// Ahead are the draft and secret ancestors of the working directory parent, which weren't pushed
+/// yet. Behind are the public changesets descending from its latest public ancestor which aren't
+/// checked out, usually pulled but not updated to.
+pub fn get_ahead_behind(context: &Context, hg_root: &Path) -> Option<(usize, usize)> {
+    let store = get_store(hg_root);
+    let (parent, _) = get_parents(hg_root)?;
+    let changelog = FileStamp::of(&store.join("00changelog.i"))?;
+    let phaseroots = FileStamp::of(&store.join("phaseroots"));
+
+    let cache_file = context.get_cache_dir().join(AHEAD_BEHIND_CACHE_FILE);
+    let mut cache = AheadBehindCache::load(&cache_file);
+    let key = store.to_string_lossy().into_owned();
+    if let Some(cached) = cache.stores.get(&key) {
+        if cached.changelog == changelog
+            && cached.phaseroots == phaseroots
+            && cached.parent == parent
+        {
+            log::trace!("Using cached ahead/behind counts of {:?}", store);
+            return Some((cached.ahead, cached.behind));
+        }
+    }
+
+    let (ahead, behind) = count_ahead_behind(&store, &parent)?;
+    cache.stores.insert(
+        key,
+        CachedAheadBehind {
+            changelog,
+            phaseroots,
+            parent,
+            ahead,
+            behind,
+        },
+    );
+    cache.save(&cache_file);
+    Some((ahead, behind))
+}
+
+fn count_ahead_behind(store: &Path, parent: &str) -> Option<(usize, usize)> {
+    let changelog = read_changelog(store)?;
+    let working_dir = changelog.iter().position(|entry| entry.node == parent)?;
+
+    // Each line of `phaseroots` is a phase and a node, and public (0) roots aren't listed
+    let phaseroots = read_file(store.join("phaseroots")).unwrap_or_default();
+    let draft_roots: Vec<&str> = phaseroots
+        .lines()
+        .filter_map(|line| {
+            let (phase, node) = line.split_once(' ')?;
+            (phase != "0").then_some(node.trim())
+        })
+        .collect();
+
+    // Revisions are numbered in topological order, so parents are always visited first
+    let mut is_draft = vec![false; changelog.len()];
+    for (rev, entry) in changelog.iter().enumerate() {
+        is_draft[rev] = draft_roots.contains(&entry.node.as_str())
+            || entry
+                .parents
+                .iter()
+                .flatten()
+                .any(|parent| is_draft[*parent]);
+    }
+
+    let mut is_ancestor = vec![false; changelog.len()];
+    is_ancestor[working_dir] = true;
+    for rev in (0..=working_dir).rev() {
+        if is_ancestor[rev] {
+            for parent in changelog[rev].parents.iter().flatten() {
+                is_ancestor[*parent] = true;
+            }
+        }
+    }
+
+    let ahead = (0..=working_dir)
+        .filter(|rev| is_ancestor[*rev] && is_draft[*rev])
+        .count();
+
+    let Some(base) = (0..=working_dir)
+        .rev()
+        .find(|rev| is_ancestor[*rev] && !is_draft[*rev])
+    else {
+        return Some((ahead, 0));
+    };
+    let mut is_descendant = vec![false; changelog.len()];
+    is_descendant[base] = true;
+    let mut behind = 0;
+    for rev in base + 1..changelog.len() {
+        is_descendant[rev] = changelog[rev]
+            .parents
+            .iter()
+            .flatten()
+            .any(|parent| is_descendant[*parent]);
+        if is_descendant[rev] && !is_draft[rev] && !is_ancestor[rev] {
+            behind += 1;
+        }
+    }
+
+    Some((ahead, behind))
+}
+
+/// Detects unfinished operations from the state files Mercurial keeps in `.hg`, in the same
+/// order as `hg summary` reports them
+pub fn get_operation(hg_root: &Path) -> Option<Operation> {
+    let hg_dir = hg_root.join(".hg");
+    let states = [
+        ("histedit-state", Operation::Histedit),
+        ("rebasestate", Operation::Rebase),
+        ("graftstate", Operation::Graft),
+        ("shelvedstate", Operation::Unshelve),
+        ("merge/state", Operation::Merge),
+        ("merge/state2", Operation::Merge),
+    ];
+    if let Some((_, operation)) = states.iter().find(|(file, _)| hg_dir.join(file).is_file()) {
+        return Some(*operation);
+    }
+
+    // `hg bisect --reset` empties the file instead of removing it
+    let is_bisecting = fs::metadata(hg_dir.join("bisect.state"))
+        .map(|metadata| metadata.len() > 0)
+        .unwrap_or(false);
+    is_bisecting.then_some(Operation::Bisect)
+}
+
+/// The number of files in the working directory per status
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct HgStatus {
+    pub modified: usize,
+    pub added: usize,
+    /// Removed files, including those deleted without `hg remove` (missing)
+    pub removed: usize,
+    /// `None` if the status was read without `hg`, which is needed to apply `.hgignore`
+    pub unknown: Option<usize>,
+}
+
+impl HgStatus {
+    pub fn is_dirty(&self) -> bool {
+        self.modified > 0 || self.added > 0 || self.removed > 0 || self.unknown.unwrap_or(0) > 0
+    }
+
+    fn add(&mut self, line: &str) {
+        match line.get(..2) {
+            Some("M ") => self.modified += 1,
+            Some("A ") => self.added += 1,
+            Some("R " | "! ") => self.removed += 1,
+            Some("? ") => *self.unknown.get_or_insert(0) += 1,
+            _ => {}
+        }
+    }
+}
+
+/// Gets the status of the working directory.
+///
+/// The status of tracked files is read from `.hg/dirstate`, falling back to `hg status` whenever
+/// the dirstate is ambiguous. Unknown files are listed by `hg status -u`, as only `hg` applies
+/// `.hgignore`. Running `hg` is subject to `command_timeout`.
+pub fn get_status(context: &Context, hg_root: &Path, with_unknown: bool) -> Option<HgStatus> {
+    let Some(status) = get_dirstate_status(hg_root) else {
+        // Only modified, added, removed, deleted (missing) and unknown files
+        let filter = if with_unknown { "-mardu" } else { "-mard" };
+        return run_status(context, hg_root, filter, with_unknown);
+    };
+
+    if !with_unknown {
+        return Some(status);
+    }
+    let unknown = run_status(context, hg_root, "-u", true).and_then(|status| status.unknown);
+    Some(HgStatus { unknown, ..status })
+}
+
+fn run_status(
+    context: &Context,
+    hg_root: &Path,
+    filter: &str,
+    with_unknown: bool,
+) -> Option<HgStatus> {
+    let output = context.exec_cmd(
+        "hg",
+        &[
+            "--repository",
+            hg_root.to_str()?,
+            "status",
+            "--color",
+            "never",
+            filter,
+        ],
+    )?;
+    let status = output
+        .stdout
+        .lines()
+        .fold(HgStatus::default(), |mut status, line| {
+            status.add(line);
+            status
+        });
+    Some(HgStatus {
+        unknown: status.unknown.or(with_unknown.then_some(0)),
+        ..status
+    })
+}
+
+/// Reads the status of tracked files from a dirstate-v1 file.
+///
+/// Returns `None` if any file might have been modified without changing its size, since only
+/// comparing the contents can tell, or if the dirstate uses another format.
+pub fn get_dirstate_status(hg_root: &Path) -> Option<HgStatus> {
+    let dirstate = fs::read(hg_root.join(".hg").join("dirstate")).ok()?;
+    if dirstate.starts_with(DIRSTATE_V2_MARKER) {
+        return None;
+    }
+
+    let mut status = HgStatus::default();
+    // Each entry: state (1 byte), mode, size, mtime and name length (big-endian i32), name
+    let mut entries = dirstate.get(2 * NODE_LENGTH..)?;
+    while !entries.is_empty() {
+        let header = entries.get(..17)?;
+        let field = |offset: usize| {
+            i32::from_be_bytes(header[offset..offset + 4].try_into().unwrap_or_default())
+        };
+        let (size, mtime, name_length) = (field(5), field(9), usize::try_from(field(13)).ok()?);
+        let name = entries.get(17..17 + name_length)?;
+        entries = &entries[17 + name_length..];
+
+        match header[0] {
+            b'a' => status.added += 1,
+            b'r' => status.removed += 1,
+            b'm' => status.modified += 1,
+            b'n' => {
+                // A copy source follows the file name, separated by a NUL byte
+                let name = name.split(|byte| *byte == 0).next()?;
+                let path = hg_root.join(std::str::from_utf8(name).ok()?);
+                let Ok(metadata) = path.symlink_metadata() else {
+                    status.removed += 1;
+                    continue;
+                };
+                if size < 0 || mtime < 0 {
+                    return None;
+                }
+                if metadata.len() != u64::from(size.unsigned_abs()) {
+                    status.modified += 1;
+                    continue;
+                }
+                let file_mtime = metadata
+                    .modified()
+                    .ok()?
+                    .duration_since(UNIX_EPOCH)
+                    .ok()?
+                    .as_secs();
+                // Mercurial keeps the lower 31 bits of the modification time
+                if file_mtime & 0x7fff_ffff != u64::from(mtime.unsigned_abs()) {
+                    return None;
+                }
+            }
+            _ => return None,
+        }
+    }
+
+    Some(status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::default_context;
+    use std::fs;
+    use std::io;
+
+    fn dirstate_entry(state: u8, size: i32, mtime: i32, name: &str) -> Vec<u8> {
+        let mut entry = vec![state];
+        entry.extend(0o644_i32.to_be_bytes());
+        entry.extend(size.to_be_bytes());
+        entry.extend(mtime.to_be_bytes());
+        entry.extend((name.len() as i32).to_be_bytes());
+        entry.extend(name.as_bytes());
+        entry
+    }
+
+    fn file_mtime(path: &Path) -> io::Result<i32> {
+        let mtime = path
+            .metadata()?
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        Ok((mtime & 0x7fff_ffff) as i32)
+    }
+
+    #[test]
+    fn parse_dirstate() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::create_dir(dir.path().join(".hg"))?;
+        fs::write(dir.path().join("clean.txt"), "clean")?;
+        fs::write(dir.path().join("changed.txt"), "changed size")?;
+        fs::write(dir.path().join("new.txt"), "new")?;
+
+        let mut dirstate = vec![0xab; NODE_LENGTH];
+        dirstate.extend([0; NODE_LENGTH]);
+        let clean_mtime = file_mtime(&dir.path().join("clean.txt"))?;
+        dirstate.extend(dirstate_entry(b'n', 5, clean_mtime, "clean.txt"));
+        dirstate.extend(dirstate_entry(b'n', 7, clean_mtime, "changed.txt"));
+        dirstate.extend(dirstate_entry(b'n', 3, clean_mtime, "missing.txt"));
+        dirstate.extend(dirstate_entry(b'a', -1, -1, "new.txt"));
+        dirstate.extend(dirstate_entry(b'r', 0, 0, "old.txt"));
+        fs::write(dir.path().join(".hg/dirstate"), &dirstate)?;
+
+        let expected = HgStatus {
+            modified: 1,
+            added: 1,
+            removed: 2,
+            unknown: None,
+        };
+        assert_eq!(get_dirstate_status(dir.path()), Some(expected));
+        assert_eq!(
+            get_parents(dir.path()),
+            Some((String::from("ab").repeat(NODE_LENGTH), None))
+        );
+
+        // The size didn't change, so the contents have to be compared
+        dirstate.extend(dirstate_entry(b'n', 3, clean_mtime - 10, "new.txt"));
+        fs::write(dir.path().join(".hg/dirstate"), &dirstate)?;
+        assert_eq!(get_dirstate_status(dir.path()), None);
+
+        dir.close()
+    }
+
+    fn changelog_entry(rev: i32, parents: [i32; 2], node: u8) -> Vec<u8> {
+        // The first entry starts with the version (1) of a revlog without inline data
+        let mut entry = if rev == 0 {
+            vec![0, 0, 0, 1]
+        } else {
+            vec![0; 4]
+        };
+        entry.extend([0; 20]);
+        entry.extend(parents[0].to_be_bytes());
+        entry.extend(parents[1].to_be_bytes());
+        entry.extend([node; NODE_LENGTH]);
+        entry.extend([0; 12]);
+        entry
+    }
+
+    #[test]
+    fn count_ahead_behind() -> io::Result<()> {
+        let context = default_context();
+        let dir = tempfile::tempdir()?;
+        fs::create_dir_all(dir.path().join(".hg/store"))?;
+
+        // 0 - 1 - 2 (draft, working directory) - 3 (draft)
+        //      \
+        //       4 - 5 (pulled)
+        let changelog = [
+            changelog_entry(0, [-1, -1], 0x10),
+            changelog_entry(1, [0, -1], 0x11),
+            changelog_entry(2, [1, -1], 0x12),
+            changelog_entry(3, [2, -1], 0x13),
+            changelog_entry(4, [1, -1], 0x14),
+            changelog_entry(5, [4, -1], 0x15),
+        ]
+        .concat();
+        fs::write(dir.path().join(".hg/store/00changelog.i"), changelog)?;
+        fs::write(
+            dir.path().join(".hg/store/phaseroots"),
+            format!("1 {}\n", "12".repeat(NODE_LENGTH)),
+        )?;
+
+        let mut dirstate = vec![0x12; NODE_LENGTH];
+        dirstate.extend([0; NODE_LENGTH]);
+        fs::write(dir.path().join(".hg/dirstate"), &dirstate)?;
+        assert_eq!(get_ahead_behind(&context, dir.path()), Some((1, 2)));
+
+        let mut dirstate = vec![0x11; NODE_LENGTH];
+        dirstate.extend([0; NODE_LENGTH]);
+        fs::write(dir.path().join(".hg/dirstate"), &dirstate)?;
+        assert_eq!(get_ahead_behind(&context, dir.path()), Some((0, 2)));
+
+        fs::remove_file(dir.path().join(".hg/store/phaseroots"))?;
+        let mut dirstate = vec![0x15; NODE_LENGTH];
+        dirstate.extend([0; NODE_LENGTH]);
+        fs::write(dir.path().join(".hg/dirstate"), &dirstate)?;
+        assert_eq!(get_ahead_behind(&context, dir.path()), Some((0, 0)));
+        dir.close()
+    }
+
+    #[test]
+    fn ahead_behind_of_shared_repo() -> io::Result<()> {
+        let context = default_context();
+        let source = tempfile::tempdir()?;
+        fs::create_dir_all(source.path().join(".hg/store"))?;
+        let changelog = [
+            changelog_entry(0, [-1, -1], 0x10),
+            changelog_entry(1, [0, -1], 0x11),
+        ]
+        .concat();
+        fs::write(source.path().join(".hg/store/00changelog.i"), changelog)?;
+        fs::write(
+            source.path().join(".hg/store/phaseroots"),
+            format!("1 {}\n", "11".repeat(NODE_LENGTH)),
+        )?;
+
+        let share = tempfile::tempdir()?;
+        fs::create_dir_all(share.path().join(".hg"))?;
+        fs::write(
+            share.path().join(".hg/sharedpath"),
+            source.path().join(".hg").to_string_lossy().as_bytes(),
+        )?;
+        let mut dirstate = vec![0x11; NODE_LENGTH];
+        dirstate.extend([0; NODE_LENGTH]);
+        fs::write(share.path().join(".hg/dirstate"), &dirstate)?;
+
+        assert_eq!(get_store(share.path()), source.path().join(".hg/store"));
+        assert_eq!(get_ahead_behind(&context, share.path()), Some((1, 0)));
+        source.close()?;
+        share.close()
+    }
+
+    #[test]
+    fn cache_ahead_behind() -> io::Result<()> {
+        let context = default_context();
+        let dir = tempfile::tempdir()?;
+        fs::create_dir_all(dir.path().join(".hg/store"))?;
+        let changelog = [
+            changelog_entry(0, [-1, -1], 0x10),
+            changelog_entry(1, [0, -1], 0x11),
+        ]
+        .concat();
+        fs::write(dir.path().join(".hg/store/00changelog.i"), &changelog)?;
+        let mut dirstate = vec![0x10; NODE_LENGTH];
+        dirstate.extend([0; NODE_LENGTH]);
+        fs::write(dir.path().join(".hg/dirstate"), &dirstate)?;
+        assert_eq!(get_ahead_behind(&context, dir.path()), Some((0, 1)));
+
+        // The cached counts are used while the changelog keeps its modification time and size
+        let cache_file = context.get_cache_dir().join(AHEAD_BEHIND_CACHE_FILE);
+        let mut cache = AheadBehindCache::load(&cache_file);
+        let store = dir.path().join(".hg/store").to_string_lossy().into_owned();
+        cache.stores.get_mut(&store).unwrap().ahead = 7;
+        cache.save(&cache_file);
+        assert_eq!(get_ahead_behind(&context, dir.path()), Some((7, 1)));
+
+        // A change of the changelog invalidates them
+        fs::write(dir.path().join(".hg/store/00changelog.i"), &changelog[..64])?;
+        assert_eq!(get_ahead_behind(&context, dir.path()), Some((0, 0)));
+        dir.close()
+    }
+
+    #[test]
+    fn parse_dirstate_v2_parents() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::create_dir(dir.path().join(".hg"))?;
+        let mut docket = DIRSTATE_V2_MARKER.to_vec();
+        docket.extend([0x12; 32]);
+        docket.extend([0x34; 32]);
+        docket.extend([0; 16]);
+        fs::write(dir.path().join(".hg/dirstate"), docket)?;
+
+        assert_eq!(
+            get_parents(dir.path()),
+            Some((
+                String::from("12").repeat(NODE_LENGTH),
+                Some(String::from("34").repeat(NODE_LENGTH))
+            ))
+        );
+        assert_eq!(get_dirstate_status(dir.path()), None);
+        dir.close()
+    }
+
+    #[test]
+    fn parse_status_output() {
+        let status = "M a.txt\nA b.txt\nR c.txt\n! d.txt\n? e.txt\n? f.txt\n"
+            .lines()
+            .fold(HgStatus::default(), |mut status, line| {
+                status.add(line);
+                status
+            });
+        let expected = HgStatus {
+            modified: 1,
+            added: 1,
+            removed: 2,
+            unknown: Some(2),
+        };
+        assert_eq!(status, expected);
+    }
+
+    #[test]
+    fn detect_operation() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let hg_dir = dir.path().join(".hg");
+        fs::create_dir_all(hg_dir.join("merge"))?;
+        assert_eq!(get_operation(dir.path()), None);
+
+        fs::write(hg_dir.join("bisect.state"), "")?;
+        assert_eq!(get_operation(dir.path()), None);
+        fs::write(hg_dir.join("bisect.state"), "good 1234\n")?;
+        assert_eq!(get_operation(dir.path()), Some(Operation::Bisect));
+
+        fs::write(hg_dir.join("merge/state2"), "")?;
+        assert_eq!(get_operation(dir.path()), Some(Operation::Merge));
+
+        // A graft stops with a merge state when it runs into conflicts
+        fs::write(hg_dir.join("graftstate"), "")?;
+        assert_eq!(get_operation(dir.path()), Some(Operation::Graft));
+        dir.close()
+    }
+
+    #[test]
+    fn lookup_tag() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::create_dir(dir.path().join(".hg"))?;
+        let node = "ab".repeat(NODE_LENGTH);
+        fs::write(
+            dir.path().join(".hgtags"),
+            format!("{node} v1.0\n{} v0.9\n", "cd".repeat(NODE_LENGTH)),
+        )?;
+        assert_eq!(get_tag(dir.path(), &node), Some(String::from("v1.0")));
+
+        fs::write(dir.path().join(".hg/localtags"), format!("{node} local\n"))?;
+        assert_eq!(get_tag(dir.path(), &node), Some(String::from("local")));
+        dir.close()
+    }
+}
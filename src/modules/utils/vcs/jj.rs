@@ -0,0 +1,453 @@
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use once_cell::sync::OnceCell;
+
+use super::{Operation, Vcs};
+use crate::context::Context;
+use crate::utils::{encode_to_hex, read_file};
+
+/// One line per field of the working copy commit, parsed by [`parse_log`]
+pub const LOG_TEMPLATE: &str = r#"change_id.shortest(8) ++ "\n" ++ bookmarks.join(" ") ++ "\n" ++ if(empty, "empty", "changed") ++ "\n" ++ if(conflict, "conflict", "")"#;
+
+pub struct Jj<'a> {
+    context: &'a Context<'a>,
+    root: PathBuf,
+    repo_dir: PathBuf,
+    working_copy: OnceCell<Option<WorkingCopy>>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+struct WorkingCopy {
+    change_id: String,
+    bookmarks: Vec<String>,
+    empty: bool,
+    conflict: bool,
+}
+
+impl<'a> Jj<'a> {
+    /// Opens the workspace at `root` if `.jj/repo` points to a repository
+    pub fn open(context: &'a Context, root: &Path) -> Option<Self> {
+        let repo_dir = get_repo_dir(root)?;
+        log::trace!("Found jj repo: {}", repo_dir.display());
+        Some(Jj {
+            context,
+            root: root.to_path_buf(),
+            repo_dir,
+            working_copy: OnceCell::new(),
+        })
+    }
+
+    /// Reads the working copy commit without snapshotting the working copy, which would
+    /// create a new operation on every prompt
+    fn working_copy(&self) -> Option<&WorkingCopy> {
+        self.working_copy
+            .get_or_init(|| {
+                read_working_copy(&self.root, &self.repo_dir).or_else(|| self.log_working_copy())
+            })
+            .as_ref()
+    }
+
+    /// Asks `jj` for the working copy commit, for repositories which cannot be read natively
+    fn log_working_copy(&self) -> Option<WorkingCopy> {
+        let output = self.context.exec_cmd(
+            "jj",
+            &[
+                OsStr::new("--repository"),
+                self.root.as_os_str(),
+                OsStr::new("--ignore-working-copy"),
+                OsStr::new("--color"),
+                OsStr::new("never"),
+                OsStr::new("log"),
+                OsStr::new("--no-graph"),
+                OsStr::new("-r"),
+                OsStr::new("@"),
+                OsStr::new("-T"),
+                OsStr::new(LOG_TEMPLATE),
+            ],
+        )?;
+        parse_log(&output.stdout)
+    }
+}
+
+/// `.jj/repo` is the repository itself, or in secondary workspaces a file containing its path
+fn get_repo_dir(root: &Path) -> Option<PathBuf> {
+    let jj_dir = root.join(".jj");
+    let repo = jj_dir.join("repo");
+    let repo_dir = if repo.is_file() {
+        jj_dir.join(read_file(&repo).ok()?.trim())
+    } else {
+        repo
+    };
+    repo_dir.is_dir().then_some(repo_dir)
+}
+
+/// Reads the working copy commit from the view of the current operation, the way
+/// `jj --ignore-working-copy` resolves `@`. Returns `None` when only jj itself can tell, e.g. for
+/// concurrent operations, stores other than git, merge commits or commits written by a jj version
+/// which did not record the change id in a `change-id` header.
+fn read_working_copy(root: &Path, repo_dir: &Path) -> Option<WorkingCopy> {
+    let mut op_heads = fs::read_dir(repo_dir.join("op_heads/heads")).ok()?;
+    let op_id = op_heads.next()?.ok()?.file_name();
+    if op_heads.next().is_some() {
+        return None;
+    }
+
+    let operation = fs::read(repo_dir.join("op_store/operations").join(op_id)).ok()?;
+    // `Operation.view_id`
+    let view_id = proto_bytes(&operation, 1).next()?;
+    let view = fs::read(repo_dir.join("op_store/views").join(encode_to_hex(view_id))).ok()?;
+
+    // `Checkout.workspace_id`, which is left out for the default workspace
+    let checkout = fs::read(root.join(".jj/working_copy/checkout")).unwrap_or_default();
+    let workspace = proto_bytes(&checkout, 3).next().unwrap_or(b"default");
+    let (commit_id, bookmarks) = parse_view(&view, workspace)?;
+
+    if read_file(repo_dir.join("store/type")).ok()?.trim() != "git" {
+        return None;
+    }
+    let store_dir = repo_dir.join("store");
+    let git_dir = store_dir.join(read_file(store_dir.join("git_target")).ok()?.trim());
+    let repo = gix::open_opts(git_dir, gix::open::Options::isolated()).ok()?;
+
+    let commit_id = gix::oid::try_from_bytes(commit_id).ok()?.to_owned();
+    let commit = repo.find_object(commit_id).ok()?.try_into_commit().ok()?;
+    let commit = commit.decode().ok()?;
+    let change_id = commit.extra_headers().find("change-id")?.to_string();
+
+    let mut parents = commit.parents();
+    let parent_tree = match (parents.next(), parents.next()) {
+        // Children of the root commit have no parent in git
+        (None, _) => gix::ObjectId::empty_tree(repo.object_hash()),
+        (Some(parent), None) => {
+            let parent = repo.find_object(parent).ok()?.try_into_commit().ok()?;
+            parent.tree_id().ok()?.detach()
+        }
+        _ => return None,
+    };
+
+    Some(WorkingCopy {
+        change_id: change_id.chars().take(8).collect(),
+        bookmarks,
+        empty: commit.tree() == parent_tree,
+        // Conflicted commits list the trees of their sides in a `jj:trees` header
+        conflict: commit.extra_headers().find("jj:trees").is_some(),
+    })
+}
+
+/// Finds the working copy commit of `workspace` and the local bookmarks pointing to it in a
+/// serialized `View`
+fn parse_view<'b>(view: &'b [u8], workspace: &[u8]) -> Option<(&'b [u8], Vec<String>)> {
+    // `View.wc_commit_ids`, a map from workspace id to commit id, or the legacy `wc_commit_id`
+    let commit_id = proto_bytes(view, 8)
+        .find(|entry| proto_bytes(entry, 1).next().unwrap_or_default() == workspace)
+        .and_then(|entry| proto_bytes(entry, 2).next())
+        .or_else(|| proto_bytes(view, 2).next())?;
+
+    // `View.bookmarks`, each with a `name` and a `local_target`
+    let bookmarks = proto_bytes(view, 5)
+        .filter_map(|bookmark| {
+            let name = proto_bytes(bookmark, 1).next()?;
+            let target = proto_bytes(bookmark, 2).next()?;
+            ref_target_ids(target)
+                .any(|id| id == commit_id)
+                .then(|| String::from_utf8_lossy(name).into_owned())
+        })
+        .collect();
+
+    Some((commit_id, bookmarks))
+}
+
+/// The commit ids of a `RefTarget`: its `commit_id`, or the added sides of its `conflict`
+fn ref_target_ids(target: &[u8]) -> impl Iterator<Item = &[u8]> {
+    let conflict_adds = proto_bytes(target, 3)
+        .flat_map(|conflict| proto_bytes(conflict, 2))
+        .filter_map(|term| proto_bytes(term, 1).next());
+    proto_bytes(target, 1).chain(conflict_adds)
+}
+
+/// The values of the length-delimited fields numbered `field` of a protobuf message
+fn proto_bytes(message: &[u8], field: u64) -> impl Iterator<Item = &[u8]> {
+    let mut buf = message;
+    std::iter::from_fn(move || {
+        let key = read_varint(&mut buf)?;
+        let value = match key & 0b111 {
+            0 => read_varint(&mut buf).map(|_| None)?,
+            1 => buf.get(8..).map(|rest| buf = rest).map(|_| None)?,
+            2 => {
+                let len = read_varint(&mut buf)? as usize;
+                let value = buf.get(..len)?;
+                buf = &buf[len..];
+                Some(value)
+            }
+            5 => buf.get(4..).map(|rest| buf = rest).map(|_| None)?,
+            _ => return None,
+        };
+        Some((key >> 3, value))
+    })
+    .filter(move |(number, _)| *number == field)
+    .filter_map(|(_, value)| value)
+}
+
+fn read_varint(buf: &mut &[u8]) -> Option<u64> {
+    let mut value = 0;
+    for (i, byte) in buf.iter().enumerate().take(10) {
+        value |= u64::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            *buf = &buf[i + 1..];
+            return Some(value);
+        }
+    }
+    None
+}
+
+fn parse_log(output: &str) -> Option<WorkingCopy> {
+    let mut lines = output.lines();
+    let change_id = lines.next().filter(|id| !id.is_empty())?.to_string();
+    let bookmarks = lines
+        .next()?
+        .split_whitespace()
+        // A trailing `*` marks bookmarks which differ from their remote
+        .map(|bookmark| bookmark.trim_end_matches('*').to_string())
+        .collect();
+    let empty = lines.next()? == "empty";
+    let conflict = lines.next() == Some("conflict");
+
+    Some(WorkingCopy {
+        change_id,
+        bookmarks,
+        empty,
+        conflict,
+    })
+}
+
+impl<'a> Vcs for Jj<'a> {
+    fn name(&self) -> &'static str {
+        "jj"
+    }
+
+    fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The bookmarks of the working copy commit, or its change id if there are none
+    fn branch(&self) -> Option<String> {
+        let working_copy = self.working_copy()?;
+        if working_copy.bookmarks.is_empty() {
+            Some(working_copy.change_id.clone())
+        } else {
+            Some(working_copy.bookmarks.join(" "))
+        }
+    }
+
+    fn commit(&self) -> Option<String> {
+        Some(self.working_copy()?.change_id.clone())
+    }
+
+    fn is_dirty(&self) -> Option<bool> {
+        Some(!self.working_copy()?.empty)
+    }
+
+    fn operation(&self) -> Option<Operation> {
+        self.working_copy()?.conflict.then_some(Operation::Conflict)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::create_command;
+    use std::io::{self, Write};
+    use std::process::Stdio;
+
+    /// Encodes a length-delimited protobuf field
+    fn proto_field(number: u8, value: &[u8]) -> Vec<u8> {
+        [&[number << 3 | 2, value.len() as u8], value].concat()
+    }
+
+    fn git(dir: &Path, args: &[&str], input: &str) -> io::Result<String> {
+        let mut child = create_command("git")?
+            .args(args)
+            .current_dir(dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        child.stdin.take().unwrap().write_all(input.as_bytes())?;
+        let output = child.wait_with_output()?;
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    #[test]
+    fn parse_view_of_workspace() {
+        let bookmark = |name: &str, commit_id: &[u8]| {
+            proto_field(
+                5,
+                &[
+                    proto_field(1, name.as_bytes()),
+                    proto_field(2, &proto_field(1, commit_id)),
+                ]
+                .concat(),
+            )
+        };
+        let wc_commit_id = |workspace: &str, commit_id: &[u8]| {
+            proto_field(
+                8,
+                &[
+                    proto_field(1, workspace.as_bytes()),
+                    proto_field(2, commit_id),
+                ]
+                .concat(),
+            )
+        };
+        let view = [
+            proto_field(1, b"head"),
+            wc_commit_id("default", b"abc"),
+            wc_commit_id("secondary", b"def"),
+            bookmark("main", b"abc"),
+            bookmark("feature", b"def"),
+            bookmark("topic", b"abc"),
+        ]
+        .concat();
+
+        assert_eq!(
+            parse_view(&view, b"default"),
+            Some((
+                &b"abc"[..],
+                vec![String::from("main"), String::from("topic")]
+            ))
+        );
+        assert_eq!(
+            parse_view(&view, b"secondary"),
+            Some((&b"def"[..], vec![String::from("feature")]))
+        );
+        assert_eq!(parse_view(&proto_field(1, b"head"), b"default"), None);
+    }
+
+    #[test]
+    fn read_colocated_working_copy() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let root = dir.path();
+        git(root, &["init", "--quiet"], "")?;
+
+        let blob = git(root, &["hash-object", "-w", "--stdin"], "content")?;
+        let tree = git(root, &["mktree"], &format!("100644 blob {blob}\tfile\n"))?;
+        let signature = "starship <starship@example.com> 0 +0000";
+        let parent = git(
+            root,
+            &["hash-object", "-t", "commit", "-w", "--stdin"],
+            &format!(
+                "tree 4b825dc642cb6eb9a060e54bf8d69288fbee4904\n\
+                 author {signature}\ncommitter {signature}\n\
+                 change-id zzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzz\n\nparent\n"
+            ),
+        )?;
+        let commit = git(
+            root,
+            &["hash-object", "-t", "commit", "-w", "--stdin"],
+            &format!(
+                "tree {tree}\nparent {parent}\nauthor {signature}\ncommitter {signature}\n\
+                 change-id kxqpmzvsrtlnouwkysxvwnkorsrqzwyk\n\ncommit\n"
+            ),
+        )?;
+
+        let repo_dir = root.join(".jj/repo");
+        fs::create_dir_all(repo_dir.join("op_heads/heads"))?;
+        fs::create_dir_all(repo_dir.join("op_store/operations"))?;
+        fs::create_dir_all(repo_dir.join("op_store/views"))?;
+        fs::create_dir_all(repo_dir.join("store"))?;
+        fs::write(repo_dir.join("store/type"), "git")?;
+        fs::write(repo_dir.join("store/git_target"), "../../../.git")?;
+
+        let read_commit = |commit: &str| -> io::Result<Option<WorkingCopy>> {
+            let commit_id = (0..commit.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&commit[i..i + 2], 16).unwrap())
+                .collect::<Vec<_>>();
+            let view = [
+                proto_field(
+                    8,
+                    &[proto_field(1, b"default"), proto_field(2, &commit_id)].concat(),
+                ),
+                proto_field(
+                    5,
+                    &[
+                        proto_field(1, b"main"),
+                        proto_field(2, &proto_field(1, &commit_id)),
+                    ]
+                    .concat(),
+                ),
+            ]
+            .concat();
+            fs::write(repo_dir.join("op_store/views/0a0b"), view)?;
+            fs::write(
+                repo_dir.join("op_store/operations/0c0d"),
+                proto_field(1, &[0x0a, 0x0b]),
+            )?;
+            fs::write(repo_dir.join("op_heads/heads/0c0d"), "")?;
+            Ok(read_working_copy(root, &repo_dir))
+        };
+
+        assert_eq!(
+            read_commit(&commit)?,
+            Some(WorkingCopy {
+                change_id: String::from("kxqpmzvs"),
+                bookmarks: vec![String::from("main")],
+                empty: false,
+                conflict: false,
+            })
+        );
+        assert_eq!(
+            read_commit(&parent)?,
+            Some(WorkingCopy {
+                change_id: String::from("zzzzzzzz"),
+                bookmarks: vec![String::from("main")],
+                empty: true,
+                conflict: false,
+            })
+        );
+
+        // Concurrent operations need jj to be resolved
+        fs::write(repo_dir.join("op_heads/heads/0e0f"), "")?;
+        assert_eq!(read_commit(&commit)?, None);
+        dir.close()
+    }
+
+    #[test]
+    fn parse_working_copy() {
+        assert_eq!(
+            parse_log("kxqpmzvs\nmain* feature\nchanged\nconflict"),
+            Some(WorkingCopy {
+                change_id: String::from("kxqpmzvs"),
+                bookmarks: vec![String::from("main"), String::from("feature")],
+                empty: false,
+                conflict: true,
+            })
+        );
+        assert_eq!(
+            parse_log("kx\n\nempty\n"),
+            Some(WorkingCopy {
+                change_id: String::from("kx"),
+                bookmarks: vec![],
+                empty: true,
+                conflict: false,
+            })
+        );
+        assert_eq!(parse_log(""), None);
+    }
+
+    #[test]
+    fn repo_dir_of_secondary_workspace() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let main = dir.path().join("main");
+        let secondary = dir.path().join("secondary");
+        fs::create_dir_all(main.join(".jj/repo"))?;
+        fs::create_dir_all(secondary.join(".jj"))?;
+        fs::write(secondary.join(".jj/repo"), "../../main/.jj/repo")?;
+
+        assert!(get_repo_dir(&main).is_some());
+        assert!(get_repo_dir(&secondary).is_some());
+        assert_eq!(get_repo_dir(dir.path()), None);
+        dir.close()
+    }
+}
@@ -0,0 +1,113 @@
+//! A common interface to the version control systems starship knows about.
+//!
+//! Each backend is detected by a marker in the root of its working copy. When a directory
+//! contains several markers (e.g. a Jujutsu repository colocated with git), the backend which
+//! comes first in the requested order wins.
+
+use std::path::Path;
+
+use crate::context::Context;
+
+pub mod fossil;
+pub mod git;
+pub mod hg;
+pub mod jj;
+pub mod pijul;
+
+/// An operation which was started but not finished yet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Merge,
+    Rebase,
+    Revert,
+    CherryPick,
+    Bisect,
+    ApplyMailbox,
+    Graft,
+    Histedit,
+    Unshelve,
+    Conflict,
+}
+
+pub trait Vcs: Sync {
+    /// The name of the version control system, e.g. `git`
+    fn name(&self) -> &'static str;
+
+    /// The root of the working copy
+    fn root(&self) -> &Path;
+
+    /// The current branch, bookmark or channel
+    fn branch(&self) -> Option<String>;
+
+    /// A short identifier of the checked out commit, changeset or change
+    fn commit(&self) -> Option<String> {
+        None
+    }
+
+    /// Whether the working copy contains changes which were not committed
+    fn is_dirty(&self) -> Option<bool> {
+        None
+    }
+
+    /// How many commits the current branch is ahead and behind its upstream
+    fn ahead_behind(&self) -> Option<(usize, usize)> {
+        None
+    }
+
+    /// The operation in progress, if any
+    fn operation(&self) -> Option<Operation> {
+        None
+    }
+}
+
+/// The files or folders marking the root of a working copy of the backend
+fn markers(backend: &str) -> (&'static [&'static str], &'static [&'static str]) {
+    match backend {
+        "jj" => (&[], &[".jj"]),
+        // `.git` is a file in worktrees and submodules
+        "git" => (&[".git"], &[".git"]),
+        "hg" => (&[], &[".hg"]),
+        "fossil" => (&[fossil::CHECKOUT_DB], &[]),
+        "pijul" => (&[], &[".pijul"]),
+        _ => (&[], &[]),
+    }
+}
+
+fn has_marker(dir: &Path, backend: &str) -> bool {
+    let (files, folders) = markers(backend);
+    files.iter().any(|file| dir.join(file).is_file())
+        || folders.iter().any(|folder| dir.join(folder).is_dir())
+}
+
+/// Finds the innermost working copy of any of `backends` containing the current directory.
+pub fn discover<'a>(context: &'a Context, backends: &[&str]) -> Option<Box<dyn Vcs + 'a>> {
+    let (files, folders): (Vec<&str>, Vec<&str>) =
+        backends
+            .iter()
+            .fold((vec![], vec![]), |(mut files, mut folders), backend| {
+                let (backend_files, backend_folders) = markers(backend);
+                files.extend(backend_files);
+                folders.extend(backend_folders);
+                (files, folders)
+            });
+
+    let root = context
+        .begin_ancestor_scan()
+        .set_files(&files)
+        .set_folders(&folders)
+        .scan()?;
+
+    backends
+        .iter()
+        .filter(|backend| has_marker(root, backend))
+        .find_map(|backend| -> Option<Box<dyn Vcs + 'a>> {
+            match *backend {
+                "jj" => Some(Box::new(jj::Jj::open(context, root)?)),
+                "git" => Some(Box::new(git::Git::open(context)?)),
+                "hg" => Some(Box::new(hg::Hg::open(context, root))),
+                "fossil" => Some(Box::new(fossil::Fossil::open(context, root))),
+                "pijul" => Some(Box::new(pijul::Pijul::open(context, root))),
+                _ => None,
+            }
+        })
+}
@@ -0,0 +1,41 @@
+use std::path::{Path, PathBuf};
+
+use super::Vcs;
+use crate::context::Context;
+
+pub struct Pijul<'a> {
+    context: &'a Context<'a>,
+    root: PathBuf,
+}
+
+impl<'a> Pijul<'a> {
+    pub fn open(context: &'a Context, root: &Path) -> Self {
+        Pijul {
+            context,
+            root: root.to_path_buf(),
+        }
+    }
+}
+
+impl<'a> Vcs for Pijul<'a> {
+    fn name(&self) -> &'static str {
+        "pijul"
+    }
+
+    fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn branch(&self) -> Option<String> {
+        get_current_channel(self.context)
+    }
+}
+
+pub fn get_current_channel(ctx: &Context) -> Option<String> {
+    let output = ctx.exec_cmd("pijul", &["channel"])?.stdout;
+
+    output
+        .lines()
+        .find_map(|l| l.strip_prefix("* "))
+        .map(str::to_owned)
+}
@@ -0,0 +1,314 @@
+use super::utils::truncate::truncate_text;
+use super::utils::vcs::{self, Operation};
+use super::{Context, Module, ModuleConfig};
+
+use crate::configs::vcs::VcsConfig;
+use crate::formatter::StringFormatter;
+
+/// Creates a module with the branch and state of the repository in the current directory
+///
+/// Will display the branch if the current directory is within a working copy of any of the
+/// configured `backends`. The innermost working copy is used, and when a directory contains
+/// several (e.g. a jj repository colocated with git) the first of `backends` wins.
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("vcs");
+    let config: VcsConfig = VcsConfig::try_load(module.config);
+
+    // As we default to disabled=true, we have to check here after loading our config module,
+    // before it was only checking against whatever is in the config starship.toml
+    if config.disabled {
+        return None;
+    };
+
+    let len = if config.truncation_length <= 0 {
+        log::warn!(
+            "\"truncation_length\" should be a positive value, found {}",
+            config.truncation_length
+        );
+        usize::MAX
+    } else {
+        config.truncation_length as usize
+    };
+
+    let repo = vcs::discover(context, &config.backends)?;
+    log::trace!("Found {} repo: {}", repo.name(), repo.root().display());
+
+    let symbol = config
+        .get_symbol(repo.name())
+        .or_else(|| VcsConfig::default().get_symbol(repo.name()))
+        .unwrap_or_default();
+
+    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+        formatter
+            .map_meta(|variable, _| match variable {
+                "symbol" => Some(symbol),
+                _ => None,
+            })
+            .map_style(|variable| match variable {
+                "style" => Some(Ok(config.style)),
+                "operation_style" => Some(Ok(config.operation_style)),
+                _ => None,
+            })
+            .map(|variable| match variable {
+                "vcs" => Some(Ok(repo.name().to_string())),
+                "dirty" => repo
+                    .is_dirty()
+                    .unwrap_or_default()
+                    .then(|| Ok(config.dirty_symbol.to_string())),
+                "operation" => repo
+                    .operation()
+                    .map(|operation| Ok(get_operation_label(&config, operation).to_string())),
+                "branch" => repo
+                    .branch()
+                    .or_else(|| repo.commit())
+                    .map(|branch| Ok(truncate_text(&branch, len, config.truncation_symbol))),
+                "commit" => repo.commit().map(Ok),
+                "ahead" => repo
+                    .ahead_behind()
+                    .filter(|(ahead, _)| *ahead > 0)
+                    .map(|(ahead, _)| Ok(ahead.to_string())),
+                "behind" => repo
+                    .ahead_behind()
+                    .filter(|(_, behind)| *behind > 0)
+                    .map(|(_, behind)| Ok(behind.to_string())),
+                _ => None,
+            })
+            .parse(None, Some(context))
+    });
+
+    module.set_segments(match parsed {
+        Ok(segments) => segments,
+        Err(error) => {
+            log::warn!("Error in module `vcs`:\n{}", error);
+            return None;
+        }
+    });
+
+    Some(module)
+}
+
+fn get_operation_label<'a>(config: &VcsConfig<'a>, operation: Operation) -> &'a str {
+    match operation {
+        Operation::Merge => config.merge,
+        Operation::Rebase => config.rebase,
+        Operation::Revert => config.revert,
+        Operation::CherryPick => config.cherry_pick,
+        Operation::Bisect => config.bisect,
+        Operation::ApplyMailbox => config.am,
+        Operation::Graft => config.graft,
+        Operation::Histedit => config.histedit,
+        Operation::Unshelve => config.unshelve,
+        Operation::Conflict => config.conflict,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nu_ansi_term::Color;
+    use std::fs;
+    use std::io;
+    use std::path::Path;
+
+    use crate::modules::utils::vcs::jj::LOG_TEMPLATE;
+    use crate::test::{fixture_repo, FixtureProvider, ModuleRenderer};
+    use crate::utils::CommandOutput;
+
+    fn jj_log_command(root: &Path) -> String {
+        format!(
+            "jj --repository {} --ignore-working-copy --color never log --no-graph -r @ -T {}",
+            root.display(),
+            LOG_TEMPLATE
+        )
+    }
+
+    fn jj_log_output(stdout: &str) -> Option<CommandOutput> {
+        Some(CommandOutput {
+            stdout: String::from(stdout),
+            stderr: String::default(),
+        })
+    }
+
+    #[test]
+    fn show_nothing_on_empty_dir() -> io::Result<()> {
+        let repo_dir = tempfile::tempdir()?;
+
+        let actual = ModuleRenderer::new("vcs")
+            .path(repo_dir.path())
+            .config(toml::toml! {
+                [vcs]
+                disabled = false
+            })
+            .collect();
+
+        assert_eq!(None, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn show_nothing_if_disabled() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+
+        let actual = ModuleRenderer::new("vcs").path(repo_dir.path()).collect();
+
+        assert_eq!(None, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn test_git_repo() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+        fs::write(repo_dir.path().join("untracked.txt"), "new")?;
+
+        let actual = ModuleRenderer::new("vcs")
+            .path(repo_dir.path())
+            .config(toml::toml! {
+                [vcs]
+                disabled = false
+            })
+            .collect();
+        let expected = Some(format!(
+            "on {} {} ",
+            Color::Purple.bold().paint("\u{e0a0} master"),
+            Color::Purple.bold().paint("*"),
+        ));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn test_git_rebase_in_progress() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+        fs::create_dir(repo_dir.path().join(".git/rebase-merge"))?;
+        fs::write(
+            repo_dir.path().join(".git/rebase-merge/head-name"),
+            "refs/heads/master",
+        )?;
+        fs::write(repo_dir.path().join(".git/rebase-merge/interactive"), "")?;
+
+        let actual = ModuleRenderer::new("vcs")
+            .path(repo_dir.path())
+            .config(toml::toml! {
+                [vcs]
+                disabled = false
+                format = "$vcs:$branch \\($operation\\)"
+            })
+            .collect();
+        let expected = Some(String::from("git:master (REBASING)"));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn test_jj_colocated_with_git() -> io::Result<()> {
+        let repo_dir = tempfile::tempdir()?;
+        fs::create_dir_all(repo_dir.path().join(".git"))?;
+        fs::create_dir_all(repo_dir.path().join(".jj/repo/store"))?;
+        let sub_dir = repo_dir.path().join("src");
+        fs::create_dir(&sub_dir)?;
+        let command = jj_log_command(repo_dir.path());
+
+        let actual = ModuleRenderer::new("vcs")
+            .path(&sub_dir)
+            .cmd(&command, jj_log_output("kxqpmzvs\nmain*\nchanged\n"))
+            .config(toml::toml! {
+                [vcs]
+                disabled = false
+            })
+            .collect();
+        let expected = Some(format!(
+            "on {} {} ",
+            Color::Purple.bold().paint("jj main"),
+            Color::Purple.bold().paint("*"),
+        ));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn test_jj_without_bookmark_and_conflict() -> io::Result<()> {
+        let repo_dir = tempfile::tempdir()?;
+        fs::create_dir_all(repo_dir.path().join(".jj/repo"))?;
+        let command = jj_log_command(repo_dir.path());
+
+        let actual = ModuleRenderer::new("vcs")
+            .path(repo_dir.path())
+            .cmd(&command, jj_log_output("kxqp\n\nempty\nconflict"))
+            .config(toml::toml! {
+                [vcs]
+                disabled = false
+            })
+            .collect();
+        let expected = Some(format!(
+            "on {} ({}) ",
+            Color::Purple.bold().paint("jj kxqp"),
+            Color::Yellow.bold().paint("CONFLICT"),
+        ));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn test_backend_order() -> io::Result<()> {
+        let repo_dir = tempfile::tempdir()?;
+        fs::create_dir_all(repo_dir.path().join(".jj/repo"))?;
+        fs::create_dir_all(repo_dir.path().join(".hg"))?;
+        fs::write(repo_dir.path().join(".hg/branch"), "stable\n")?;
+
+        let actual = ModuleRenderer::new("vcs")
+            .path(repo_dir.path())
+            .config(toml::toml! {
+                [vcs]
+                disabled = false
+                backends = ["hg", "jj"]
+                format = "$vcs:$branch"
+            })
+            .collect();
+
+        assert_eq!(Some(String::from("hg:stable")), actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn test_innermost_repo_wins() -> io::Result<()> {
+        let repo_dir = tempfile::tempdir()?;
+        fs::create_dir_all(repo_dir.path().join(".jj/repo"))?;
+        let nested = repo_dir.path().join("vendor/lib");
+        fs::create_dir_all(nested.join(".hg"))?;
+        fs::write(nested.join(".hg/bookmarks.current"), "feature")?;
+
+        let actual = ModuleRenderer::new("vcs")
+            .path(&nested)
+            .config(toml::toml! {
+                [vcs]
+                disabled = false
+                format = "$vcs:$branch"
+            })
+            .collect();
+
+        assert_eq!(Some(String::from("hg:feature")), actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn test_pijul_truncation() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Pijul)?;
+
+        let actual = ModuleRenderer::new("vcs")
+            .path(repo_dir.path())
+            .config(toml::toml! {
+                [vcs]
+                disabled = false
+                truncation_length = 4
+                symbols = { pijul = "P " }
+            })
+            .collect();
+        let expected = Some(format!("on {} ", Color::Purple.bold().paint("P trib…")));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+}
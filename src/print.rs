@@ -1,16 +1,21 @@
 use clap::{builder::PossibleValue, ValueEnum};
-use nu_ansi_term::AnsiStrings;
+use nu_ansi_term::{AnsiStrings, Color, Style};
 use rayon::prelude::*;
-use std::collections::BTreeSet;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fmt::{Debug, Write as FmtWrite};
+use std::fs;
+use std::hash::{Hash, Hasher};
 use std::io::{self, Write};
 use std::path::PathBuf;
+use std::sync::Mutex;
 use std::time::Duration;
 use terminal_size::terminal_size;
 use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthChar;
 
-use crate::configs::PROMPT_ORDER;
+use crate::config::ColorMode;
+use crate::configs::{SeparatorConfig, PROMPT_ORDER};
 use crate::context::{Context, Properties, Shell, Target};
 use crate::formatter::string_formatter::StringFormatterError;
 use crate::formatter::{StringFormatter, VariableHolder};
@@ -24,31 +29,92 @@ pub struct Grapheme<'a>(pub &'a str);
 
 impl<'a> Grapheme<'a> {
     pub fn width(&self) -> usize {
+        self.width_with(false)
+    }
+
+    /// The width of the grapheme, where East Asian ambiguous-width characters take two columns
+    /// if `ambiguous_wide` is set, like in CJK locales
+    pub fn width_with(&self, ambiguous_wide: bool) -> usize {
+        // Variation selectors choose between the emoji and the text presentation
+        if self.0.contains('\u{fe0f}') {
+            return 2;
+        }
+        if self.0.contains('\u{fe0e}') {
+            return 1;
+        }
+        // Flags are pairs of regional indicators, which are narrow on their own
+        if self
+            .0
+            .chars()
+            .filter(|c| ('\u{1f1e6}'..='\u{1f1ff}').contains(c))
+            .count()
+            == 2
+        {
+            return 2;
+        }
+        // The width of zero width joiner sequences is the width of their widest emoji
         self.0
             .chars()
-            .filter_map(UnicodeWidthChar::width)
+            .filter_map(|c| match ambiguous_wide {
+                true => c.width_cjk(),
+                false => c.width(),
+            })
             .max()
             .unwrap_or(0)
     }
 }
 
 pub trait UnicodeWidthGraphemes {
-    fn width_graphemes(&self) -> usize;
+    fn width_graphemes(&self) -> usize {
+        self.width_graphemes_with(false)
+    }
+
+    fn width_graphemes_with(&self, ambiguous_wide: bool) -> usize;
 }
 
 impl<T> UnicodeWidthGraphemes for T
 where
     T: AsRef<str>,
 {
-    fn width_graphemes(&self) -> usize {
+    fn width_graphemes_with(&self, ambiguous_wide: bool) -> usize {
         self.as_ref()
             .graphemes(true)
             .map(Grapheme)
-            .map(|g| g.width())
+            .map(|g| g.width_with(ambiguous_wide))
             .sum()
     }
 }
 
+/// The columns available to the lines of a prompt, which decide the size of `fill` segments
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PromptWidth {
+    /// The width of the terminal
+    pub terminal: usize,
+    /// The columns kept free on the last line, where the right prompt is drawn
+    pub right_prompt: usize,
+    /// Whether East Asian ambiguous-width characters take two columns
+    pub ambiguous_wide: bool,
+}
+
+impl PromptWidth {
+    pub fn new(terminal: usize) -> Self {
+        PromptWidth {
+            terminal,
+            ..Default::default()
+        }
+    }
+
+    /// The columns left for a line of `used` columns, which gives up the space of the right
+    /// prompt if the shell would hide it for lack of space anyway
+    pub fn available(&self, used: usize, last_line: bool) -> usize {
+        if last_line && used + self.right_prompt < self.terminal {
+            self.terminal - self.right_prompt
+        } else {
+            self.terminal
+        }
+    }
+}
+
 #[test]
 fn test_grapheme_aware_width() {
     // UnicodeWidthStr::width would return 8
@@ -57,6 +123,22 @@ fn test_grapheme_aware_width() {
     assert_eq!(11, "normal text".width_graphemes());
 }
 
+#[test]
+fn test_grapheme_presentation_width() {
+    assert_eq!(2, "\u{2601}\u{fe0f}".width_graphemes());
+    assert_eq!(1, "\u{2601}\u{fe0e}".width_graphemes());
+    assert_eq!(2, "\u{1f1e9}\u{1f1ea}".width_graphemes());
+    assert_eq!(2, "\u{1f3f3}\u{fe0f}\u{200d}\u{1f308}".width_graphemes());
+    assert_eq!(4, "1\u{fe0f}\u{20e3}2\u{fe0f}\u{20e3}".width_graphemes());
+}
+
+#[test]
+fn test_ambiguous_width() {
+    assert_eq!(3, "\u{2500}\u{2192}\u{b7}".width_graphemes());
+    assert_eq!(6, "\u{2500}\u{2192}\u{b7}".width_graphemes_with(true));
+    assert_eq!(2, "ab".width_graphemes_with(true));
+}
+
 pub fn prompt(args: Properties, target: Target) {
     let context = Context::new(args, target);
     let stdout = io::stdout();
@@ -77,65 +159,407 @@ pub fn get_prompt(context: Context) -> String {
         _ => {}
     }
 
+    if context.target == Target::Right && config.cache_right_prompt {
+        if let Some(prompt) = take_right_prompt(&context) {
+            return prompt;
+        }
+    }
+
     // A workaround for a fish bug (see #739,#279). Applying it to all shells
     // breaks things (see #808,#824,#834). Should only be printed in fish.
     if Shell::Fish == context.shell && context.target == Target::Main {
         buf.push_str("\x1b[J"); // An ASCII control code to clear screen
     }
 
-    let (formatter, modules) = load_formatter_and_modules(&context);
+    let root_module = root_module_for(&context, &context.target);
+    let ambiguous_wide = config.ambiguous_width == 2;
+    // The right prompt shares the last line of the prompt, so `fill` leaves space for it. bash
+    // has no right prompt, so it is drawn at the start of that line by starship itself.
+    let right_module = (context.target == Target::Main
+        && !config.right_format.is_empty()
+        && (context.shell == Shell::Bash || last_line_has_fill(&root_module)))
+    .then(|| root_module_for(&context, &Target::Right));
+    let right_width = right_module.as_ref().map_or(0, |module| {
+        module
+            .segments
+            .iter()
+            .map(|segment| segment.width_graphemes_with(ambiguous_wide))
+            .sum()
+    });
+    let width = PromptWidth {
+        terminal: context.width,
+        right_prompt: right_width,
+        ambiguous_wide,
+    };
+
+    let module_strings = root_module.ansi_strings_for_shell(context.shell, Some(width));
+    if config.add_newline && !matches!(context.target, Target::Continuation | Target::Transient) {
+        // continuation and transient prompts normally do not include newlines, but they can
+        writeln!(buf).unwrap();
+    }
+    write!(buf, "{}", AnsiStrings(&module_strings)).unwrap();
+
+    if context.target == Target::Right {
+        // right prompts generally do not allow newlines
+        buf = buf.replace('\n', "");
+    }
+
+    if let (Shell::Zsh | Shell::Fish, Some(right_module), true) =
+        (context.shell, &right_module, config.cache_right_prompt)
+    {
+        let width = PromptWidth {
+            right_prompt: 0,
+            ..width
+        };
+        let module_strings = right_module.ansi_strings_for_shell(context.shell, Some(width));
+        let right_prompt = AnsiStrings(&module_strings).to_string().replace('\n', "");
+        store_right_prompt(&context, &right_prompt);
+    }
+
+    if let (Shell::Bash, Some(right_module)) = (context.shell, &right_module) {
+        let line_start = buf.rfind('\n').map_or(0, |i| i + 1);
+        let left_width = last_line_width(&root_module, ambiguous_wide);
+        let right_prompt = bash_right_prompt(right_module, left_width, right_width, context.width);
+        buf.insert_str(line_start, &right_prompt);
+    }
+
+    // escape \n and ! characters for tcsh
+    if context.shell == Shell::Tcsh {
+        buf = buf.replace('!', "\\!");
+        // space is required before newline
+        buf = buf.replace('\n', " \\n");
+    }
+
+    buf
+}
+
+/// Creates the root module holding the segments of the format of `target`. Modules in `layout`
+/// give way when a line is wider than the terminal: from the lowest priority, the modules on
+/// lines which overflow switch to their `compact_format` first and are hidden after that, until
+/// every line fits.
+fn root_module_for<'a>(context: &'a Context, target: &Target) -> Module<'a> {
+    let layout = &context.root_config.layout;
+    let mut hidden: HashSet<&str> = layout
+        .iter()
+        .filter(|(_, module_layout)| module_layout.min_width > context.width)
+        .map(|(module, _)| module.as_str())
+        .collect();
+    if layout.is_empty() || context.width == 0 {
+        return render_root_module(context, target, &hidden, None);
+    }
+
+    let mut by_priority: Vec<_> = layout
+        .iter()
+        .filter(|(module, _)| !hidden.contains(module.as_str()))
+        .collect();
+    by_priority.sort_by_key(|(_, module_layout)| module_layout.priority);
+    let mut steps = by_priority
+        .iter()
+        .filter(|(_, module_layout)| module_layout.compact_format.is_some())
+        .map(|(module, _)| (module.as_str(), true))
+        .chain(
+            by_priority
+                .iter()
+                .map(|(module, _)| (module.as_str(), false)),
+        );
+
+    let cache = Mutex::new(HashMap::new());
+    let ambiguous_wide = context.root_config.ambiguous_width == 2;
+    loop {
+        let root_module = render_root_module(context, target, &hidden, Some(&cache));
+        let overflowing = overflowing_lines(&root_module, context.width, ambiguous_wide);
+        if overflowing.is_empty() {
+            return root_module;
+        }
+        // Modules on lines which fit stay as they are, as they don't make room on other lines
+        let step = match cache.lock() {
+            Ok(rendered) => steps.find(|(module, _)| {
+                rendered.get(*module).map_or(false, |segments| {
+                    is_on_lines(&root_module, segments, &overflowing)
+                })
+            }),
+            Err(_) => None,
+        };
+        match step {
+            Some((module, true)) => {
+                log::debug!("Switching {module} to its compact format to fit the terminal");
+                context.set_compact(module);
+                if let Ok(mut cache) = cache.lock() {
+                    cache.remove(module);
+                }
+            }
+            Some((module, false)) => {
+                log::debug!("Hiding {module} to fit the terminal");
+                hidden.insert(module);
+            }
+            None => return root_module,
+        }
+    }
+}
+
+fn render_root_module<'a>(
+    context: &'a Context,
+    target: &Target,
+    hidden: &HashSet<&str>,
+    cache: Option<&Mutex<HashMap<String, Vec<Segment>>>>,
+) -> Module<'a> {
+    let (formatter, modules) = load_formatter_and_modules(context, target);
+    let separator = &context.root_config.separator;
+    let add_separators = match separator.mode.as_str() {
+        "powerline" => {
+            matches!(target, Target::Main | Target::Profile(_))
+                && !context.root_config.accessibility
+        }
+        "none" => false,
+        mode => {
+            log::warn!("Unknown separator mode {mode:?}, expected \"powerline\" or \"none\"");
+            false
+        }
+    };
+
+    let module_segments = |module: &str| {
+        let cached = cache.and_then(|cache| cache.lock().ok()?.get(module).cloned());
+        let segments = cached.unwrap_or_else(|| {
+            let segments = handle_module(module, context, &modules)
+                .into_iter()
+                .flat_map(|module| module.segments)
+                .collect::<Vec<Segment>>();
+            if let Some(mut cache) = cache.and_then(|cache| cache.lock().ok()) {
+                cache.insert(module.to_string(), segments.clone());
+            }
+            segments
+        });
+        match add_separators {
+            true => std::iter::once(Segment::ModuleStart)
+                .chain(segments)
+                .collect(),
+            false => segments,
+        }
+    };
 
     let formatter = formatter.map_variables_to_segments(|module| {
         // Make $all display all modules not explicitly referenced
         if module == "all" {
             Some(Ok(all_modules_uniq(&modules)
                 .par_iter()
-                .flat_map(|module| {
-                    handle_module(module, &context, &modules)
-                        .into_iter()
-                        .flat_map(|module| module.segments)
-                        .collect::<Vec<Segment>>()
-                })
+                .filter(|module| !hidden.contains(module.as_str()))
+                .flat_map(|module| module_segments(module))
                 .collect::<Vec<_>>()))
-        } else if context.is_module_disabled_in_config(module) {
+        } else if context.is_module_disabled_in_config(module) || hidden.contains(module) {
             None
         } else {
             // Get segments from module
-            Some(Ok(handle_module(module, &context, &modules)
-                .into_iter()
-                .flat_map(|module| module.segments)
-                .collect::<Vec<Segment>>()))
+            Some(Ok(module_segments(module)))
         }
     });
 
-    // Creates a root module and prints it.
+    let mut segments = formatter
+        .parse(None, Some(context))
+        .expect("Unexpected error returned in root format variables");
+    if add_separators {
+        segments = separate_modules(&segments, separator);
+    }
+    let color_mode = ColorMode::from_context(context);
+    for segment in &mut segments {
+        segment.map_style(|style| color_mode.apply(style));
+    }
+
     let mut root_module = Module::new("Starship Root", "The root module", None);
-    root_module.set_segments(
-        formatter
-            .parse(None, Some(&context))
-            .expect("Unexpected error returned in root format variables"),
-    );
-
-    let module_strings = root_module.ansi_strings_for_shell(context.shell, Some(context.width));
-    if config.add_newline && context.target != Target::Continuation {
-        // continuation prompts normally do not include newlines, but they can
-        writeln!(buf).unwrap();
+    root_module.set_segments(segments);
+    root_module
+}
+
+/// Puts the glyphs of `separator` between the visible modules of each line. A glyph takes the
+/// background of the module before it as its color and the background of the next module as
+/// its background, so that the modules appear to flow into each other.
+fn separate_modules(segments: &[Segment], separator: &SeparatorConfig) -> Vec<Segment> {
+    let glyph = |symbol: &str, fg: Option<Color>, bg: Option<Color>| {
+        let mut style = Style::new();
+        style.foreground = fg;
+        style.background = bg;
+        match symbol {
+            "" => Vec::new(),
+            _ => Segment::from_text(Some(style), symbol),
+        }
+    };
+    let background = |segment: &&Segment| segment.style().and_then(|style| style.background);
+
+    let mut separated = Vec::new();
+    let mut lines = segments
+        .split(|segment| matches!(segment, Segment::LineTerm))
+        .peekable();
+    while let Some(line) = lines.next() {
+        // The background at the end of the last visible module, once there is one
+        let mut previous: Option<Option<Color>> = None;
+        for module in line.split(|segment| matches!(segment, Segment::ModuleStart)) {
+            let mut visible = module.iter().filter(|segment| !segment.value().is_empty());
+            let first = match visible.next() {
+                Some(first) => first,
+                None => {
+                    separated.extend_from_slice(module);
+                    continue;
+                }
+            };
+            let first_bg = background(&first);
+            let last_bg = visible
+                .next_back()
+                .map_or(first_bg, |last| background(&last));
+
+            separated.extend(match (previous, first_bg) {
+                (Some(Some(previous)), next) if Some(previous) != next => {
+                    glyph(&separator.symbol, Some(previous), next)
+                }
+                (Some(Some(_)), next) => glyph(&separator.thin_symbol, None, next),
+                (_, Some(next)) => glyph(&separator.start_symbol, Some(next), None),
+                _ => Vec::new(),
+            });
+            separated.extend_from_slice(module);
+            previous = Some(last_bg);
+        }
+
+        if let Some(Some(previous)) = previous {
+            separated.extend(glyph(&separator.end_symbol, Some(previous), None));
+        }
+        if lines.peek().is_some() {
+            separated.push(Segment::LineTerm);
+        }
     }
-    write!(buf, "{}", AnsiStrings(&module_strings)).unwrap();
+    separated
+}
 
-    if context.target == Target::Right {
-        // right prompts generally do not allow newlines
-        buf = buf.replace('\n', "");
+/// The lines of the prompt which are wider than the terminal, not counting `fill` segments
+fn overflowing_lines(module: &Module, width: usize, ambiguous_wide: bool) -> HashSet<usize> {
+    module
+        .segments
+        .split(|segment| matches!(segment, Segment::LineTerm))
+        .enumerate()
+        .filter(|(_, line)| {
+            line.iter()
+                .filter(|segment| !matches!(segment, Segment::Fill(_)))
+                .map(|segment| segment.width_graphemes_with(ambiguous_wide))
+                .sum::<usize>()
+                > width
+        })
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// Whether the visible `segments` of a module appear on one of the `lines` of the root module.
+/// Segments are compared by their text, as the root format may still change their style.
+fn is_on_lines(root_module: &Module, segments: &[Segment], lines: &HashSet<usize>) -> bool {
+    if segments
+        .iter()
+        .all(|segment| segment.width_graphemes() == 0)
+    {
+        return false;
     }
+    let same = |a: &Segment, b: &Segment| {
+        a.value() == b.value() && matches!(a, Segment::Fill(_)) == matches!(b, Segment::Fill(_))
+    };
 
-    // escape \n and ! characters for tcsh
-    if context.shell == Shell::Tcsh {
-        buf = buf.replace('!', "\\!");
-        // space is required before newline
-        buf = buf.replace('\n', " \\n");
+    let mut line = 0;
+    let root: Vec<(usize, &Segment)> = root_module
+        .segments
+        .iter()
+        .filter(|segment| !matches!(segment, Segment::ModuleStart))
+        .map(|segment| {
+            let index = line;
+            if matches!(segment, Segment::LineTerm) {
+                line += 1;
+            }
+            (index, segment)
+        })
+        .collect();
+    root.windows(segments.len()).any(|window| {
+        window.iter().zip(segments).all(|((_, a), b)| same(a, b))
+            && window.iter().any(|(index, _)| lines.contains(index))
+    })
+}
+
+fn last_line_segments<'a>(module: &'a Module) -> impl Iterator<Item = &'a Segment> {
+    module
+        .segments
+        .iter()
+        .rev()
+        .take_while(|segment| !matches!(segment, Segment::LineTerm))
+}
+
+fn last_line_has_fill(module: &Module) -> bool {
+    last_line_segments(module).any(|segment| matches!(segment, Segment::Fill(_)))
+}
+
+/// The width of the last line of the prompt, without its `fill` segments
+fn last_line_width(module: &Module, ambiguous_wide: bool) -> usize {
+    last_line_segments(module)
+        .filter(|segment| !matches!(segment, Segment::Fill(_)))
+        .map(|segment| segment.width_graphemes_with(ambiguous_wide))
+        .sum()
+}
+
+/// Renders `right_format` for bash, which has no right prompt of its own. Enclosed in `\[ \]`,
+/// it saves the cursor, moves to the right edge of the terminal, draws the prompt and restores
+/// the cursor. Positioning from the right edge keeps the prompt aligned when the terminal is
+/// resized, and a right prompt which does not fit next to the left prompt is dropped.
+fn bash_right_prompt(
+    root_module: &Module,
+    left_width: usize,
+    width: usize,
+    terminal_width: usize,
+) -> String {
+    if width == 0 || left_width + width >= terminal_width {
+        return String::new();
     }
 
-    buf
+    let module_strings = root_module.ansi_strings_for_shell(Shell::Unknown, None);
+    let prompt = AnsiStrings(&module_strings).to_string().replace('\n', "");
+    // Move back one column less than the width, as the cursor starts in the last column
+    let move_back = match width {
+        1 => String::new(),
+        _ => format!("\x1b[{}D", width - 1),
+    };
+    format!("\\[\x1b7\x1b[999C{move_back}{prompt}\x1b8\\]")
+}
+
+/// The file in which the main prompt of a zsh or fish session leaves the right prompt it rendered
+/// to size `fill`, so that the right prompt drawn next doesn't run its modules again. The file
+/// starts with a hash of the arguments, which both prompts of a redraw share.
+///
+/// With `cache_right_prompt` every such main prompt writes this file, and the right prompt
+/// deletes it. A file left behind by a main prompt whose right prompt wasn't drawn is still taken
+/// by a later right prompt with the same arguments and directory, even without a main prompt
+/// before it, and shows what was rendered back then.
+fn right_prompt_file(context: &Context) -> Option<(PathBuf, String)> {
+    let session_key = context
+        .get_env("STARSHIP_SESSION_KEY")
+        .filter(|key| !key.is_empty())?;
+    let mut hasher = DefaultHasher::new();
+    format!("{:?}", context.properties).hash(&mut hasher);
+    context.current_dir.hash(&mut hasher);
+    let file = context
+        .get_cache_dir()
+        .join(format!("session_{session_key}.right"));
+    Some((file, format!("{:016x}\n", hasher.finish())))
+}
+
+fn store_right_prompt(context: &Context, prompt: &str) {
+    let Some((file, key)) = right_prompt_file(context) else {
+        return;
+    };
+    let result = file
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::write(&file, key + prompt));
+    if let Err(e) = result {
+        log::debug!("Unable to write right prompt {:?}: {}", file, e);
+    }
+}
+
+/// Takes the right prompt left by the main prompt, if it was rendered with the same arguments
+fn take_right_prompt(context: &Context) -> Option<String> {
+    let (file, key) = right_prompt_file(context)?;
+    let contents = fs::read_to_string(&file).ok()?;
+    fs::remove_file(&file).ok()?;
+    contents.strip_prefix(&key).map(str::to_string)
 }
 
 pub fn module(module_name: &str, args: Properties) {
@@ -145,7 +569,13 @@ pub fn module(module_name: &str, args: Properties) {
 }
 
 pub fn get_module(module_name: &str, context: Context) -> Option<String> {
-    modules::handle(module_name, &context).map(|m| m.to_string())
+    let color_mode = ColorMode::from_context(&context);
+    modules::handle(module_name, &context).map(|mut module| {
+        for segment in &mut module.segments {
+            segment.map_style(|style| color_mode.apply(style));
+        }
+        module.to_string()
+    })
 }
 
 pub fn timings(args: Properties) {
@@ -292,7 +722,7 @@ pub fn explain(args: Properties) {
 fn compute_modules<'a>(context: &'a Context) -> Vec<Module<'a>> {
     let mut prompt_order: Vec<Module<'a>> = Vec::new();
 
-    let (_formatter, modules) = load_formatter_and_modules(context);
+    let (_formatter, modules) = load_formatter_and_modules(context, &context.target);
 
     for module in &modules {
         // Manually add all modules if `$all` is encountered
@@ -403,9 +833,12 @@ fn all_modules_uniq(module_list: &BTreeSet<String>) -> Vec<String> {
 
 /// Load the correct formatter for the context (ie left prompt or right prompt)
 /// and the list of all modules used in a format string
-fn load_formatter_and_modules<'a>(context: &'a Context) -> (StringFormatter<'a>, BTreeSet<String>) {
+fn load_formatter_and_modules<'a>(
+    context: &'a Context,
+    target: &Target,
+) -> (StringFormatter<'a>, BTreeSet<String>) {
     let config = &context.root_config;
-    let (formatter, config_param) = match &context.target {
+    let (formatter, config_param) = match target {
         Target::Main => (StringFormatter::new(&config.format), "format".to_string()),
         Target::Right => (
             StringFormatter::new(&config.right_format),
@@ -415,6 +848,10 @@ fn load_formatter_and_modules<'a>(context: &'a Context) -> (StringFormatter<'a>,
             StringFormatter::new(&config.continuation_prompt),
             "continuation_prompt".to_string(),
         ),
+        Target::Transient => (
+            StringFormatter::new(&config.transient_format),
+            "transient_format".to_string(),
+        ),
         Target::Profile(name) => (
             match config.profiles.get(name) {
                 Some(format) => StringFormatter::new(format),
@@ -448,7 +885,23 @@ fn load_formatter_and_modules<'a>(context: &'a Context) -> (StringFormatter<'a>,
 
 #[cfg(feature = "config-schema")]
 pub fn print_schema() {
-    let schema = schemars::schema_for!(crate::configs::FullConfig);
+    use schemars::schema::Schema;
+
+    let mut schema = schemars::schema_for!(crate::configs::FullConfig);
+    // Every module with a `format` accepts an `a11y_format`, which is resolved in `Context`
+    let a11y_format = schemars::gen::SchemaGenerator::default().subschema_for::<Option<String>>();
+    for definition in schema.definitions.values_mut() {
+        let Schema::Object(definition) = definition else {
+            continue;
+        };
+        if let Some(object) = definition.object.as_mut() {
+            if object.properties.contains_key("format") {
+                object
+                    .properties
+                    .insert(String::from("a11y_format"), a11y_format.clone());
+            }
+        }
+    }
     println!("{}", serde_json::to_string_pretty(&schema).unwrap());
 }
 
@@ -525,6 +978,406 @@ mod test {
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn no_color_strips_colors() {
+        let mut context = default_context().set_config(toml::toml! {
+                add_newline=false
+                format="[>](bold #ff0000) "
+        });
+        context.env.insert("NO_COLOR", String::from("1"));
+
+        let expected = format!("{} ", Style::new().bold().paint(">"));
+        let actual = get_prompt(context);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn powerline_separators_between_modules() {
+        let context = default_context().set_config(toml::toml! {
+                add_newline=false
+                format="$os$env_var$character"
+                [separator]
+                mode="powerline"
+                [os]
+                disabled=false
+                format="[os](bg:red)"
+                [env_var]
+                variable="STARSHIP_UNSET_VARIABLE"
+                format="[$env_value](bg:green)"
+                [character]
+                format="[>](bg:blue)"
+        });
+
+        let expected = AnsiStrings(&[
+            Style::new().on(Color::Red).paint("os"),
+            Color::Red.on(Color::Blue).paint("\u{e0b0}"),
+            Style::new().on(Color::Blue).paint(">"),
+            Color::Blue.paint("\u{e0b0}"),
+        ])
+        .to_string();
+        let actual = get_prompt(context);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn powerline_separators_start_and_thin_symbols() {
+        let context = default_context().set_config(toml::toml! {
+                add_newline=false
+                format="$os$character"
+                [separator]
+                mode="powerline"
+                thin_symbol="|"
+                start_symbol="("
+                end_symbol=")"
+                [os]
+                disabled=false
+                format="[os](bg:red)"
+                [character]
+                format="[>](bg:red)"
+        });
+
+        let expected = AnsiStrings(&[
+            Color::Red.paint("("),
+            Style::new().on(Color::Red).paint("os"),
+            Style::new().on(Color::Red).paint("|"),
+            Style::new().on(Color::Red).paint(">"),
+            Color::Red.paint(")"),
+        ])
+        .to_string();
+        let actual = get_prompt(context);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn accessibility_mode_textual_modules() {
+        let mut context = default_context().set_config(toml::toml! {
+                add_newline=false
+                accessibility=true
+                format="$shlvl$character"
+                [shlvl]
+                disabled=false
+                format="[$symbol$shlvl](bold) "
+                [character]
+                success_symbol="[❯](green)"
+        });
+        context.env.insert("SHLVL", String::from("3"));
+        context.properties.status_code = Some(String::from("1"));
+
+        let expected = format!(
+            "{} {} ",
+            Style::new().bold().paint("shlvl 3"),
+            Color::Red.bold().paint("error >")
+        );
+        let actual = get_prompt(context);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn accessibility_mode_a11y_format() {
+        let a11y_prompt = |accessibility: bool| {
+            let mut config = toml::toml! {
+                add_newline=false
+                format="$env_var"
+                [env_var]
+                variable="STARSHIP_A11Y_TEST"
+                format="[\u{e0b6}$env_value](bold)"
+                a11y_format="[$env_value is set](italic)"
+            };
+            config.insert(String::from("accessibility"), accessibility.into());
+            let mut context = default_context().set_config(config);
+            context
+                .env
+                .insert("STARSHIP_A11Y_TEST", String::from("value"));
+            get_prompt(context)
+        };
+
+        let expected = Style::new().italic().paint("value is set").to_string();
+        assert_eq!(expected, a11y_prompt(true));
+        let expected = Style::new().bold().paint("\u{e0b6}value").to_string();
+        assert_eq!(expected, a11y_prompt(false));
+    }
+
+    #[test]
+    fn accessibility_mode_keeps_user_config() {
+        let mut context = default_context().set_config(toml::toml! {
+                add_newline=false
+                accessibility=true
+                format="$shlvl${env_var.STARSHIP_A11Y_TEST}"
+                [shlvl]
+                disabled=false
+                symbol="level "
+                format="[$symbol$shlvl](bold) "
+                [env_var.STARSHIP_A11Y_TEST]
+                format="[\u{e0b6}$env_value](bold)"
+                a11y_format="[$env_value is set](italic)"
+        });
+        context.env.insert("SHLVL", String::from("3"));
+        context
+            .env
+            .insert("STARSHIP_A11Y_TEST", String::from("value"));
+
+        let expected = format!(
+            "{} {}",
+            Style::new().bold().paint("level 3"),
+            Style::new().italic().paint("value is set")
+        );
+        let actual = get_prompt(context);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn layout_hides_modules_below_min_width() {
+        let mut context = default_context().set_config(toml::toml! {
+                add_newline=false
+                format="$os$character"
+                [character]
+                format="> "
+                [os]
+                disabled=false
+                format="os "
+                [layout.os]
+                min_width=100
+        });
+        context.width = 80;
+
+        let expected = String::from("> ");
+        let actual = get_prompt(context);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn layout_switches_to_compact_format() {
+        let mut context = default_context().set_config(toml::toml! {
+                add_newline=false
+                format="$os$character"
+                [character]
+                format="> "
+                [os]
+                disabled=false
+                format="operating system "
+                [layout.os]
+                compact_format="os "
+        });
+        context.width = 10;
+
+        let expected = String::from("os > ");
+        let actual = get_prompt(context);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn layout_hides_lowest_priority_first() {
+        let mut context = default_context().set_config(toml::toml! {
+                add_newline=false
+                format="$os$env_var$line_break$character"
+                [character]
+                format="> "
+                [os]
+                disabled=false
+                format="operating system "
+                [env_var.TEST_VAR]
+                format="$env_value "
+                [layout.env_var]
+                priority=2
+                [layout.os]
+                priority=1
+                compact_format="os "
+        });
+        context.env.insert("TEST_VAR", String::from("variable"));
+        context.width = 11;
+
+        // Compacting `os` is not enough, so it is hidden
+        let expected = String::from("variable \n> ");
+        let actual = get_prompt(context);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn layout_keeps_modules_on_lines_which_fit() {
+        let mut context = default_context().set_config(toml::toml! {
+                add_newline=false
+                format="$os$line_break$env_var$character"
+                [character]
+                format="> "
+                [os]
+                disabled=false
+                format="os "
+                [env_var.TEST_VAR]
+                format="$env_value "
+                [layout.env_var]
+                priority=2
+                [layout.os]
+                priority=1
+                compact_format="o "
+        });
+        context.env.insert("TEST_VAR", String::from("variable"));
+        context.width = 8;
+
+        // Only the second line is too wide, so `os` keeps its format despite its lower priority
+        let expected = String::from("os \n> ");
+        let actual = get_prompt(context);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn bash_right_prompt() {
+        let mut context = default_context().set_config(toml::toml! {
+                add_newline=false
+                format="$line_break$character"
+                right_format="$os"
+                [character]
+                format="> "
+                [os]
+                disabled=false
+                format="right"
+        });
+        context.shell = Shell::Bash;
+        context.width = 40;
+
+        let expected = String::from("\n\\[\x1b7\x1b[999C\x1b[4Dright\x1b8\\]> ");
+        let actual = get_prompt(context);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn fill_leaves_space_for_right_prompt() {
+        let mut context = default_context().set_config(toml::toml! {
+                add_newline=false
+                format="a$fill$line_break$fill$character"
+                right_format="$os"
+                [character]
+                format="> "
+                [fill]
+                symbol="."
+                style=""
+                [os]
+                disabled=false
+                format="right"
+        });
+        context.width = 15;
+
+        let expected = format!("a{}\n{}> ", ".".repeat(14), ".".repeat(8));
+        let actual = get_prompt(context);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn fill_ignores_right_prompt_when_it_does_not_fit() {
+        let mut context = default_context().set_config(toml::toml! {
+                add_newline=false
+                format="$fill$character"
+                right_format="$os"
+                [character]
+                format="> "
+                [fill]
+                symbol="."
+                style=""
+                [os]
+                disabled=false
+                format="right"
+        });
+        context.width = 6;
+
+        let expected = String::from("....> ");
+        let actual = get_prompt(context);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn right_prompt_reuses_measured_prompt() {
+        let cache = tempfile::tempdir().unwrap();
+        let config = |right: &str| {
+            toml::toml! {
+                add_newline=false
+                format="$fill$character"
+                right_format="$os"
+                cache_right_prompt=true
+                [character]
+                format="> "
+                [fill]
+                symbol="."
+                style=""
+                [os]
+                disabled=false
+                format=right
+            }
+        };
+        let context = |target: Target, right: &str| {
+            let mut context = default_context().set_config(config(right));
+            context.shell = Shell::Zsh;
+            context.target = target;
+            context.width = 10;
+            context
+                .env
+                .insert("STARSHIP_CACHE", cache.path().to_string_lossy().to_string());
+            context
+                .env
+                .insert("STARSHIP_SESSION_KEY", String::from("1"));
+            context
+        };
+
+        assert_eq!("...> ", get_prompt(context(Target::Main, "right")));
+        // The right prompt measured by the main prompt is used once
+        assert_eq!("right", get_prompt(context(Target::Right, "other")));
+        assert_eq!("other", get_prompt(context(Target::Right, "other")));
+        cache.close().unwrap();
+    }
+
+    #[test]
+    fn right_prompt_is_not_cached_by_default() {
+        let cache = tempfile::tempdir().unwrap();
+        let context = |target: Target| {
+            let mut context = default_context().set_config(toml::toml! {
+                add_newline=false
+                format="$fill$character"
+                right_format="$os"
+                [character]
+                format="> "
+                [fill]
+                symbol="."
+                style=""
+                [os]
+                disabled=false
+                format="right"
+            });
+            context.shell = Shell::Zsh;
+            context.target = target;
+            context.width = 10;
+            context
+                .env
+                .insert("STARSHIP_CACHE", cache.path().to_string_lossy().to_string());
+            context
+                .env
+                .insert("STARSHIP_SESSION_KEY", String::from("1"));
+            context
+        };
+
+        assert_eq!("...> ", get_prompt(context(Target::Main)));
+        assert!(!cache.path().join("session_1.right").exists());
+        assert_eq!("right", get_prompt(context(Target::Right)));
+        cache.close().unwrap();
+    }
+
+    #[test]
+    fn bash_right_prompt_too_wide() {
+        let mut context = default_context().set_config(toml::toml! {
+                add_newline=false
+                format="$character"
+                right_format="$os"
+                [character]
+                format="> "
+                [os]
+                disabled=false
+                format="right"
+        });
+        context.shell = Shell::Bash;
+        context.width = 7;
+
+        let expected = String::from("> ");
+        let actual = get_prompt(context);
+        assert_eq!(expected, actual);
+    }
+
     #[test]
     fn custom_prompt() {
         let mut context = default_context().set_config(toml::toml! {
@@ -569,6 +1422,21 @@ mod test {
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn transient_prompt() {
+        let mut context = default_context().set_config(toml::toml! {
+                format="$directory$character"
+                transient_format="$character"
+                [character]
+                format="❯ "
+        });
+        context.target = Target::Transient;
+
+        let expected = String::from("❯ "); // should not add a newline
+        let actual = get_prompt(context);
+        assert_eq!(expected, actual);
+    }
+
     #[test]
     fn preset_list_returns_one_or_more_items() {
         assert!(preset_list().trim().split('\n').count() > 0);
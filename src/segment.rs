@@ -35,14 +35,14 @@ pub struct FillSegment {
 
 impl FillSegment {
     // Returns the AnsiString of the segment value, not including its prefix and suffix
-    pub fn ansi_string(&self, width: Option<usize>) -> AnsiString {
+    pub fn ansi_string(&self, width: Option<usize>, ambiguous_wide: bool) -> AnsiString {
         let s = match width {
             Some(w) => self
                 .value
                 .graphemes(true)
                 .cycle()
                 .scan(0usize, |len, g| {
-                    *len += Grapheme(g).width();
+                    *len += Grapheme(g).width_with(ambiguous_wide);
                     if *len <= w {
                         Some(g)
                     } else {
@@ -82,10 +82,26 @@ mod fill_seg_tests {
                 value: String::from(*text),
                 style: Some(style),
             };
-            let actual = f.ansi_string(Some(width));
+            let actual = f.ansi_string(Some(width), false);
             assert_eq!(style.paint(*expected), actual);
         }
     }
+
+    #[test]
+    fn ansi_string_ambiguous_width() {
+        let f = FillSegment {
+            value: String::from("\u{2500}"),
+            style: None,
+        };
+        assert_eq!(
+            f.ansi_string(Some(6), false).to_string(),
+            "\u{2500}".repeat(6)
+        );
+        assert_eq!(
+            f.ansi_string(Some(6), true).to_string(),
+            "\u{2500}".repeat(3)
+        );
+    }
 }
 
 /// A segment is a styled text chunk ready for printing.
@@ -94,6 +110,8 @@ pub enum Segment {
     Text(TextSegment),
     Fill(FillSegment),
     LineTerm,
+    /// Marks where the segments of a module start, for the separators between modules
+    ModuleStart,
 }
 
 impl Segment {
@@ -130,7 +148,37 @@ impl Segment {
         match self {
             Self::Fill(fs) => fs.style,
             Self::Text(ts) => ts.style,
-            Self::LineTerm => None,
+            Self::LineTerm | Self::ModuleStart => None,
+        }
+    }
+
+    /// Replaces the style of the segment, keeping segments without a style as they are
+    pub fn map_style<F: Fn(Style) -> Style>(&mut self, f: F) {
+        match self {
+            Self::Fill(FillSegment { style, .. }) | Self::Text(TextSegment { style, .. }) => {
+                *style = style.map(f);
+            }
+            Self::LineTerm | Self::ModuleStart => {}
+        }
+    }
+
+    /// Removes pictographs like emoji and Nerd Font glyphs from the text of the segment, which
+    /// screen readers read out as noise
+    pub fn strip_decorative_symbols(&mut self) {
+        if let Self::Text(TextSegment { value, .. }) = self {
+            let mut stripped = String::with_capacity(value.len());
+            let mut removed = false;
+            for c in value.chars() {
+                if is_decorative_symbol(c) {
+                    removed = true;
+                } else if c == ' ' && removed && (stripped.is_empty() || stripped.ends_with(' ')) {
+                    // Drop the space which separated the symbol from the text
+                } else {
+                    stripped.push(c);
+                    removed = false;
+                }
+            }
+            *value = stripped;
         }
     }
 
@@ -146,7 +194,7 @@ impl Segment {
                     ts.style = style
                 }
             }
-            Self::LineTerm => {}
+            Self::LineTerm | Self::ModuleStart => {}
         }
     }
 
@@ -155,23 +203,29 @@ impl Segment {
             Self::Fill(fs) => &fs.value,
             Self::Text(ts) => &ts.value,
             Self::LineTerm => LINE_TERMINATOR_STRING,
+            Self::ModuleStart => "",
         }
     }
 
     // Returns the AnsiString of the segment value, not including its prefix and suffix
     pub fn ansi_string(&self) -> AnsiString {
         match self {
-            Self::Fill(fs) => fs.ansi_string(None),
+            Self::Fill(fs) => fs.ansi_string(None, false),
             Self::Text(ts) => ts.ansi_string(),
             Self::LineTerm => AnsiString::from(LINE_TERMINATOR_STRING),
+            Self::ModuleStart => AnsiString::from(""),
         }
     }
 
     pub fn width_graphemes(&self) -> usize {
+        self.width_graphemes_with(false)
+    }
+
+    pub fn width_graphemes_with(&self, ambiguous_wide: bool) -> usize {
         match self {
-            Self::Fill(fs) => fs.value.width_graphemes(),
-            Self::Text(ts) => ts.value.width_graphemes(),
-            Self::LineTerm => 0,
+            Self::Fill(fs) => fs.value.width_graphemes_with(ambiguous_wide),
+            Self::Text(ts) => ts.value.width_graphemes_with(ambiguous_wide),
+            Self::LineTerm | Self::ModuleStart => 0,
         }
     }
 }
@@ -179,8 +233,53 @@ impl Segment {
 const LINE_TERMINATOR: char = '\n';
 const LINE_TERMINATOR_STRING: &str = "\n";
 
+/// Whether the char is a pictograph or part of one, rather than text or punctuation
+fn is_decorative_symbol(c: char) -> bool {
+    matches!(c,
+        // Box drawing, block elements and geometric shapes
+        '\u{2500}'..='\u{25FF}'
+        // Miscellaneous symbols and dingbats
+        | '\u{2600}'..='\u{27BF}'
+        // Miscellaneous technical symbols and arrows
+        | '\u{2300}'..='\u{23FF}'
+        | '\u{2B00}'..='\u{2BFF}'
+        // Joiners and selectors of emoji sequences
+        | '\u{200D}'
+        | '\u{20E3}'
+        | '\u{FE0E}'..='\u{FE0F}'
+        // Private use areas, where Nerd Fonts put their glyphs
+        | '\u{E000}'..='\u{F8FF}'
+        | '\u{F0000}'..='\u{10FFFF}'
+        // Emoji and other pictographs
+        | '\u{1F000}'..='\u{1FAFF}'
+    )
+}
+
 impl fmt::Display for Segment {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}", self.ansi_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Segment;
+
+    #[test]
+    fn strip_decorative_symbols() {
+        let inputs = [
+            ("\u{1f980} rust", "rust"),
+            ("on \u{e0a0} main", "on main"),
+            ("a \u{2591}\u{2592}\u{2593} b", "a b"),
+            ("v1 \u{276f} ", "v1 "),
+            ("\u{1f1e9}\u{1f1ea}\u{fe0f}", ""),
+            ("~/src [!]", "~/src [!]"),
+        ];
+
+        for (text, expected) in inputs {
+            let mut segments = Segment::from_text(None, text);
+            segments[0].strip_decorative_symbols();
+            assert_eq!(segments[0].value(), expected);
+        }
+    }
+}
@@ -119,6 +119,16 @@ impl<'a> ModuleRenderer<'a> {
         self
     }
 
+    pub fn last_command<T>(mut self, command: T) -> Self
+    where
+        T: Into<String>,
+    {
+        self.context
+            .env
+            .insert("STARSHIP_LAST_COMMAND", command.into());
+        self
+    }
+
     pub fn keymap<T>(mut self, keymap: T) -> Self
     where
         T: Into<String>,
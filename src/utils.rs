@@ -2,7 +2,7 @@ use process_control::{ChildExt, Control};
 use std::ffi::OsStr;
 use std::fmt::Debug;
 use std::fs::read_to_string;
-use std::io::{Error, ErrorKind, Result};
+use std::io::{BufRead, BufReader, Error, ErrorKind, Result};
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use std::time::{Duration, Instant};
@@ -598,11 +598,70 @@ pub fn exec_timeout(cmd: &mut Command, time_limit: Duration) -> Option<CommandOu
     }
 }
 
-// Render the time into a nice human-readable string
-pub fn render_time(raw_millis: u128, show_millis: bool) -> String {
+/// Counts the lines a command writes to stdout within `time_limit`
+///
+/// Unlike [`exec_timeout`], the output read so far is kept when the command takes too long, in
+/// which case it's terminated and the count is returned as incomplete with `false`.
+pub fn count_lines_timeout(cmd: &mut Command, time_limit: Duration) -> Option<(usize, bool)> {
+    let start = Instant::now();
+    let mut process = match cmd.stdout(Stdio::piped()).stderr(Stdio::null()).spawn() {
+        Ok(process) => process,
+        Err(error) => {
+            log::info!("Unable to run {:?}, {:?}", cmd.get_program(), error);
+            return None;
+        }
+    };
+
+    let stdout = process.stdout.take()?;
+    let reader = std::thread::spawn(move || {
+        BufReader::new(stdout)
+            .split(b'\n')
+            .take_while(std::result::Result::is_ok)
+            .count()
+    });
+
+    let status = match process
+        .controlled()
+        .time_limit(time_limit)
+        .terminate_for_timeout()
+        .wait()
+    {
+        Ok(status) => status,
+        Err(error) => {
+            log::info!(
+                "Executing command {:?} failed by: {:?}",
+                cmd.get_program(),
+                error
+            );
+            return None;
+        }
+    };
+    // The pipe is closed once the command exits or was terminated
+    let count = reader.join().ok()?;
+
+    match status {
+        Some(status) if !status.success() => None,
+        Some(_) => Some((count, true)),
+        None => {
+            log::debug!(
+                "Counted {} lines of {:?} before timing out after {:?}",
+                count,
+                cmd.get_program(),
+                start.elapsed()
+            );
+            Some((count, false))
+        }
+    }
+}
+
+/// Render a time in milliseconds with the units of days, hours, minutes, seconds and
+/// milliseconds of the locale
+pub fn render_time(raw_millis: u128, show_millis: bool, units: [&str; 5]) -> String {
+    let [day, hour, minute, second, millisecond] = units;
+
     // Make sure it renders something if the time equals zero instead of an empty string
     if raw_millis == 0 {
-        return "0ms".into();
+        return format!("0{millisecond}");
     }
 
     // Calculate a simple breakdown into days/hours/minutes/seconds/milliseconds
@@ -612,7 +671,7 @@ pub fn render_time(raw_millis: u128, show_millis: bool) -> String {
     let (hours, days) = (raw_hours % 24, raw_hours / 24);
 
     let components = [days, hours, minutes, seconds];
-    let suffixes = ["d", "h", "m", "s"];
+    let suffixes = [day, hour, minute, second];
 
     let mut rendered_components: Vec<String> = components
         .iter()
@@ -620,7 +679,7 @@ pub fn render_time(raw_millis: u128, show_millis: bool) -> String {
         .map(render_time_component)
         .collect();
     if show_millis || raw_millis < 1000 {
-        rendered_components.push(render_time_component((&millis, &"ms")));
+        rendered_components.push(render_time_component((&millis, &millisecond)));
     }
     rendered_components.join("")
 }
@@ -633,6 +692,33 @@ fn render_time_component((component, suffix): (&u128, &&str)) -> String {
     }
 }
 
+/// Render a number like `-12345.6` with the decimal and group separators of the locale, grouping
+/// the digits of the whole part by thousands
+pub fn render_number(number: &str, separators: [&str; 2]) -> String {
+    let [decimal, group] = separators;
+    let (sign, unsigned) = match number.strip_prefix('-') {
+        Some(unsigned) => ("-", unsigned),
+        None => ("", number),
+    };
+    let (whole, fraction) = match unsigned.split_once('.') {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (unsigned, None),
+    };
+
+    let mut rendered = String::from(sign);
+    for (i, digit) in whole.chars().enumerate() {
+        if i > 0 && (whole.len() - i) % 3 == 0 {
+            rendered.push_str(group);
+        }
+        rendered.push(digit);
+    }
+    if let Some(fraction) = fraction {
+        rendered.push_str(decimal);
+        rendered.push_str(fraction);
+    }
+    rendered
+}
+
 pub fn home_dir() -> Option<PathBuf> {
     dirs_next::home_dir()
 }
@@ -690,29 +776,82 @@ impl PathExt for Path {
 mod tests {
     use super::*;
 
+    #[test]
+    #[cfg(not(windows))]
+    fn count_lines_of_finished_command() {
+        let mut cmd = create_command("sh").unwrap();
+        cmd.args(["-c", "printf 'a\\nb\\nc\\n'"]);
+
+        assert_eq!(
+            count_lines_timeout(&mut cmd, Duration::from_secs(10)),
+            Some((3, true))
+        );
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn count_lines_until_timeout() {
+        let mut cmd = create_command("sh").unwrap();
+        cmd.args(["-c", "echo a; echo b; exec sleep 10"]);
+
+        assert_eq!(
+            count_lines_timeout(&mut cmd, Duration::from_millis(500)),
+            Some((2, false))
+        );
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn count_lines_of_failed_command() {
+        let mut cmd = create_command("sh").unwrap();
+        cmd.args(["-c", "echo a; exit 1"]);
+
+        assert_eq!(count_lines_timeout(&mut cmd, Duration::from_secs(10)), None);
+    }
+
+    #[test]
+    fn render_number_with_separators() {
+        assert_eq!(render_number("7", [".", ","]), "7");
+        assert_eq!(render_number("1234", [".", ","]), "1,234");
+        assert_eq!(render_number("-1234567", [",", "."]), "-1.234.567");
+        assert_eq!(
+            render_number("12345.25", [",", "\u{202f}"]),
+            "12\u{202f}345,25"
+        );
+        assert_eq!(render_number("999.5", [",", "."]), "999,5");
+    }
+
+    const UNITS: [&str; 5] = ["d", "h", "m", "s", "ms"];
+
     #[test]
     fn test_0ms() {
-        assert_eq!(render_time(0_u128, true), "0ms")
+        assert_eq!(render_time(0_u128, true, UNITS), "0ms")
     }
     #[test]
     fn test_500ms() {
-        assert_eq!(render_time(500_u128, true), "500ms")
+        assert_eq!(render_time(500_u128, true, UNITS), "500ms")
     }
     #[test]
     fn test_10s() {
-        assert_eq!(render_time(10_000_u128, true), "10s")
+        assert_eq!(render_time(10_000_u128, true, UNITS), "10s")
     }
     #[test]
     fn test_90s() {
-        assert_eq!(render_time(90_000_u128, true), "1m30s")
+        assert_eq!(render_time(90_000_u128, true, UNITS), "1m30s")
     }
     #[test]
     fn test_10110s() {
-        assert_eq!(render_time(10_110_000_u128, true), "2h48m30s")
+        assert_eq!(render_time(10_110_000_u128, true, UNITS), "2h48m30s")
     }
     #[test]
     fn test_1d() {
-        assert_eq!(render_time(86_400_000_u128, true), "1d")
+        assert_eq!(render_time(86_400_000_u128, true, UNITS), "1d")
+    }
+    #[test]
+    fn test_units() {
+        let units = ["j", "h", "min", "s", "ms"];
+        assert_eq!(render_time(0_u128, false, units), "0ms");
+        assert_eq!(render_time(90_061_000_u128, false, units), "1j1h1min1s");
     }
 
     #[test]